@@ -0,0 +1,6 @@
+pub mod batch_remove;
+pub mod clone;
+pub mod clone_all;
+pub mod import;
+pub mod pull;
+pub mod tags_io;
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result, anyhow};
+use std::fmt;
+use std::fs;
+
+use crate::config::Config;
+use crate::storage::Storage;
+
+/// What happened to one path of a batch removal
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoveOutcome {
+    Removed,
+    // Only in dry runs
+    WouldRemove,
+    NotFound,
+    Error(String),
+}
+
+/// One line of the batch removal summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoveReport {
+    pub path: String,
+    pub outcome: RemoveOutcome,
+}
+
+impl fmt::Display for RemoveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            RemoveOutcome::Removed => write!(f, "Removed: {}", self.path),
+            RemoveOutcome::WouldRemove => write!(f, "Would remove: {}", self.path),
+            RemoveOutcome::NotFound => write!(f, "Not found: {}", self.path),
+            RemoveOutcome::Error(reason) => write!(f, "Error: {} - {}", self.path, reason),
+        }
+    }
+}
+
+/// Reads one path per line, skipping blank lines and `#` comments
+pub fn parse_path_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Deletes each path from storage, or only checks that it is tracked with `dry_run`
+pub fn remove_paths(storage: &mut Storage, paths: &[String], dry_run: bool) -> Vec<RemoveReport> {
+    paths
+        .iter()
+        .map(|path| {
+            let outcome = if dry_run {
+                match storage.get_repo(path) {
+                    Ok(Some(_)) => RemoveOutcome::WouldRemove,
+                    Ok(None) => RemoveOutcome::NotFound,
+                    Err(e) => RemoveOutcome::Error(e.to_string()),
+                }
+            } else {
+                match storage.delete_repo(path) {
+                    Ok(true) => RemoveOutcome::Removed,
+                    Ok(false) => RemoveOutcome::NotFound,
+                    Err(e) => RemoveOutcome::Error(e.to_string()),
+                }
+            };
+            RemoveReport {
+                path: path.clone(),
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// Removes the repos listed in a file and prints a line per path. Fails if any
+/// removal failed, which includes untracked paths unless `ignore_not_found` is set
+pub fn run(config: &Config, file: &str, dry_run: bool, ignore_not_found: bool) -> Result<()> {
+    let content = fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+    let paths = parse_path_list(&content);
+
+    let mut storage = Storage::new(config)?;
+    let reports = remove_paths(&mut storage, &paths, dry_run);
+    for report in &reports {
+        println!("{}", report);
+    }
+
+    let removed = reports
+        .iter()
+        .filter(|report| report.outcome == RemoveOutcome::Removed)
+        .count();
+    if removed > 0 {
+        storage.save(config)?;
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|report| match report.outcome {
+            RemoveOutcome::NotFound => !ignore_not_found,
+            RemoveOutcome::Error(_) => true,
+            _ => false,
+        })
+        .count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} removals failed", failed, reports.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_batch_remove {
+    use super::*;
+    use crate::storage::RepoAccess;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_path_list() {
+        let paths =
+            parse_path_list("# old repos\n/repos/a\n\n  /repos/b  \n  # indented comment\n");
+        assert_eq!(paths, vec!["/repos/a", "/repos/b"]);
+    }
+
+    #[test]
+    fn test_remove_paths_from_file() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("old-repos.txt");
+        fs::write(&file, "/repos/a\n# keep b\n/repos/missing\n\n/repos/c\n").unwrap();
+
+        let mut storage = Storage::default();
+        for path in ["/repos/a", "/repos/b", "/repos/c"] {
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(vec![]));
+        }
+        let paths = parse_path_list(&fs::read_to_string(&file).unwrap());
+
+        let dry_run: Vec<String> = remove_paths(&mut storage, &paths, true)
+            .iter()
+            .map(|report| report.to_string())
+            .collect();
+        assert_eq!(
+            dry_run,
+            vec![
+                "Would remove: /repos/a",
+                "Not found: /repos/missing",
+                "Would remove: /repos/c"
+            ]
+        );
+        assert_eq!(storage.repos.len(), 3);
+
+        let reports = remove_paths(&mut storage, &paths, false);
+        let lines: Vec<String> = reports.iter().map(|report| report.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Removed: /repos/a",
+                "Not found: /repos/missing",
+                "Removed: /repos/c"
+            ]
+        );
+        assert_eq!(storage.repos.keys().collect::<Vec<_>>(), vec!["/repos/b"]);
+        assert_eq!(
+            RemoveReport {
+                path: "/x".to_string(),
+                outcome: RemoveOutcome::Error("boom".to_string())
+            }
+            .to_string(),
+            "Error: /x - boom"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{Result, anyhow};
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::commands::clone_all::git_clone;
+use crate::config::Config;
+use crate::storage::{AddResult, Storage};
+use crate::util::git;
+use crate::util::infer_name_from_remote_url;
+
+/// Where `clone` puts a repo: `path` if given, otherwise a directory named after
+/// the repo in `projects_dir`, or in the current directory without one
+pub fn clone_target(url: &str, path: Option<&str>, projects_dir: Option<&str>) -> Result<PathBuf> {
+    if let Some(path) = path {
+        return Ok(PathBuf::from(Storage::to_absolute_path(path)?));
+    }
+
+    let name = infer_name_from_remote_url(url)
+        .ok_or_else(|| anyhow!("Can't tell the repo name from {}, pass --path", url))?;
+    let dir = match projects_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => env::current_dir()?,
+    };
+    Ok(dir.join(name))
+}
+
+/// Makes a local repo path absolute, so the `origin` git records for the clone
+/// still works from inside it. Anything else is returned as it is
+fn absolute_local_url(url: &str) -> Result<String> {
+    if Path::new(url).exists() {
+        Storage::to_absolute_path(url)
+    } else {
+        Ok(url.to_string())
+    }
+}
+
+/// Clones `url` into `target` and tracks the clone with `tags` and the `origin`
+/// URL that git recorded. Nothing is tracked if the clone fails
+pub fn clone_and_add(
+    storage: &mut Storage,
+    url: &str,
+    target: &Path,
+    tags: Vec<String>,
+) -> Result<AddResult> {
+    let url = absolute_local_url(url)?;
+    git_clone(&url, target).map_err(|e| anyhow!("Failed to clone {}: {}", url, e))?;
+
+    let result = storage.add_repo(&target.to_string_lossy(), tags)?;
+    if let Some(repo_access) = storage.repos.get_mut(&result.path) {
+        repo_access.remote_url = git::remote_origin_url(&result.path);
+    }
+    Ok(result)
+}
+
+pub fn run(config: &Config, url: &str, path: Option<&str>, tags: Vec<String>) -> Result<()> {
+    let target = clone_target(url, path, config.default_projects_dir.as_deref())?;
+    let mut storage = Storage::new(config)?;
+    let result = clone_and_add(&mut storage, url, &target, config.merge_default_tags(tags))?;
+    storage.save(config)?;
+
+    println!("Cloned {} into {}", url, result.path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_clone {
+    use super::*;
+    use crate::util::find_in_path;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_clone_target() {
+        let url = "git@github.com:tizee/mangit.git";
+        assert_eq!(
+            clone_target(url, None, Some("/src")).unwrap(),
+            PathBuf::from("/src/mangit")
+        );
+        assert_eq!(
+            clone_target(url, Some("/elsewhere/mg"), Some("/src")).unwrap(),
+            PathBuf::from("/elsewhere/mg")
+        );
+        assert_eq!(
+            clone_target("/srv/git/notes.git", None, None).unwrap(),
+            env::current_dir().unwrap().join("notes")
+        );
+        assert!(clone_target("https://github.com", None, Some("/src")).is_err());
+    }
+
+    #[test]
+    fn test_absolute_local_url() {
+        assert_eq!(
+            absolute_local_url(".").unwrap(),
+            env::current_dir().unwrap().to_string_lossy()
+        );
+        for url in [
+            "git@github.com:tizee/mangit.git",
+            "https://github.com/tizee/mangit.git",
+            "./not/there.git",
+        ] {
+            assert_eq!(absolute_local_url(url).unwrap(), url);
+        }
+    }
+
+    #[test]
+    fn test_clone_and_add() {
+        if find_in_path("git").is_none() {
+            eprintln!("git not installed, skipping");
+            return;
+        }
+        let temp_dir = tempdir().unwrap();
+        let bare = temp_dir.path().join("origin/project.git");
+        let status = Command::new("git")
+            .args(["init", "--quiet", "--bare"])
+            .arg(&bare)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let url = bare.to_string_lossy().to_string();
+
+        let mut storage = Storage::default();
+        let target = clone_target(&url, None, Some(&temp_dir.path().to_string_lossy())).unwrap();
+        assert_eq!(target, temp_dir.path().join("project"));
+
+        let result = clone_and_add(&mut storage, &url, &target, vec!["work".to_string()]).unwrap();
+        assert!(result.is_new);
+        assert!(target.join(".git").is_dir());
+        let repo_access = &storage.repos[&result.path];
+        assert_eq!(repo_access.tags, vec!["work"]);
+        // Read back from the clone
+        assert_eq!(repo_access.remote_url.as_deref(), Some(url.as_str()));
+        assert_eq!(
+            repo_access.remote_url,
+            git::remote_origin_url(&target.to_string_lossy())
+        );
+
+        // A failed clone tracks nothing
+        let missing = temp_dir.path().join("origin/missing.git");
+        let target = temp_dir.path().join("missing");
+        assert!(clone_and_add(&mut storage, &missing.to_string_lossy(), &target, vec![]).is_err());
+        assert!(!target.exists());
+        assert_eq!(storage.repos.len(), 1);
+
+        // Neither does cloning into an existing directory
+        let err = clone_and_add(&mut storage, &url, temp_dir.path(), vec![]).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert_eq!(storage.repos.len(), 1);
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::storage::Storage;
+use crate::util::git;
+use crate::util::parallel_map;
+
+/// One repo to clone: where it was tracked, where it comes from and where it goes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneJob {
+    pub source_path: String,
+    pub url: String,
+    pub target: PathBuf,
+}
+
+/// Clones every tracked repo with a known remote into `to_dir/<name>` and tracks
+/// the clones instead of the old paths, printing each move
+pub fn run(config: &Config, to_dir: &str, parallel: usize) -> Result<()> {
+    // Keep repos whose paths don't exist, those are the ones worth cloning
+    let mut storage = Storage::load_all(config)?;
+    let to_dir = Path::new(to_dir);
+
+    let (jobs, skipped) = plan_clones(&storage, to_dir);
+    for path in &skipped {
+        eprintln!("Warning: skipping {}, no remote URL recorded", path);
+    }
+
+    let results = run_clones(&jobs, parallel, |job| {
+        let result = git_clone(&job.url, &job.target);
+        match &result {
+            Ok(()) => println!("Cloned {} into {}", job.url, job.target.display()),
+            Err(e) => eprintln!("Failed to clone {}: {}", job.url, e),
+        }
+        result
+    });
+
+    let mut cloned = 0;
+    for (job, result) in jobs.iter().zip(&results) {
+        if result.is_ok() && track_clone(&mut storage, job) {
+            println!(
+                "Now tracking {} as {}",
+                job.source_path,
+                job.target.display()
+            );
+            cloned += 1;
+        }
+    }
+    storage.save(config)?;
+
+    println!(
+        "{} repos cloned, {} failed, {} skipped (no remote URL)",
+        cloned,
+        jobs.len() - cloned,
+        skipped.len()
+    );
+    Ok(())
+}
+
+/// Builds a clone job for every repo with a remote URL. Returns the jobs and the
+/// paths of the repos without a remote, both sorted by path
+pub fn plan_clones(storage: &Storage, to_dir: &Path) -> (Vec<CloneJob>, Vec<String>) {
+    let mut paths: Vec<&String> = storage.repos.keys().collect();
+    paths.sort();
+
+    let mut jobs = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let repo_access = &storage.repos[path];
+        match &repo_access.remote_url {
+            Some(url) => jobs.push(CloneJob {
+                source_path: path.clone(),
+                url: url.clone(),
+                target: to_dir.join(repo_access.display_name(path)),
+            }),
+            None => skipped.push(path.clone()),
+        }
+    }
+
+    (jobs, skipped)
+}
+
+/// Moves a repo's entry from its old path to the finished clone and records the
+/// clone's `origin` URL as it was set up by git. False if the repo isn't tracked
+pub fn track_clone(storage: &mut Storage, job: &CloneJob) -> bool {
+    let Some(mut repo_access) = storage.repos.remove(&job.source_path) else {
+        return false;
+    };
+    let target = job.target.to_string_lossy().to_string();
+    repo_access.remote_url = git::remote_origin_url(&target).or_else(|| Some(job.url.clone()));
+    storage.repos.insert(target, repo_access);
+    true
+}
+
+/// Runs `clone` for every job on up to `parallel` threads, results in job order
+pub fn run_clones<F>(jobs: &[CloneJob], parallel: usize, clone: F) -> Vec<Result<()>>
+where
+    F: Fn(&CloneJob) -> Result<()> + Sync,
+{
+    parallel_map(jobs, parallel, clone)
+}
+
+/// Runs `git clone` of `url` into `target`, which must not exist yet
+pub fn git_clone(url: &str, target: &Path) -> Result<()> {
+    if target.exists() {
+        return Err(anyhow!("{} already exists", target.display()));
+    }
+
+    let output = Command::new("git")
+        .arg("clone")
+        .arg("--quiet")
+        .arg(url)
+        .arg(target)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("{}", stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_clone_all {
+    use super::*;
+    use crate::storage::RepoAccess;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    fn storage_with_remotes() -> Storage {
+        let mut storage = Storage::default();
+        for (path, url) in [
+            ("/old/mangit", Some("https://github.com/tizee/mangit.git")),
+            ("/old/notes", None),
+            ("/old/dotfiles", Some("git@github.com:tizee/dotfiles.git")),
+        ] {
+            let mut repo_access = RepoAccess::new(vec![]);
+            repo_access.remote_url = url.map(str::to_string);
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+        storage
+    }
+
+    #[test]
+    fn test_plan_clones() {
+        let storage = storage_with_remotes();
+        let (jobs, skipped) = plan_clones(&storage, Path::new("/new"));
+
+        assert_eq!(
+            jobs,
+            vec![
+                CloneJob {
+                    source_path: "/old/dotfiles".to_string(),
+                    url: "git@github.com:tizee/dotfiles.git".to_string(),
+                    target: PathBuf::from("/new/dotfiles"),
+                },
+                CloneJob {
+                    source_path: "/old/mangit".to_string(),
+                    url: "https://github.com/tizee/mangit.git".to_string(),
+                    target: PathBuf::from("/new/mangit"),
+                },
+            ]
+        );
+        assert_eq!(skipped, vec!["/old/notes"]);
+    }
+
+    #[test]
+    fn test_track_clone() {
+        let mut storage = storage_with_remotes();
+        storage.repos.get_mut("/old/mangit").unwrap().name = Some("mg".to_string());
+        let (jobs, _) = plan_clones(&storage, Path::new("/new"));
+
+        assert!(track_clone(&mut storage, &jobs[1]));
+        assert!(!storage.repos.contains_key("/old/mangit"));
+        let repo_access = &storage.repos["/new/mg"];
+        assert_eq!(repo_access.name.as_deref(), Some("mg"));
+        // Not a real clone, so git has no origin to report and the URL is kept
+        assert_eq!(
+            repo_access.remote_url.as_deref(),
+            Some("https://github.com/tizee/mangit.git")
+        );
+
+        // Already moved
+        assert!(!track_clone(&mut storage, &jobs[1]));
+    }
+
+    #[test]
+    fn test_run_clones_in_parallel() {
+        let jobs: Vec<CloneJob> = (0..6)
+            .map(|i| CloneJob {
+                source_path: format!("/old/{}", i),
+                url: format!("https://example.com/{}.git", i),
+                target: PathBuf::from(format!("/new/{}", i)),
+            })
+            .collect();
+
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let cloned = Mutex::new(Vec::new());
+
+        // Stands in for `git clone`, failing for one of the repos
+        let results = run_clones(&jobs, 3, |job| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            running.fetch_sub(1, Ordering::SeqCst);
+
+            if job.url.ends_with("/4.git") {
+                return Err(anyhow!("repository not found"));
+            }
+            cloned.lock().unwrap().push(job.url.clone());
+            Ok(())
+        });
+
+        // How many overlap depends on the scheduler, but never more than asked for
+        let max_running = max_running.load(Ordering::SeqCst);
+        assert!(max_running > 1 && max_running <= 3, "{}", max_running);
+        assert_eq!(results.len(), 6);
+        assert!(results[4].is_err());
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 5);
+        assert_eq!(cloned.lock().unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Config, is_git_repo};
+use crate::storage::Storage;
+
+/// Formats `import` reads, as written by `export`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// A header row with a `path` column and an optional `tags` column (`;` separated)
+    Csv,
+    /// An array of objects with a `path` and optional `tags`
+    Json,
+}
+
+/// A repo to import. Other exported fields are ignored
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportEntry {
+    pub path: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// What happened to one entry of an import
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportOutcome {
+    Added,
+    // Already tracked, the entry brought new tags
+    Updated,
+    // Already tracked with all of the entry's tags
+    Unchanged,
+    Failed(String),
+}
+
+/// One line of the import summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportReport {
+    pub path: String,
+    pub outcome: ImportOutcome,
+    pub dry_run: bool,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.outcome, self.dry_run) {
+            (ImportOutcome::Added, false) => write!(f, "Added: {}", self.path),
+            (ImportOutcome::Added, true) => write!(f, "Would add: {}", self.path),
+            (ImportOutcome::Updated, false) => write!(f, "Updated: {}", self.path),
+            (ImportOutcome::Updated, true) => write!(f, "Would update: {}", self.path),
+            (ImportOutcome::Unchanged, _) => write!(f, "Unchanged: {}", self.path),
+            (ImportOutcome::Failed(reason), _) => {
+                write!(f, "Warning: skipping {} - {}", self.path, reason)
+            }
+        }
+    }
+}
+
+/// Parses the entries of an exported file
+pub fn parse_entries(content: &str, format: ImportFormat) -> Result<Vec<ImportEntry>> {
+    match format {
+        ImportFormat::Csv => parse_csv(content),
+        ImportFormat::Json => serde_json::from_str(content).context("Failed to parse JSON"),
+    }
+}
+
+fn parse_csv(content: &str) -> Result<Vec<ImportEntry>> {
+    let mut rows = parse_csv_rows(content)?.into_iter();
+    let header = rows.next().ok_or_else(|| anyhow!("CSV file is empty"))?;
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let path_column = column("path").ok_or_else(|| anyhow!("CSV header has no path column"))?;
+    let tags_column = column("tags");
+
+    Ok(rows
+        .filter(|row| row.iter().any(|field| !field.is_empty()))
+        .map(|row| {
+            let field = |index: usize| row.get(index).map_or("", String::as_str);
+            ImportEntry {
+                path: field(path_column).to_string(),
+                tags: tags_column
+                    .map(|index| {
+                        field(index)
+                            .split(';')
+                            .map(str::trim)
+                            .filter(|tag| !tag.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// Splits CSV into rows of fields, with quoted fields as in RFC 4180
+fn parse_csv_rows(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(anyhow!("CSV has an unterminated quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Tracks each entry's repo, merging its tags into those of an already tracked
+/// repo. With `dry_run` storage is left alone
+pub fn import_entries(
+    storage: &mut Storage,
+    entries: &[ImportEntry],
+    dry_run: bool,
+) -> Vec<ImportReport> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (path, outcome) = match import_entry(storage, entry, dry_run) {
+                Ok((abs_path, outcome)) => (abs_path, outcome),
+                Err(e) => (entry.path.clone(), ImportOutcome::Failed(e.to_string())),
+            };
+            ImportReport {
+                path,
+                outcome,
+                dry_run,
+            }
+        })
+        .collect()
+}
+
+fn import_entry(
+    storage: &mut Storage,
+    entry: &ImportEntry,
+    dry_run: bool,
+) -> Result<(String, ImportOutcome)> {
+    let abs_path = Storage::to_absolute_path(&entry.path)?;
+    if !Path::new(&abs_path).exists() {
+        return Err(anyhow!("path does not exist"));
+    }
+    if !is_git_repo(Path::new(&abs_path)) {
+        return Err(anyhow!("not a git repo"));
+    }
+
+    let (tags, outcome) = match storage.repos.get(&abs_path) {
+        None => (entry.tags.clone(), ImportOutcome::Added),
+        Some(repo_access) => {
+            let mut tags = repo_access.tags.clone();
+            for tag in &entry.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            if tags.len() == repo_access.tags.len() {
+                return Ok((abs_path, ImportOutcome::Unchanged));
+            }
+            (tags, ImportOutcome::Updated)
+        }
+    };
+
+    if !dry_run {
+        storage.add_repo(&abs_path, tags)?;
+    }
+    Ok((abs_path, outcome))
+}
+
+/// Imports the repos in an exported file, printing a line per entry and a summary.
+/// Invalid paths are warned about without stopping the import
+pub fn run(config: &Config, file: &str, format: ImportFormat, dry_run: bool) -> Result<()> {
+    let content = fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+    let entries = parse_entries(&content, format)?;
+
+    let mut storage = Storage::new(config)?;
+    let reports = import_entries(&mut storage, &entries, dry_run);
+    let count = |wanted: fn(&ImportOutcome) -> bool| {
+        reports
+            .iter()
+            .filter(|report| wanted(&report.outcome))
+            .count()
+    };
+    for report in &reports {
+        match report.outcome {
+            ImportOutcome::Failed(_) => eprintln!("{}", report),
+            _ => println!("{}", report),
+        }
+    }
+
+    let added = count(|outcome| *outcome == ImportOutcome::Added);
+    let updated = count(|outcome| *outcome == ImportOutcome::Updated);
+    if !dry_run && added + updated > 0 {
+        storage.save(config)?;
+    }
+    println!(
+        "{}{} added, {} updated, {} unchanged, {} failed",
+        if dry_run { "Dry run: " } else { "" },
+        added,
+        updated,
+        count(|outcome| *outcome == ImportOutcome::Unchanged),
+        count(|outcome| matches!(outcome, ImportOutcome::Failed(_)))
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_import {
+    use super::*;
+    use crate::storage::RepoAccess;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_csv() {
+        let entries = parse_entries(
+            "name,path,tags\r\n\"a, b\",/repos/a,\"rust;cli\"\n\n\"say \"\"hi\"\"\",/repos/b,\n",
+            ImportFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ImportEntry {
+                    path: "/repos/a".to_string(),
+                    tags: vec!["rust".to_string(), "cli".to_string()],
+                },
+                ImportEntry {
+                    path: "/repos/b".to_string(),
+                    tags: vec![],
+                },
+            ]
+        );
+
+        assert!(parse_entries("name,tags\nx,y\n", ImportFormat::Csv).is_err());
+        assert!(parse_entries("path\n\"/repos/a\n", ImportFormat::Csv).is_err());
+        let entries =
+            parse_entries(r#"[{"path": "/repos/a", "name": "a"}]"#, ImportFormat::Json).unwrap();
+        assert_eq!(entries[0].path, "/repos/a");
+        assert!(entries[0].tags.is_empty());
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let temp_dir = tempdir().unwrap();
+        let repo = |name: &str| {
+            let path = temp_dir.path().join(name);
+            fs::create_dir_all(path.join(".git")).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let (new, tracked, same) = (repo("new"), repo("tracked"), repo("same"));
+        let plain_dir = temp_dir.path().join("plain");
+        fs::create_dir_all(&plain_dir).unwrap();
+        let plain_dir = plain_dir.to_string_lossy().to_string();
+        let missing = temp_dir
+            .path()
+            .join("missing")
+            .to_string_lossy()
+            .to_string();
+
+        let mut storage = Storage::default();
+        storage
+            .repos
+            .insert(tracked.clone(), RepoAccess::new(vec!["rust".to_string()]));
+        storage
+            .repos
+            .insert(same.clone(), RepoAccess::new(vec!["go".to_string()]));
+
+        let csv = format!(
+            "path,name,tags\n{},new,web;cli\n{},tracked,cli;rust\n{},same,go\n{},plain,\n{},missing,x\n",
+            new, tracked, same, plain_dir, missing
+        );
+        let entries = parse_entries(&csv, ImportFormat::Csv).unwrap();
+
+        let outcomes = |reports: Vec<ImportReport>| -> Vec<ImportOutcome> {
+            reports.into_iter().map(|report| report.outcome).collect()
+        };
+        let expected = vec![
+            ImportOutcome::Added,
+            ImportOutcome::Updated,
+            ImportOutcome::Unchanged,
+            ImportOutcome::Failed("not a git repo".to_string()),
+            ImportOutcome::Failed("path does not exist".to_string()),
+        ];
+
+        let reports = import_entries(&mut storage, &entries, true);
+        assert_eq!(reports[0].to_string(), format!("Would add: {}", new));
+        assert_eq!(outcomes(reports), expected);
+        assert_eq!(storage.repos.len(), 2);
+        assert_eq!(storage.repos[&tracked].tags, vec!["rust"]);
+
+        assert_eq!(
+            outcomes(import_entries(&mut storage, &entries, false)),
+            expected
+        );
+        assert_eq!(storage.repos.len(), 3);
+        assert_eq!(storage.repos[&new].tags, vec!["web", "cli"]);
+        assert_eq!(storage.repos[&tracked].tags, vec!["rust", "cli"]);
+        assert_eq!(storage.repos[&same].tags, vec!["go"]);
+
+        // Importing the same file again changes nothing
+        let reports = import_entries(&mut storage, &entries, false);
+        assert!(
+            reports[..3]
+                .iter()
+                .all(|report| report.outcome == ImportOutcome::Unchanged)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::storage::Storage;
+use crate::util::git::run_git_with_timeout;
+use crate::util::parallel_map;
+
+/// How one repo's pull or fetch went
+#[derive(Debug, Clone, PartialEq)]
+pub struct PullReport {
+    pub name: String,
+    pub path: String,
+    // git's error message if it failed
+    pub result: Result<(), String>,
+}
+
+impl fmt::Display for PullReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Ok(()) => write!(f, "ok\t{}\t{}", self.name, self.path),
+            Err(reason) => write!(f, "failed\t{}\t{}\t{}", self.name, self.path, reason),
+        }
+    }
+}
+
+/// Runs `sync` on every path on up to `parallel` threads, reports in path order.
+/// A failing repo doesn't stop the others
+pub fn pull_repos<F>(
+    storage: &Storage,
+    paths: &[String],
+    parallel: usize,
+    sync: F,
+) -> Vec<PullReport>
+where
+    F: Fn(&str) -> Result<()> + Sync,
+{
+    let results = parallel_map(paths, parallel, |path| sync(path));
+    paths
+        .iter()
+        .zip(results)
+        .map(|(path, result)| PullReport {
+            name: storage.repos[path].display_name(path),
+            path: path.clone(),
+            result: result.map_err(|e| e.to_string()),
+        })
+        .collect()
+}
+
+/// `git pull --ff-only`, or only `git fetch` with `fetch_only`. Fails instead of
+/// prompting for credentials, and gives up after `timeout`
+pub fn git_pull(path: &str, fetch_only: bool, timeout: Duration) -> Result<()> {
+    let args: &[&str] = if fetch_only {
+        &["fetch", "--quiet"]
+    } else {
+        &["pull", "--ff-only", "--quiet"]
+    };
+    run_git_with_timeout(path, args, timeout).map(|_| ())
+}
+
+/// Pulls the repo `name` resolves to, or every tracked repo. Fails if any pull failed
+pub fn run(
+    config: &Config,
+    name: Option<&str>,
+    fetch_only: bool,
+    parallel: usize,
+    timeout: Duration,
+) -> Result<()> {
+    let storage = Storage::new(config)?;
+    let paths = match name {
+        Some(name) => vec![storage.resolve_repo(name)?],
+        None => storage.all_paths(),
+    };
+
+    let reports = pull_repos(&storage, &paths, parallel, |path| {
+        git_pull(path, fetch_only, timeout)
+    });
+    for report in &reports {
+        println!("{}", report);
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|report| report.result.is_err())
+        .count();
+    if failed > 0 {
+        let action = if fetch_only { "fetches" } else { "pulls" };
+        return Err(anyhow!("{} of {} {} failed", failed, reports.len(), action));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_pull {
+    use super::*;
+    use crate::storage::RepoAccess;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    fn storage_with_repos(count: usize) -> Storage {
+        let mut storage = Storage::default();
+        for i in 0..count {
+            storage
+                .repos
+                .insert(format!("/repos/{}", i), RepoAccess::new(vec![]));
+        }
+        storage
+    }
+
+    #[test]
+    fn test_pull_repos_serially() {
+        let storage = storage_with_repos(3);
+        let paths = storage.all_paths();
+        assert_eq!(paths, vec!["/repos/0", "/repos/1", "/repos/2"]);
+
+        // Stands in for git, recording the order the repos were pulled in
+        let pulled = Mutex::new(Vec::new());
+        let reports = pull_repos(&storage, &paths, 1, |path| {
+            pulled.lock().unwrap().push(path.to_string());
+            if path == "/repos/1" {
+                return Err(anyhow!("Not possible to fast-forward"));
+            }
+            Ok(())
+        });
+
+        assert_eq!(*pulled.lock().unwrap(), paths);
+        let lines: Vec<String> = reports.iter().map(|report| report.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "ok\t0\t/repos/0",
+                "failed\t1\t/repos/1\tNot possible to fast-forward",
+                "ok\t2\t/repos/2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pull_repos_in_parallel() {
+        let storage = storage_with_repos(6);
+        let paths = storage.all_paths();
+
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let reports = pull_repos(&storage, &paths, 3, |path| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            running.fetch_sub(1, Ordering::SeqCst);
+
+            if path == "/repos/4" {
+                return Err(anyhow!("no tracking information"));
+            }
+            Ok(())
+        });
+
+        // How many overlap depends on the scheduler, but never more than asked for
+        let max_running = max_running.load(Ordering::SeqCst);
+        assert!(max_running > 1 && max_running <= 3, "{}", max_running);
+        let reported: Vec<&str> = reports.iter().map(|report| report.path.as_str()).collect();
+        assert_eq!(reported, paths);
+        assert_eq!(
+            reports
+                .iter()
+                .filter(|report| report.result.is_err())
+                .count(),
+            1
+        );
+        assert!(reports[4].result.is_err());
+    }
+}
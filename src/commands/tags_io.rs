@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::Config;
+use crate::storage::Storage;
+
+/// The tags of one repo in an export file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoTags {
+    pub path: String,
+    pub tags: Vec<String>,
+}
+
+/// Contents of a `tags --export` file: usage counts for reference and the
+/// per-repo tags that `tags --import` reads back
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TagExport {
+    #[serde(default)]
+    pub tags: BTreeMap<String, usize>,
+    pub repos: Vec<RepoTags>,
+}
+
+/// Outcome of `import_tags`
+#[derive(Debug, Default, PartialEq)]
+pub struct TagImportReport {
+    pub updated: usize,
+    // Paths in the file that aren't tracked
+    pub not_tracked: Vec<String>,
+}
+
+/// Collects every repo's tags, sorted by path
+pub fn export_tags(storage: &Storage) -> TagExport {
+    let mut repos: Vec<RepoTags> = storage
+        .repos
+        .iter()
+        .map(|(path, repo_access)| RepoTags {
+            path: path.clone(),
+            tags: repo_access.tags.clone(),
+        })
+        .collect();
+    repos.sort_by(|a, b| a.path.cmp(&b.path));
+
+    TagExport {
+        tags: storage.get_all_tags().into_iter().collect(),
+        repos,
+    }
+}
+
+/// Applies exported tags to the tracked repos with the same path, adding them to
+/// the existing tags or replacing those with `replace`
+pub fn import_tags(storage: &mut Storage, export: &TagExport, replace: bool) -> TagImportReport {
+    let mut report = TagImportReport::default();
+
+    for entry in &export.repos {
+        let Some(repo_access) = storage.repos.get_mut(&entry.path) else {
+            report.not_tracked.push(entry.path.clone());
+            continue;
+        };
+
+        if replace {
+            repo_access.tags = entry.tags.clone();
+        } else {
+            for tag in &entry.tags {
+                if !repo_access.tags.contains(tag) {
+                    repo_access.tags.push(tag.clone());
+                }
+            }
+        }
+        report.updated += 1;
+    }
+
+    report
+}
+
+/// Writes all tags to a JSON file
+pub fn run_export(config: &Config, file: &str) -> Result<()> {
+    let storage = Storage::new(config)?;
+    let export = export_tags(&storage);
+
+    let json = serde_json::to_string_pretty(&export).context("Failed to serialize tags")?;
+    fs::write(file, json).with_context(|| format!("Failed to write {}", file))?;
+    println!(
+        "Exported {} tags of {} repos to {}",
+        export.tags.len(),
+        export.repos.len(),
+        file
+    );
+    Ok(())
+}
+
+/// Reads a file written by `run_export` and applies its tags
+pub fn run_import(config: &Config, file: &str, replace: bool) -> Result<()> {
+    let data = fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+    let export: TagExport =
+        serde_json::from_str(&data).with_context(|| format!("Failed to parse {}", file))?;
+
+    let mut storage = Storage::new(config)?;
+    let report = import_tags(&mut storage, &export, replace);
+    for path in &report.not_tracked {
+        eprintln!("Skipping {}, not tracked", path);
+    }
+    storage.save(config)?;
+
+    println!(
+        "{} repos updated, {} skipped (not tracked)",
+        report.updated,
+        report.not_tracked.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_tags_io {
+    use super::*;
+    use crate::storage::RepoAccess;
+
+    fn storage(repos: &[(&str, &[&str])]) -> Storage {
+        let mut storage = Storage::default();
+        for (path, tags) in repos {
+            let tags = tags.iter().map(|tag| tag.to_string()).collect();
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(tags));
+        }
+        storage
+    }
+
+    fn tags_by_path(storage: &Storage) -> BTreeMap<String, Vec<String>> {
+        storage
+            .repos
+            .iter()
+            .map(|(path, repo_access)| (path.clone(), repo_access.tags.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let original = storage(&[
+            ("/repos/mangit", &["rust", "cli"]),
+            ("/repos/blog", &["web"]),
+            ("/repos/empty", &[]),
+        ]);
+        let export = export_tags(&original);
+        assert_eq!(export.tags["rust"], 1);
+        assert_eq!(export.repos[0].path, "/repos/blog");
+
+        let json = serde_json::to_string(&export).unwrap();
+        let parsed: TagExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, export);
+
+        let mut restored = storage(&[
+            ("/repos/mangit", &[]),
+            ("/repos/blog", &[]),
+            ("/repos/empty", &[]),
+        ]);
+        let report = import_tags(&mut restored, &parsed, true);
+        assert_eq!(report.updated, 3);
+        assert_eq!(tags_by_path(&restored), tags_by_path(&original));
+    }
+
+    #[test]
+    fn test_import_merge_and_replace() {
+        let export = TagExport {
+            tags: BTreeMap::new(),
+            repos: vec![
+                RepoTags {
+                    path: "/repos/mangit".to_string(),
+                    tags: vec!["cli".to_string(), "tool".to_string()],
+                },
+                RepoTags {
+                    path: "/repos/gone".to_string(),
+                    tags: vec!["old".to_string()],
+                },
+            ],
+        };
+
+        let mut merged = storage(&[("/repos/mangit", &["rust", "cli"])]);
+        let report = import_tags(&mut merged, &export, false);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.not_tracked, vec!["/repos/gone"]);
+        assert_eq!(
+            merged.repos["/repos/mangit"].tags,
+            vec!["rust", "cli", "tool"]
+        );
+
+        let mut replaced = storage(&[("/repos/mangit", &["rust", "cli"])]);
+        import_tags(&mut replaced, &export, true);
+        assert_eq!(replaced.repos["/repos/mangit"].tags, vec!["cli", "tool"]);
+    }
+}
@@ -1,12 +1,93 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::storage::Priority;
+use crate::util::tmux::TmuxOpenMode;
+
+pub const DEFAULT_LANGUAGE_CHECK_INTERVAL_HOURS: u64 = 24;
+pub const DEFAULT_RECENT_BOOST_FACTOR: f64 = 5.0;
+// Access times kept per repo unless `max_access_history` says otherwise
+pub const DEFAULT_MAX_ACCESS_HISTORY: usize = 10;
+// Bounds `config set max-access-history` accepts
+pub const MAX_ACCESS_HISTORY_LIMITS: (usize, usize) = (1, 1000);
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct Config {
     pub mangit_dir: String,
+    // Re-detect a repo's language when it is accessed, at most once per interval
+    pub auto_update_language: bool,
+    pub language_check_interval_hours: u64,
+    // Score multiplier for `search --boost-recent`
+    pub recent_boost_factor: f64,
+    // Searches leave out repos below this frecency unless `--min-frecency` is given
+    pub default_search_min_frecency: f64,
+    // Where repos are expected to live, see `add --require-projects-dir`
+    pub default_projects_dir: Option<String>,
+    // Warn when adding a repo outside `default_projects_dir`
+    pub warn_outside_projects_dir: bool,
+    // Scale frecency scores by repo priority (high x2, low x0.5)
+    pub frecency_weight_by_priority: bool,
+    // Name added repos after their remote instead of their directory
+    pub prefer_remote_name: bool,
+    // API tokens for `info --show-ci-status`, needed for private repos
+    pub github_token: Option<String>,
+    pub gitlab_token: Option<String>,
+    // Tags given to every repo on `add`, on top of the ones passed explicitly
+    pub default_tags: Vec<String>,
+    // Behave as if `add --check-duplicate-name` was always passed
+    pub prevent_duplicate_names: bool,
+    // Priorities for `add --auto-set-priority`, the first rule whose pattern matches wins
+    pub priority_rules: Vec<PriorityRule>,
+    // Behave as if `add --auto-set-priority` was always passed
+    pub auto_set_priority: bool,
+    // Behave as if `add --check-ci-config` was always passed
+    pub auto_tag_ci: bool,
+    // Where `open --tmux` opens repos: new-window, h-split or v-split
+    pub tmux_open_mode: TmuxOpenMode,
+    // Access times kept per repo for frecency, older ones are dropped
+    pub max_access_history: usize,
+    // Score each access adds to a repo's frecency, by how long ago it was
+    pub frecency: FrecencyConfig,
+    // Editor chosen with `config set-editor`
+    pub default_editor: Option<String>,
+}
+
+/// Frecency weights of an access within the last minute, half hour, hour, day,
+/// week, or longer ago
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct FrecencyConfig {
+    pub weight_minute: f64,
+    pub weight_half_hour: f64,
+    pub weight_hour: f64,
+    pub weight_day: f64,
+    pub weight_week: f64,
+    pub weight_older: f64,
+}
+
+impl Default for FrecencyConfig {
+    fn default() -> Self {
+        FrecencyConfig {
+            weight_minute: 100.0,
+            weight_half_hour: 80.0,
+            weight_hour: 60.0,
+            weight_day: 40.0,
+            weight_week: 20.0,
+            weight_older: 10.0,
+        }
+    }
+}
+
+/// Gives repos whose path matches `path_pattern` (e.g. `*/work/*`) a priority
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriorityRule {
+    pub path_pattern: String,
+    pub priority: Priority,
 }
 
 impl Default for Config {
@@ -14,11 +95,94 @@ impl Default for Config {
         let home = home_dir().unwrap_or_else(|| PathBuf::from("~"));
         Config {
             mangit_dir: home.join(".mangit").to_string_lossy().to_string(),
+            auto_update_language: false,
+            language_check_interval_hours: DEFAULT_LANGUAGE_CHECK_INTERVAL_HOURS,
+            recent_boost_factor: DEFAULT_RECENT_BOOST_FACTOR,
+            default_search_min_frecency: 0.0,
+            default_projects_dir: None,
+            warn_outside_projects_dir: false,
+            frecency_weight_by_priority: false,
+            prefer_remote_name: false,
+            github_token: None,
+            gitlab_token: None,
+            default_tags: Vec::new(),
+            prevent_duplicate_names: false,
+            priority_rules: Vec::new(),
+            auto_set_priority: false,
+            auto_tag_ci: false,
+            tmux_open_mode: TmuxOpenMode::NewWindow,
+            max_access_history: DEFAULT_MAX_ACCESS_HISTORY,
+            frecency: FrecencyConfig::default(),
+            default_editor: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+/// A problem found while validating a config field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub value: String,
+    pub issue: String,
+    pub severity: IssueSeverity,
+}
+
+impl ConfigIssue {
+    fn new(field: &str, value: &str, issue: &str, severity: IssueSeverity) -> Self {
+        ConfigIssue {
+            field: field.to_string(),
+            value: value.to_string(),
+            issue: issue.to_string(),
+            severity,
         }
     }
 }
 
 impl Config {
+    /// Loads the config from `config.json` in the default mangit directory,
+    /// falling back to the defaults if there is none
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Config::default().config_path())
+    }
+
+    /// Loads the config from a file. Missing fields keep their defaults
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Saves the config to the default config file
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Config::default().config_path())
+    }
+
+    /// Saves the config to a file, creating its directory if needed
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create config directory")?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write config file {}", path.display()))
+    }
+
+    /// Returns the config file path
+    pub fn config_path(&self) -> PathBuf {
+        self.mangit_dir_path().join("config.json")
+    }
+
     /// Returns the mangit directory as PathBuf
     pub fn mangit_dir_path(&self) -> PathBuf {
         PathBuf::from(&self.mangit_dir)
@@ -29,6 +193,37 @@ impl Config {
         self.mangit_dir_path().join("repos.json")
     }
 
+    /// Returns a path to write the repos file to before it replaces the old one.
+    /// Each call gives a new name, so concurrent saves never share a file
+    pub fn repos_tmp_path(&self) -> PathBuf {
+        let suffix = Uuid::new_v4().simple().to_string();
+        self.mangit_dir_path().join(format!(
+            "repos.json.{}.{}.tmp",
+            std::process::id(),
+            &suffix[..8]
+        ))
+    }
+
+    /// Whether a file name in the mangit directory is a temporary repos file
+    pub fn is_repos_tmp_file(file_name: &str) -> bool {
+        file_name.starts_with("repos.json.") && file_name.ends_with(".tmp")
+    }
+
+    /// Returns the tag vocabulary file path
+    pub fn tag_vocab_path(&self) -> PathBuf {
+        self.mangit_dir_path().join("tag_vocab.json")
+    }
+
+    /// Returns the saved searches file path
+    pub fn saved_searches_path(&self) -> PathBuf {
+        self.mangit_dir_path().join("saved_searches.json")
+    }
+
+    /// Returns the PID file path of the background cleanup task
+    pub fn cleanup_pid_path(&self) -> PathBuf {
+        self.mangit_dir_path().join("cleanup.pid")
+    }
+
     /// Ensures the mangit directory exists
     pub fn ensure_mangit_dir(&self) -> Result<()> {
         let dir = self.mangit_dir_path();
@@ -37,6 +232,127 @@ impl Config {
         }
         Ok(())
     }
+
+    /// Appends `default_tags` to explicitly given tags, dropping duplicates
+    pub fn merge_default_tags(&self, tags: Vec<String>) -> Vec<String> {
+        let mut merged = Vec::with_capacity(tags.len() + self.default_tags.len());
+        for tag in tags.into_iter().chain(self.default_tags.iter().cloned()) {
+            if !merged.contains(&tag) {
+                merged.push(tag);
+            }
+        }
+        merged
+    }
+
+    /// Checks all config fields and returns the problems found
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        Self::validate_dir("mangit_dir", &self.mangit_dir, &mut issues);
+        if let Some(projects_dir) = &self.default_projects_dir {
+            Self::validate_dir("default_projects_dir", projects_dir, &mut issues);
+        }
+        if let Err(e) = Self::check_max_access_history(self.max_access_history) {
+            issues.push(ConfigIssue::new(
+                "max_access_history",
+                &self.max_access_history.to_string(),
+                &e.to_string(),
+                IssueSeverity::Error,
+            ));
+        }
+        issues
+    }
+
+    /// Sets how many access times are kept per repo, within `MAX_ACCESS_HISTORY_LIMITS`
+    pub fn set_max_access_history(&mut self, value: usize) -> Result<()> {
+        Self::check_max_access_history(value)?;
+        self.max_access_history = value;
+        Ok(())
+    }
+
+    /// Checks a frecency weight for `config set`, which has to be a finite number of 0 or more
+    pub fn parse_frecency_weight(value: &str) -> Result<f64> {
+        let weight: f64 = value
+            .parse()
+            .map_err(|_| anyhow!("must be a number, got {}", value))?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(anyhow!("must be 0 or more, got {}", value));
+        }
+        Ok(weight)
+    }
+
+    fn check_max_access_history(value: usize) -> Result<()> {
+        let (min, max) = MAX_ACCESS_HISTORY_LIMITS;
+        if !(min..=max).contains(&value) {
+            return Err(anyhow!("must be between {} and {}", min, max));
+        }
+        Ok(())
+    }
+
+    fn validate_dir(field: &str, value: &str, issues: &mut Vec<ConfigIssue>) {
+        use IssueSeverity::{Error, Warning};
+
+        if value.trim().is_empty() {
+            issues.push(ConfigIssue::new(field, value, "path is empty", Error));
+            return;
+        }
+
+        if value.starts_with('~') {
+            issues.push(ConfigIssue::new(
+                field,
+                value,
+                "path starts with an unexpanded '~'",
+                Error,
+            ));
+            return;
+        }
+
+        let path = Path::new(value);
+        if !path.is_absolute() {
+            issues.push(ConfigIssue::new(
+                field,
+                value,
+                "path is not absolute",
+                Warning,
+            ));
+        }
+
+        match fs::metadata(path) {
+            Ok(metadata) if !metadata.is_dir() => {
+                issues.push(ConfigIssue::new(
+                    field,
+                    value,
+                    "path is not a directory",
+                    Error,
+                ));
+            }
+            Ok(metadata) => {
+                if metadata.permissions().readonly() {
+                    issues.push(ConfigIssue::new(
+                        field,
+                        value,
+                        "directory is not writable",
+                        Error,
+                    ));
+                }
+                if fs::read_dir(path).is_err() {
+                    issues.push(ConfigIssue::new(
+                        field,
+                        value,
+                        "directory is not readable",
+                        Error,
+                    ));
+                }
+            }
+            Err(_) => {
+                issues.push(ConfigIssue::new(
+                    field,
+                    value,
+                    "directory does not exist yet (run `mangit init`)",
+                    Warning,
+                ));
+            }
+        }
+    }
 }
 
 /// Checks if a path is a valid git repository
@@ -68,6 +384,7 @@ mod tests_config {
                 .join(".mangit")
                 .to_string_lossy()
                 .to_string(),
+            ..Config::default()
         };
         let expected_path = temp_dir.path().join(".mangit");
         assert_eq!(config.mangit_dir_path(), expected_path);
@@ -79,6 +396,7 @@ mod tests_config {
         let expected_dir = temp_dir.path().join(".mangit");
         let config = Config {
             mangit_dir: expected_dir.to_string_lossy().to_string(),
+            ..Config::default()
         };
         assert!(!expected_dir.exists());
         let result = config.ensure_mangit_dir();
@@ -86,6 +404,139 @@ mod tests_config {
         assert!(expected_dir.exists());
     }
 
+    #[test]
+    fn test_save_and_load_config() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("nested").join("config.json");
+        let config = Config {
+            default_editor: Some("nvim".to_string()),
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_merge_default_tags() {
+        let config = Config {
+            default_tags: vec!["machine:mybox".to_string(), "personal".to_string()],
+            ..Config::default()
+        };
+        assert_eq!(
+            config.merge_default_tags(Vec::new()),
+            vec!["machine:mybox", "personal"]
+        );
+        assert_eq!(
+            config.merge_default_tags(vec!["rust".to_string(), "personal".to_string()]),
+            vec!["rust", "personal", "machine:mybox"]
+        );
+        assert_eq!(
+            Config::default().merge_default_tags(vec!["rust".to_string(), "rust".to_string()]),
+            vec!["rust"]
+        );
+    }
+
+    #[test]
+    fn test_validate_valid_config() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config {
+            mangit_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn test_frecency_weights() {
+        let config: Config = serde_json::from_str(r#"{"frecency": {"weight_day": 50.0}}"#).unwrap();
+        assert_eq!(config.frecency.weight_day, 50.0);
+        assert_eq!(config.frecency.weight_minute, 100.0);
+        assert_eq!(config.frecency.weight_older, 10.0);
+
+        assert_eq!(Config::parse_frecency_weight("50").unwrap(), 50.0);
+        assert_eq!(Config::parse_frecency_weight("0").unwrap(), 0.0);
+        for invalid in ["-1", "NaN", "inf", "lots"] {
+            assert!(Config::parse_frecency_weight(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_max_access_history() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = Config {
+            mangit_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.max_access_history, DEFAULT_MAX_ACCESS_HISTORY);
+
+        config.set_max_access_history(1000).unwrap();
+        assert_eq!(config.max_access_history, 1000);
+        for invalid in [0, 1001] {
+            let err = config.set_max_access_history(invalid).unwrap_err();
+            assert_eq!(err.to_string(), "must be between 1 and 1000");
+        }
+        assert_eq!(config.max_access_history, 1000);
+
+        config.max_access_history = 0;
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "max_access_history");
+    }
+
+    #[test]
+    fn test_validate_empty_mangit_dir() {
+        let config = Config {
+            mangit_dir: "".to_string(),
+            ..Config::default()
+        };
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "mangit_dir");
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+    }
+
+    #[test]
+    fn test_validate_unexpanded_home() {
+        let config = Config {
+            mangit_dir: "~/.mangit".to_string(),
+            ..Config::default()
+        };
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].value, "~/.mangit");
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+    }
+
+    #[test]
+    fn test_validate_mangit_dir_is_file() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("not_a_dir");
+        fs::write(&file_path, "").unwrap();
+        let config = Config {
+            mangit_dir: file_path.to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue, "path is not a directory");
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+    }
+
+    #[test]
+    fn test_validate_missing_and_relative_dir() {
+        let config = Config {
+            mangit_dir: "relative/missing/.mangit".to_string(),
+            ..Config::default()
+        };
+        let issues = config.validate();
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.severity == IssueSeverity::Warning)
+        );
+    }
+
     #[test]
     fn test_is_git_repo() {
         let temp_dir = tempdir().unwrap();
@@ -96,4 +547,26 @@ mod tests_config {
         fs::create_dir_all(&git_dir).unwrap();
         assert!(is_git_repo(temp_dir.path()));
     }
+
+    #[test]
+    fn test_load_from() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("config.json");
+
+        // A missing file gives the defaults
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+
+        // Missing fields keep their defaults
+        fs::write(&path, r#"{"auto_update_language": true}"#).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert!(config.auto_update_language);
+        assert_eq!(
+            config.language_check_interval_hours,
+            DEFAULT_LANGUAGE_CHECK_INTERVAL_HOURS
+        );
+        assert_eq!(config.mangit_dir, Config::default().mangit_dir);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
 }
@@ -0,0 +1,2 @@
+pub mod tmux;
+pub mod vscode;
@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::util::find_in_path;
+
+/// Returns the working directories of all tmux panes, without duplicates.
+/// Empty when tmux isn't installed or no server is running
+pub fn get_tmux_pane_paths() -> Result<Vec<PathBuf>> {
+    let Some(tmux) = find_in_path("tmux") else {
+        return Ok(Vec::new());
+    };
+
+    let output = Command::new(tmux)
+        .args(["list-panes", "-a", "-F", "#{pane_current_path}"])
+        .output()
+        .context("Failed to run tmux")?;
+    // tmux fails with "no server running" when there are no sessions
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(parse_pane_paths(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses `tmux list-panes -F '#{pane_current_path}'` output, keeping the first
+/// occurrence of each path
+fn parse_pane_paths(output: &str) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter(|line| seen.insert(*line))
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_tmux {
+    use super::*;
+
+    #[test]
+    fn test_parse_pane_paths() {
+        let output = "/home/me/src/mangit\n/home/me\n/home/me/src/mangit\n\n/tmp\n/home/me\n";
+        assert_eq!(
+            parse_pane_paths(output),
+            vec![
+                PathBuf::from("/home/me/src/mangit"),
+                PathBuf::from("/home/me"),
+                PathBuf::from("/tmp"),
+            ]
+        );
+        assert!(parse_pane_paths("").is_empty());
+    }
+}
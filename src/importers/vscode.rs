@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util::normalize_path;
+
+#[derive(Deserialize, Debug)]
+struct Workspace {
+    #[serde(default)]
+    folders: Vec<WorkspaceFolder>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WorkspaceFolder {
+    path: Option<String>,
+}
+
+/// Reads a `*.code-workspace` file and returns the folder paths it references.
+/// Relative paths are resolved against the directory containing the workspace file,
+/// and `.` and `..` are resolved so `ws/../x` comes back as `x`
+pub fn parse_workspace(path: &Path) -> Result<Vec<PathBuf>> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read workspace file: {}", path.display()))?;
+    let workspace: Workspace = serde_json::from_str(&strip_jsonc(&data))
+        .with_context(|| format!("Failed to parse workspace file: {}", path.display()))?;
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    Ok(workspace
+        .folders
+        .into_iter()
+        .filter_map(|folder| folder.path)
+        // Joining an absolute path replaces the base
+        .map(|folder_path| normalize_path(&base_dir.join(folder_path)))
+        .collect())
+}
+
+/// VS Code writes JSON with comments and trailing commas, strip both so
+/// serde_json can parse it
+fn strip_jsonc(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            output.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                output.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+            output.push(c);
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == ',' {
+            // Drop the comma if only whitespace is left before the closing bracket
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if !matches!(next, Some('}') | Some(']')) {
+                output.push(c);
+            }
+        } else {
+            output.push(c);
+        }
+
+        i += 1;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests_vscode {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_workspace() {
+        let temp_dir = tempdir().unwrap();
+        let workspace_path = temp_dir.path().join("project.code-workspace");
+        fs::write(
+            &workspace_path,
+            r#"{
+    // Folders opened in this workspace
+    "folders": [
+        { "path": "backend" },
+        { "name": "Frontend", "path": "../frontend" },
+        { "path": "/abs/./shared", },
+        /* a folder without a path is ignored */
+        { "uri": "vscode-remote://host/repo" }
+    ],
+    "settings": { "editor.tabSize": 4, "files.exclude": { "**/target": true } }
+}"#,
+        )
+        .unwrap();
+
+        let paths = parse_workspace(&workspace_path).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                temp_dir.path().join("backend"),
+                temp_dir.path().parent().unwrap().join("frontend"),
+                PathBuf::from("/abs/shared"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_workspace_invalid() {
+        let temp_dir = tempdir().unwrap();
+        let workspace_path = temp_dir.path().join("broken.code-workspace");
+        fs::write(&workspace_path, "{ \"folders\": [").unwrap();
+        assert!(parse_workspace(&workspace_path).is_err());
+
+        assert!(parse_workspace(&temp_dir.path().join("missing.code-workspace")).is_err());
+    }
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings() {
+        let input = r#"{ "url": "http://example.com/*x*/", "a": [1, 2,], }"#;
+        assert_eq!(
+            strip_jsonc(input),
+            r#"{ "url": "http://example.com/*x*/", "a": [1, 2] }"#
+        );
+    }
+}
@@ -1,11 +1,70 @@
+mod commands;
 mod config;
+mod importers;
+mod output;
 mod repository;
 mod storage;
+mod util;
 
 use anyhow::{Result, anyhow};
-use clap::{Parser, Subcommand};
-use config::Config;
-use storage::Storage;
+use chrono::{DateTime, Duration, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use commands::import::ImportFormat;
+use config::{Config, IssueSeverity, is_git_repo};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use importers::{tmux, vscode};
+use output::OutputFormat;
+use output::cluster::render_clusters;
+use output::csv::CsvRenderer;
+use output::dot::DotRenderer;
+use output::env::render_env_declarations;
+use output::fish::render_fish_abbreviations;
+use output::histogram::{LanguageHistogram, terminal_width};
+use output::html::HtmlRenderer;
+use output::info::{InfoFormat, InfoLine, RepoInfoPrinter};
+use output::json::JsonRenderer;
+use output::nvim::{NvimQuickfixRenderer, NvimTelescopeRenderer};
+use output::status::StatusTable;
+use output::tag_graph::{build_tag_graph, render_tag_graph};
+use output::toml::TomlRenderer;
+use output::tsv::TsvRenderer;
+use repository::{
+    Repository, TagRequirement, auto_generate_description, detect_ci, detect_language_version,
+    extract_readme_description, repo_name_from_path, score_breakdown,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration as StdDuration;
+use storage::saved_searches::{SavedSearch, SavedSearches};
+use storage::vocab::TagVocabulary;
+use storage::{
+    AccessWindow, ActivityTier, ArchiveFilter, LanguageFilter, Priority, RemoteUrlFilter,
+    RepoAccess, SearchExpr, Storage, boost_scores, classify_activity, compute_tag_frecency_scores,
+};
+use util::ci::{self, CiStatus};
+use util::edit_distance::suggest_correction;
+use util::expr::SortExpr;
+use util::fzf::{self, FzfItem};
+use util::git::{self, ChangedAfter, RemoteStatus};
+use util::project_url::{ProjectService, validate_project_url};
+use util::random::Rng;
+use util::rofi::{self, RofiItem};
+use util::template::Template;
+use util::tmux::{TmuxOpenMode, inside_tmux, open_in_tmux};
+use util::{
+    find_available_editors, find_git_repos, format_time_ago, infer_name_from_remote_url,
+    is_under_dir, match_priority_rules, normalize_scores, open_in_editor, open_shell_in, open_url,
+    parallel_map, parse_duration, resolve_editor,
+};
+
+// `update` flags that work on every tracked repo instead of one path
+const BULK_UPDATES: [&str; 3] = ["apply_tag_file", "refresh_all", "auto_generate_description"];
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about = "Manage Git repositories with tags")]
@@ -19,212 +78,3160 @@ enum Commands {
     /// Initialize mangit
     Init,
 
-    /// Add a repo with tags
-    Add {
-        /// Path to repository
-        path: String,
+    /// Add a repo with tags
+    Add {
+        /// Path to repository
+        path: String,
+
+        /// Tags for the repository (comma separated)
+        #[clap(short, long)]
+        tags: String,
+
+        /// Use the first line of the repo's README as its description
+        #[clap(long)]
+        set_description_from_readme: bool,
+
+        /// Priority of the repository
+        #[clap(long, value_enum)]
+        priority: Option<Priority>,
+
+        /// Refuse to add repos outside the configured `default_projects_dir`
+        #[clap(long)]
+        require_projects_dir: bool,
+
+        /// Name to show for the repo instead of its directory name
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Name the repo after its `origin` remote, e.g. `my-project` for
+        /// `git@github.com:user/my-project.git`
+        #[clap(long, conflicts_with = "name")]
+        infer_name_from_git: bool,
+
+        /// Link the repo to a ticket or issue (any http(s) URL)
+        #[clap(long, value_name = "URL", group = "project_url")]
+        link_to_project_management: Option<String>,
+
+        /// Link the repo to a Jira ticket, e.g. https://jira.myco.com/browse/PROJ-123
+        #[clap(long, value_name = "URL", group = "project_url")]
+        jira: Option<String>,
+
+        /// Link the repo to a GitHub issue, e.g. https://github.com/owner/repo/issues/42
+        #[clap(long, value_name = "URL", group = "project_url")]
+        github_issue: Option<String>,
+
+        /// Link the repo to a Linear issue, e.g. https://linear.app/myco/issue/ENG-123
+        #[clap(long, value_name = "URL", group = "project_url")]
+        linear: Option<String>,
+
+        /// Set the priority from the first matching `priority_rules` entry in the config
+        #[clap(long, conflicts_with = "priority")]
+        auto_set_priority: bool,
+
+        /// Tag the repo with the CI services it has config files for, e.g.
+        /// `ci:github-actions` or `ci:gitlab`
+        #[clap(long)]
+        check_ci_config: bool,
+
+        /// Refuse to add the repo if another tracked repo has the same name
+        #[clap(long)]
+        check_duplicate_name: bool,
+
+        /// Add the repo even if `prevent_duplicate_names` is set in the config. A
+        /// custom name still has to be unique
+        #[clap(long, conflicts_with = "check_duplicate_name")]
+        allow_duplicate_names: bool,
+
+        /// Print only the repo's ID, e.g. for `ID=$(mangit add --generate-id ...)`
+        #[clap(long)]
+        generate_id: bool,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = AddOutput::Text)]
+        output: AddOutput,
+    },
+
+    /// Delete a repo, or every repo matching a query
+    #[clap(visible_alias = "remove")]
+    Delete {
+        /// Path to repository
+        #[clap(required_unless_present = "matching", conflicts_with = "matching")]
+        path: Option<String>,
+
+        /// Delete all repos matching this query, e.g. "path:/old/projects/*"
+        #[clap(long)]
+        matching: Option<String>,
+
+        /// Only show which repos would be deleted
+        #[clap(long, requires = "matching")]
+        dry_run: bool,
+
+        /// Confirm deleting more than one repo
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Update a repo's tags
+    Update {
+        /// Path to repository
+        #[clap(required_unless_present_any = ["apply_tag_file", "refresh_all", "auto_generate_description"])]
+        path: Option<String>,
+
+        /// Add the tags from each tracked repo's `.mangit-tags` file
+        #[clap(long, conflicts_with = "path")]
+        apply_tag_file: bool,
+
+        /// Re-read language, remote URL, branch, last commit date and
+        /// `.mangit-tags` for every tracked repo
+        #[clap(long, conflicts_with_all = ["path", "apply_tag_file"])]
+        refresh_all: bool,
+
+        /// Give every repo without a description one from its README, latest commit
+        /// or name
+        #[clap(long, conflicts_with_all = ["path", "apply_tag_file", "refresh_all"])]
+        auto_generate_description: bool,
+
+        /// With --apply-tag-file, replace the tags instead of adding to them
+        #[clap(long, requires = "apply_tag_file")]
+        replace_with_file_tags: bool,
+
+        /// New tags for the repository (comma separated)
+        #[clap(short, long, conflicts_with_all = BULK_UPDATES)]
+        tags: Option<String>,
+
+        /// Record the date of the repo's first commit
+        #[clap(long, conflicts_with_all = BULK_UPDATES)]
+        fetch_first_commit: bool,
+
+        /// Re-read the repo's description from its README
+        #[clap(long, conflicts_with_all = BULK_UPDATES)]
+        refresh_description: bool,
+    },
+
+    /// Search for repos by tag or multiple tags
+    Search {
+        /// Tag(s) to search for (comma separated)
+        #[clap(required_unless_present = "run")]
+        tags: Option<String>,
+
+        /// Require all of the tags (default)
+        #[clap(long, visible_alias = "require-all-tags-matched", conflicts_with_all = ["or", "exact"])]
+        and: bool,
+
+        /// Require at least one of the tags
+        #[clap(
+            long,
+            visible_aliases = ["any", "require-any-tag-matched"],
+            conflicts_with = "exact"
+        )]
+        or: bool,
+
+        /// Require a repo's tags to be exactly these tags, no extra tags allowed
+        #[clap(long = "require-exact-tag-set")]
+        exact: bool,
+
+        /// Exclude repos with these tags (comma separated, repeatable)
+        #[clap(long, value_name = "TAGS")]
+        not: Vec<String>,
+
+        /// Leave out repos with a frecency score below this
+        #[clap(long, value_name = "SCORE")]
+        min_frecency: Option<f64>,
+
+        /// Leave out repos with a frecency score above this
+        #[clap(long, value_name = "SCORE")]
+        max_frecency: Option<f64>,
+
+        /// Only show repos with a detected language
+        #[clap(long, conflicts_with = "language_unknown")]
+        require_language: bool,
+
+        /// Only show repos without a detected language
+        #[clap(long)]
+        language_unknown: bool,
+
+        /// Also show archived repos, marked with `[ARCHIVED]` in html output and
+        /// `"archived": true` in json output
+        #[clap(long, conflicts_with = "archived_only")]
+        include_archived: bool,
+
+        /// Only show archived repos
+        #[clap(long)]
+        archived_only: bool,
+
+        /// When nothing matches, retry with misspelled tags corrected
+        #[clap(long)]
+        auto_correct: bool,
+
+        /// Print how each result matches the tags: fuzzy scores for name and
+        /// description, matching tags and language
+        #[clap(long, conflicts_with_all = ["output", "zero_terminated", "template"])]
+        score_explain: bool,
+
+        /// Only show repos at most N directory levels below the current directory
+        #[clap(long, value_name = "N")]
+        within_depth: Option<usize>,
+
+        /// Directory --within-depth counts from instead of the current directory
+        #[clap(long, value_name = "DIR", requires = "within_depth")]
+        from: Option<String>,
+
+        /// Only show repos in the same parent directory as this repo
+        #[clap(long, value_name = "PATH")]
+        within_same_project: Option<String>,
+
+        /// Only show repos last accessed within this long, e.g. 7d, 2w or 1h
+        #[clap(long, value_name = "DURATION")]
+        since: Option<String>,
+
+        /// Only show repos not accessed for at least this long, e.g. 30d or 6mo
+        #[clap(long, value_name = "DURATION")]
+        before: Option<String>,
+
+        /// Boost the scores of repos accessed in the last N hours
+        #[clap(long, value_name = "N")]
+        boost_recent: Option<u64>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Paths)]
+        output: OutputFormat,
+
+        /// Separate paths with a null byte instead of a newline (for `xargs -0`)
+        #[clap(short = '0', long)]
+        zero_terminated: bool,
+
+        /// Print each repo with a template, e.g. '{name}: {path} [{tags:sep=|}]'.
+        /// Placeholders: name, path, tags, language, last_modified, score
+        #[clap(long, conflicts_with_all = ["output", "zero_terminated"])]
+        template: Option<String>,
+
+        /// Show at most N repos
+        #[clap(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Group the results by tag set, clusters with the highest average score first
+        #[clap(long, conflicts_with_all = ["output", "zero_terminated", "template", "score_explain"])]
+        cluster_by_tags: bool,
+
+        /// Save the tags, --or, --not and --limit of this search under a name
+        #[clap(long, value_name = "NAME")]
+        save_as: Option<String>,
+
+        /// Replay a search saved with --save-as
+        #[clap(long, value_name = "NAME", conflicts_with_all = ["tags", "or", "not", "save_as"])]
+        run: Option<String>,
+    },
+
+    /// Manage searches saved with `search --save-as`
+    SavedSearches {
+        #[clap(subcommand)]
+        action: SavedSearchAction,
+    },
+
+    /// List all tracked repos, most frecent first
+    List {
+        /// Sort order of the listed repos
+        #[clap(short, long, value_enum, default_value_t = ListSort::Frecency)]
+        sort: ListSort,
+
+        /// Sort key for `--sort custom`, e.g. 'frecency * 0.7 + tag_count * 0.3'.
+        /// Variables: frecency, tag_count, age_days, access_count
+        #[clap(long, value_name = "EXPR", required_if_eq("sort", "custom"))]
+        sort_expr: Option<String>,
+
+        /// Only show repos with commits after a date (YYYY-MM-DD) or git ref (e.g. HEAD~10)
+        #[clap(long, value_name = "DATE|REF")]
+        changed_after: Option<String>,
+
+        /// Only show repos with more than N files ignored by git
+        #[clap(long, value_name = "N")]
+        max_ignored: Option<u64>,
+
+        /// Number of repos to check with git at the same time
+        #[clap(long, value_name = "N", default_value_t = 4)]
+        parallel: usize,
+
+        /// Only show repos with uncommitted changes
+        #[clap(long)]
+        changed: bool,
+
+        /// Only show repos with this kind of uncommitted change: M (modified),
+        /// A (added), D (deleted) or ? (untracked). Implies --changed
+        #[clap(long, value_name = "TYPE", value_parser = ["M", "A", "D", "?"])]
+        changed_type: Option<String>,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = OutputFormat::Paths)]
+        output: OutputFormat,
+
+        /// Write the output to a file instead of stdout
+        #[clap(short, long)]
+        file: Option<String>,
+
+        /// Separate paths with a null byte instead of a newline (for `xargs -0`)
+        #[clap(short = '0', long)]
+        zero_terminated: bool,
+
+        /// Print each repo's frecency score before its path
+        #[clap(long, conflicts_with = "zero_terminated")]
+        show_frecency_score: bool,
+
+        /// Sort by frecency and print each repo's score as a percentage of the highest
+        #[clap(long, conflicts_with_all = ["zero_terminated", "sort", "random", "show_frecency_score"])]
+        with_score: bool,
+
+        /// Only show repos with a detected language
+        #[clap(long, conflicts_with = "language_unknown")]
+        require_language: bool,
+
+        /// Only show repos without a detected language
+        #[clap(long)]
+        language_unknown: bool,
+
+        /// Also show archived repos, marked with `[ARCHIVED]` in html output and
+        /// `"archived": true` in json output
+        #[clap(long)]
+        include_archived: bool,
+
+        /// Only show archived repos
+        #[clap(long)]
+        archived_only: bool,
+
+        /// Only show repos last accessed within this long, e.g. 7d, 2w or 1h
+        #[clap(long, value_name = "DURATION")]
+        since: Option<String>,
+
+        /// Only show repos not accessed for at least this long, e.g. 30d or 6mo
+        #[clap(long, value_name = "DURATION")]
+        before: Option<String>,
+
+        /// Only show repos with a remote URL
+        #[clap(long, conflicts_with = "without_remote_url")]
+        with_remote_url: bool,
+
+        /// Only show repos without a remote URL, e.g. ones never pushed anywhere
+        #[clap(long, conflicts_with = "matching_remote")]
+        without_remote_url: bool,
+
+        /// Only show repos whose remote URL contains this text, ignoring case
+        #[clap(long, value_name = "TEXT")]
+        matching_remote: Option<String>,
+
+        /// Print `export MANGIT_REPO_<NAME>=<path>` lines, same as `--output env`
+        #[clap(long, conflicts_with = "output")]
+        export_env: bool,
+
+        /// Print fish `abbr` lines (`cd_<name>`, `mangit_<name>`), same as `--output fish`
+        #[clap(long, conflicts_with_all = ["output", "export_env"])]
+        export_fish: bool,
+
+        /// Print how many of the listed repos use each language as a bar chart
+        #[clap(long, conflicts_with_all = ["output", "file", "zero_terminated", "export_env", "export_fish", "by_activity", "with_score"])]
+        count_by_language: bool,
+
+        /// Only show repos with this priority
+        #[clap(long, value_enum)]
+        priority: Option<Priority>,
+
+        /// Print each repo's priority marker (`!!!` high, `!!` medium, `!` low) before its path
+        #[clap(long, conflicts_with = "zero_terminated")]
+        show_priority: bool,
+
+        /// Print a summary of each repo's uncommitted changes after its path
+        #[clap(long, conflicts_with = "zero_terminated")]
+        show_git_status: bool,
+
+        /// Only show repos linked to a ticket or issue
+        #[clap(long)]
+        has_project_url: bool,
+
+        /// How long to wait for `git status` in each repo
+        #[clap(
+            long,
+            value_name = "MS",
+            default_value_t = 2000,
+            requires = "show_git_status"
+        )]
+        timeout_ms: u64,
+
+        /// Group repos by how recently they were accessed
+        #[clap(long, conflicts_with_all = ["output", "file", "zero_terminated", "export_env", "export_fish"])]
+        by_activity: bool,
+
+        /// Group repos by a property
+        #[clap(
+            long,
+            value_enum,
+            conflicts_with_all = ["by_activity", "output", "file", "zero_terminated", "export_env", "export_fish"]
+        )]
+        group_by: Option<ListGroup>,
+
+        /// Put the language version (e.g. Rust edition, Node.js engine) next to the
+        /// language when grouping by language
+        #[clap(long, requires = "group_by")]
+        show_version: bool,
+
+        /// Print each repo with a template, e.g. '{name}: {path} [{tags:sep=|}]'.
+        /// Placeholders: name, path, tags, language, last_modified, score
+        #[clap(
+            long,
+            conflicts_with_all = ["output", "file", "zero_terminated", "export_env", "export_fish", "by_activity", "group_by"]
+        )]
+        template: Option<String>,
+
+        /// Print each repo's number of tags before its path
+        #[clap(short = 'T', long, conflicts_with = "zero_terminated")]
+        show_tag_count: bool,
+
+        /// Only show repos with all of these tags (comma separated)
+        #[clap(short, long)]
+        tags: Option<String>,
+
+        /// Show N randomly picked repos, e.g. to rediscover forgotten ones
+        #[clap(long, value_name = "N")]
+        random: Option<usize>,
+
+        /// Seed for --random, to get the same picks every time
+        #[clap(long, requires = "random")]
+        seed: Option<u64>,
+
+        /// Print when each repo was last accessed before its path
+        #[clap(long, conflicts_with = "zero_terminated")]
+        show_last_access: bool,
+
+        /// Show the N most recently accessed repos with their last access, ignoring
+        /// all filters and sort options
+        #[clap(long, value_name = "N", visible_alias = "last-n-accessed")]
+        last_accessed: Option<usize>,
+    },
+
+    /// List recently accessed repos, most frecent first
+    Active {
+        /// Only show repos with all of these tags (comma separated)
+        #[clap(short, long)]
+        tags: Option<String>,
+
+        /// Number of days a repo counts as active after its last access [default: 30]
+        #[clap(short, long)]
+        days: Option<u64>,
+    },
+
+    /// List the most frecent repos with their scores, regardless of tags
+    Top {
+        /// Number of repos to show
+        #[clap(default_value_t = 10)]
+        count: usize,
+
+        /// Output format
+        #[clap(short, long, value_enum, default_value_t = TopOutput::Text)]
+        output: TopOutput,
+    },
+
+    /// List the repos accessed most recently with how long ago that was
+    Recent {
+        /// Number of repos to show
+        #[clap(default_value_t = 10)]
+        count: usize,
+    },
+
+    /// Show stored information about a repo
+    Info {
+        /// Path to repository
+        path: String,
+
+        /// Show how many files are ignored by git (e.g. build artifacts)
+        #[clap(long)]
+        show_ignored: bool,
+
+        /// Show how many stash entries the repo has
+        #[clap(long)]
+        show_stash: bool,
+
+        /// Show the status of the latest GitHub Actions or GitLab CI run
+        #[clap(long)]
+        show_ci_status: bool,
+
+        /// Open the repo's ticket or issue URL in the browser
+        #[clap(long)]
+        open_project: bool,
+
+        /// Output format
+        #[clap(long, value_enum, default_value_t = InfoFormat::Text)]
+        format: InfoFormat,
+    },
+
+    /// Track the git repos referenced by a VS Code workspace file
+    ImportFromVscode {
+        /// Path to the *.code-workspace file
+        #[clap(short, long)]
+        workspace: String,
+
+        /// Tags for the imported repositories (comma separated)
+        #[clap(short, long, default_value = "")]
+        tags: String,
+    },
+
+    /// Track the git repos open in tmux panes
+    ImportFromTmux {
+        /// Tags for the imported repositories (comma separated)
+        #[clap(short, long)]
+        tags: Option<String>,
+
+        /// Only show which repos would be added
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Also list pane directories that aren't git repos
+        #[clap(short, long)]
+        verbose: bool,
+    },
+
+    /// Periodically remove repos whose paths no longer exist
+    Watch {
+        /// Seconds between cleanups, at least 1
+        #[clap(short, long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Run in the background and return immediately
+        #[clap(short, long)]
+        daemon: bool,
+
+        /// Stop the running cleanup daemon
+        #[clap(long, conflicts_with = "daemon")]
+        stop: bool,
+    },
+
+    /// Open a shell in a repo, or a tmux window or pane with --tmux
+    Open {
+        /// Path, ID or name of the repo
+        repo: String,
+
+        /// Open a tmux window (or pane, see `tmux_open_mode` in the config) instead
+        /// of a shell. Falls back to a shell outside of tmux
+        #[clap(long)]
+        tmux: bool,
+
+        /// With --tmux, split the current window side by side instead
+        #[clap(long, requires = "tmux")]
+        split: bool,
+
+        /// Open the repo in an editor instead: `default_editor` from the config,
+        /// else $EDITOR, else $VISUAL
+        #[clap(long, conflicts_with = "tmux")]
+        editor: bool,
+    },
+
+    /// Access a repo (updates frecency)
+    Access {
+        /// Path to repository
+        path: String,
+    },
+
+    /// Reset frequency data or tags for a repo or all repos
+    Reset {
+        /// Path to repository (if not provided, resets all repos)
+        #[clap(short, long)]
+        path: Option<String>,
+
+        /// Reset access history (the default when nothing else is chosen)
+        #[clap(long)]
+        frecency: bool,
+
+        /// Clear tags, leaving access history alone
+        #[clap(long)]
+        tags: bool,
+
+        /// Reset both access history and tags
+        #[clap(long, conflicts_with_all = ["frecency", "tags"])]
+        all_data: bool,
+
+        /// Replace all tags with this single tag instead of clearing them
+        #[clap(long, value_name = "TAG")]
+        to_default_tag: Option<String>,
+    },
+
+    /// List all tags with their usage counts
+    Tags {
+        /// Render an ASCII graph of tags that are used together
+        #[clap(long)]
+        graph: bool,
+
+        /// Minimum number of shared repos for two tags to be linked in the graph
+        #[clap(long, default_value_t = 1)]
+        threshold: usize,
+
+        /// Show each tag's description from the tag vocabulary
+        #[clap(long)]
+        with_descriptions: bool,
+
+        /// How to order the tags
+        #[clap(long, value_enum, default_value_t = TagSort::Count)]
+        sort: TagSort,
+
+        /// Remove vocabulary definitions of tags no repo uses anymore
+        #[clap(long)]
+        prune_orphaned: bool,
+
+        /// Write every repo's tags to a JSON file
+        #[clap(long, value_name = "FILE", conflicts_with = "import")]
+        export: Option<String>,
+
+        /// Add the tags from a file written by --export to the repos with the same paths
+        #[clap(long, value_name = "FILE")]
+        import: Option<String>,
+
+        /// With --import, add to the existing tags (the default)
+        #[clap(long, requires = "import", conflicts_with = "replace")]
+        merge: bool,
+
+        /// With --import, replace the existing tags
+        #[clap(long, requires = "import")]
+        replace: bool,
+
+        /// Output format of the tag list
+        #[clap(short, long, value_enum, default_value_t = TagsOutput::Text, conflicts_with_all = ["graph", "with_descriptions", "prune_orphaned"])]
+        output: TagsOutput,
+    },
+
+    /// Clone every tracked repo with a known remote into a directory, e.g. on a new machine
+    CloneAll {
+        /// Directory to clone the repos into
+        #[clap(long)]
+        to_dir: String,
+
+        /// Number of repos to clone at the same time
+        #[clap(long, value_name = "N", default_value_t = 4)]
+        parallel: usize,
+    },
+
+    /// Replace stored paths with their canonical form, resolving symlinks
+    PathNormalize,
+
+    /// Clone a repo and track it in one step
+    Clone {
+        /// URL to clone: https://, git@host:path or a local path
+        url: String,
+
+        /// Where to clone to [default: <default_projects_dir or current dir>/<repo name>]
+        #[clap(short, long)]
+        path: Option<String>,
+
+        /// Tags for the clone (comma separated)
+        #[clap(short, long)]
+        tags: Option<String>,
+    },
+
+    /// Run `git pull --ff-only` in a tracked repo, or in all of them
+    Pull {
+        /// Path, ID or name of the repo to pull [default: all repos]
+        name: Option<String>,
+
+        /// Only run `git fetch`
+        #[clap(long)]
+        fetch_only: bool,
+
+        /// Number of repos to pull at the same time, 1 pulls one after another
+        #[clap(long, value_name = "N", default_value_t = 4)]
+        parallel: usize,
+
+        /// Seconds to wait for each repo
+        #[clap(long, value_name = "SECS", default_value_t = 120)]
+        timeout: u64,
+    },
+
+    /// Show the branch and uncommitted changes of every tracked repo
+    Status {
+        /// Leave out repos without changes
+        #[clap(long)]
+        dirty_only: bool,
+
+        /// Number of repos to check at the same time
+        #[clap(long, value_name = "N", default_value_t = 4)]
+        parallel: usize,
+    },
+
+    /// Check tracked repos for problems
+    Verify {
+        /// Check that each repo's recorded remote URL can still be reached
+        #[clap(long)]
+        remote_exists: bool,
+
+        /// Forget remote URLs that no longer exist
+        #[clap(long, requires = "remote_exists")]
+        fix: bool,
+
+        /// Seconds to wait for each remote
+        #[clap(long, value_name = "SECS", default_value_t = 10)]
+        timeout: u64,
+
+        /// Number of remotes to check at the same time
+        #[clap(long, value_name = "N", default_value_t = 4)]
+        parallel: usize,
+    },
+
+    /// Check the stored repos for missing paths, non-git directories and repos
+    /// stored twice. Fails if problems are found, e.g. for CI
+    Validate {
+        /// Stop tracking repos whose path no longer exists
+        #[clap(long)]
+        fix: bool,
+    },
+
+    /// Stop tracking the repos listed in a file, one path per line
+    BatchRemove {
+        /// File of paths, blank lines and lines starting with `#` are skipped
+        #[clap(long, value_name = "FILE")]
+        from_file: String,
+
+        /// Only show which repos would be removed
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Don't fail when a path isn't tracked
+        #[clap(long)]
+        ignore_not_found: bool,
+    },
+
+    /// Archive the repos that have not been accessed for a while
+    Archive {
+        /// Archive repos last accessed longer ago than this, e.g. 30d, 6mo or 1y
+        #[clap(long, value_name = "DURATION")]
+        older_than: String,
+
+        /// Show which repos would be archived without archiving them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// List the other tracked repos in the same parent directory as a repo
+    Siblings {
+        /// Path to repository
+        path: String,
+
+        /// Look this many directory levels up instead of only the parent
+        #[clap(long, default_value_t = 1)]
+        depth: usize,
+    },
+
+    /// Export tracked repos in another format
+    Export {
+        /// Format to export to
+        #[clap(long, value_enum)]
+        format: ExportFormat,
+
+        /// Write the output to a file instead of stdout
+        #[clap(short, long, visible_alias = "output")]
+        file: Option<String>,
+    },
+
+    /// Track the repos in a file written by `export`, merging their tags into
+    /// repos that are already tracked
+    Import {
+        /// Format of the file
+        #[clap(long, value_enum)]
+        format: ImportFormat,
+
+        /// File to import
+        file: String,
+
+        /// Only show what would be added or updated
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Re-create the repos file from the git repos found in a directory, e.g.
+    /// after it got corrupted. The old file is kept as repos.json.bak
+    Recover {
+        /// Directory to search for git repos
+        #[clap(long)]
+        scan_dir: String,
+
+        /// How many directory levels below the scan directory to search
+        #[clap(long, value_name = "N", default_value_t = 3)]
+        max_depth: usize,
+    },
+
+    /// Track every git repo below a directory
+    Scan {
+        /// Directory to search for git repos
+        root: String,
+
+        /// Tags for the newly tracked repos (comma separated)
+        #[clap(short, long)]
+        tags: Option<String>,
+
+        /// How many directory levels below the root to search
+        #[clap(long, value_name = "N", default_value_t = 3)]
+        depth: usize,
+    },
+
+    /// Find repos that share a name but are tracked under different paths
+    ScanConflicts {
+        /// Ask for a new name for each conflicting repo
+        #[clap(long)]
+        rename_interactive: bool,
+    },
+
+    /// Print the short stable ID of a repo. Commands taking a repo path also accept its ID
+    RepoId {
+        /// Path to repository
+        path: String,
+    },
+
+    /// Rename a tag on every repo that has it, ignoring case
+    TagRename {
+        /// Tag to rename
+        old: String,
+
+        /// New name of the tag
+        new: String,
+    },
+
+    /// Change the name a repo is shown as, the directory stays where it is
+    Rename {
+        /// Current name, path or ID of the repo
+        old_name: String,
+
+        /// New name
+        new_name: String,
+    },
+
+    /// Remove a tag from every repo that has it, ignoring case
+    TagDelete {
+        /// Tag to remove
+        tag: String,
+
+        /// Stop tracking repos that have no tags left, instead of warning about them
+        #[clap(long)]
+        remove_empty: bool,
+    },
+
+    /// Import tag descriptions from a file of `tag: description` lines
+    TagImport {
+        /// Path to the vocabulary file
+        file: String,
+    },
+
+    /// Inspect the mangit configuration
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Attach a free-text note to a repo, separate from its description
+    Note {
+        #[clap(subcommand)]
+        action: NoteAction,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ListSort {
+    /// Most frecent repos first
+    Frecency,
+    /// Oldest repos first, by first commit date
+    Age,
+    /// Repos with the most tags first
+    TagCount,
+    /// High priority repos first, repos without a priority last
+    Priority,
+    /// Most recently accessed repos first
+    LastAccess,
+    /// Highest value of `--sort-expr` first
+    Custom,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AddOutput {
+    /// A message saying whether the repo was added or updated
+    Text,
+    /// The name, path, ID and whether the repo is new as a JSON object
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum TagsOutput {
+    /// `tag: count` lines under a heading
+    Text,
+    /// A JSON array of `{"tag": ..., "count": ...}` objects
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum TopOutput {
+    /// `path (score)` lines
+    Text,
+    /// A JSON array of repos like `search --output json`
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ExportFormat {
+    /// Graphviz graph of repos and their tags, e.g. for `dot -Tpng graph.dot`
+    Dot,
+    /// Path, name, tags, description, language and last access, with a header row
+    Csv,
+    /// Pretty-printed array of repos
+    Json,
+    /// One `[[repos]]` table per repo
+    Toml,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ListGroup {
+    /// Group by detected language
+    Language,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum TagSort {
+    /// Most used tags first
+    Count,
+    /// Alphabetically
+    Name,
+    /// Tags whose repos have the highest average frecency first
+    Frecency,
+}
+
+#[derive(Subcommand, Debug)]
+enum SavedSearchAction {
+    /// Show all saved searches
+    List,
+    /// Remove a saved search
+    Delete {
+        /// Name of the saved search
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum NoteAction {
+    /// Set a repo's note, replacing the old one
+    Set {
+        /// Path to repository
+        path: String,
+        /// Text of the note
+        text: String,
+    },
+    /// Print a repo's note
+    Get {
+        /// Path to repository
+        path: String,
+    },
+    /// Remove a repo's note
+    Clear {
+        /// Path to repository
+        path: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Check all config fields and report problems
+    Validate,
+    /// Choose the default editor, from a menu of the installed ones if none is given
+    SetEditor {
+        /// Editor command, e.g. `nvim` or `code -w`
+        editor: Option<String>,
+    },
+    /// Set a config value
+    Set { key: ConfigKey, value: String },
+}
+
+/// Config values `config set` can change
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ConfigKey {
+    /// Access times kept per repo for frecency, 1 to 1000
+    MaxAccessHistory,
+    /// Frecency weight of an access within the last minute
+    #[value(name = "frecency.weight-minute")]
+    FrecencyWeightMinute,
+    /// Frecency weight of an access within the last 30 minutes
+    #[value(name = "frecency.weight-half-hour")]
+    FrecencyWeightHalfHour,
+    /// Frecency weight of an access within the last hour
+    #[value(name = "frecency.weight-hour")]
+    FrecencyWeightHour,
+    /// Frecency weight of an access within the last day
+    #[value(name = "frecency.weight-day")]
+    FrecencyWeightDay,
+    /// Frecency weight of an access within the last week
+    #[value(name = "frecency.weight-week")]
+    FrecencyWeightWeek,
+    /// Frecency weight of an access more than a week ago
+    #[value(name = "frecency.weight-older")]
+    FrecencyWeightOlder,
+}
+
+fn parse_tags(tags_str: &str) -> Vec<String> {
+    tags_str
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Builds display repos (with detected language) for scored repo paths
+fn to_repositories(storage: &Storage, scored: Vec<(String, f64)>) -> Vec<(Repository, f64)> {
+    scored
+        .into_iter()
+        .map(|(path, score)| {
+            let mut repo = Repository::from((path.as_str(), &storage.repos[&path]));
+            if repo.language.is_none() {
+                repo.detect_language();
+            }
+            (repo, score)
+        })
+        .collect()
+}
+
+/// Prints one line per repo, formatted by a `--template`
+fn print_with_template(storage: &Storage, scored: Vec<(String, f64)>, template: &Template) {
+    for (repo, score) in to_repositories(storage, scored) {
+        println!("{}", template.render(&repo, score));
+    }
+}
+
+/// Prints repos grouped under their language, and its version if detected.
+/// Repos without a known language come last
+fn print_language_groups(repos: &[(Repository, f64)]) {
+    let mut groups: BTreeMap<(bool, String), Vec<&str>> = BTreeMap::new();
+    for (repo, _) in repos {
+        let key = match (&repo.language, &repo.language_version) {
+            (Some(language), Some(version)) => (false, format!("{} {}", language, version)),
+            (Some(language), None) => (false, language.clone()),
+            (None, _) => (true, "Unknown".to_string()),
+        };
+        groups.entry(key).or_default().push(&repo.path);
+    }
+
+    for (i, ((_, label), paths)) in groups.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}:", label);
+        for path in paths {
+            println!("  {}", path);
+        }
+    }
+}
+
+/// Asks which of the editors to use, the first one on empty input
+fn choose_editor(editors: &[String]) -> Result<String> {
+    if editors.is_empty() {
+        return Err(anyhow!(
+            "No editors found, pass one: mangit config set-editor <editor>"
+        ));
+    }
+
+    for (i, editor) in editors.iter().enumerate() {
+        println!("{}) {}", i + 1, editor);
+    }
+    let stdin = io::stdin();
+    loop {
+        print!("Editor [1]: ");
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Err(anyhow!("No editor chosen"));
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(editors[0].clone());
+        }
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=editors.len()).contains(&n) => return Ok(editors[n - 1].clone()),
+            _ => println!("Enter a number from 1 to {}", editors.len()),
+        }
+    }
+}
+
+/// Maps the `--or` and `--require-exact-tag-set` flags to a tag requirement
+fn tag_requirement(or: bool, exact: bool) -> TagRequirement {
+    if exact {
+        TagRequirement::Exact
+    } else if or {
+        TagRequirement::Any
+    } else {
+        TagRequirement::All
+    }
+}
+
+/// Maps the `--require-language` and `--language-unknown` flags to a filter
+fn language_filter(require_language: bool, language_unknown: bool) -> LanguageFilter {
+    if require_language {
+        LanguageFilter::Known
+    } else if language_unknown {
+        LanguageFilter::Unknown
+    } else {
+        LanguageFilter::Any
+    }
+}
+
+/// Maps the `--include-archived` and `--archived-only` flags to a filter
+fn archive_filter(include_archived: bool, archived_only: bool) -> ArchiveFilter {
+    if archived_only {
+        ArchiveFilter::Only
+    } else if include_archived {
+        ArchiveFilter::Include
+    } else {
+        ArchiveFilter::Exclude
+    }
+}
+
+/// Builds the last access bounds of `--since` and `--before`
+fn access_window(since: Option<&str>, before: Option<&str>) -> Result<AccessWindow> {
+    Ok(AccessWindow {
+        since: since.map(parse_duration).transpose()?,
+        before: before.map(parse_duration).transpose()?,
+    })
+}
+
+/// Prints one path per record, newline or null terminated
+fn print_paths(paths: &[String], zero_terminated: bool) -> Result<()> {
+    write_paths(&mut io::stdout().lock(), paths, zero_terminated)
+}
+
+/// Writes one path per record. Null terminated records keep paths that contain
+/// newlines intact
+fn write_paths(out: &mut impl Write, paths: &[String], zero_terminated: bool) -> Result<()> {
+    let terminator = if zero_terminated { '\0' } else { '\n' };
+    for path in paths {
+        write!(out, "{}{}", path, terminator)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Extra columns printed before each path in plain path output
+#[derive(Debug, Clone, Copy, Default)]
+struct PathColumns<'a> {
+    score: bool,
+    tag_count: bool,
+    priority: bool,
+    last_access: bool,
+    // Git status summaries by path, printed after the path
+    git_status: Option<&'a HashMap<String, String>>,
+}
+
+impl PathColumns<'_> {
+    fn is_empty(&self) -> bool {
+        !self.score
+            && !self.tag_count
+            && !self.priority
+            && !self.last_access
+            && self.git_status.is_none()
+    }
+}
+
+/// Writes scored repos in the given format, to `file` if set or stdout otherwise
+fn print_repos(
+    storage: &Storage,
+    scored: Vec<(String, f64)>,
+    format: OutputFormat,
+    zero_terminated: bool,
+    file: Option<&str>,
+    columns: PathColumns<'_>,
+) -> Result<()> {
+    if format == OutputFormat::Fzf {
+        if fzf::is_fzf_available() {
+            let items = to_repositories(storage, scored)
+                .into_iter()
+                .map(|(repo, _)| FzfItem {
+                    name: repo.name,
+                    tags: repo.tags,
+                    path: repo.path,
+                })
+                .collect();
+            if let Some(selected) = fzf::launch_fzf_picker(items)? {
+                println!("{}", selected);
+            }
+            return Ok(());
+        }
+        return print_repos(
+            storage,
+            scored,
+            OutputFormat::Paths,
+            zero_terminated,
+            file,
+            columns,
+        );
+    }
+
+    if format == OutputFormat::Rofi {
+        if rofi::is_rofi_available() {
+            let items = to_repositories(storage, scored)
+                .into_iter()
+                .map(|(repo, _)| RofiItem {
+                    name: repo.name,
+                    path: repo.path,
+                })
+                .collect();
+            if let Some(selected) = rofi::launch_rofi_picker(items)? {
+                println!("{}", selected);
+            }
+            return Ok(());
+        }
+        eprintln!("rofi not found in PATH, printing paths instead");
+        return print_repos(
+            storage,
+            scored,
+            OutputFormat::Paths,
+            zero_terminated,
+            file,
+            columns,
+        );
+    }
+
+    let Some(file) = file else {
+        if format == OutputFormat::Paths && columns.is_empty() {
+            let paths: Vec<String> = scored.into_iter().map(|(path, _)| path).collect();
+            return print_paths(&paths, zero_terminated);
+        }
+        print!("{}", render_repos(storage, scored, format, columns));
+        return Ok(());
+    };
+
+    let mut out =
+        fs::File::create(file).map_err(|e| anyhow!("Failed to create {}: {}", file, e))?;
+    if format == OutputFormat::Paths && columns.is_empty() {
+        let paths: Vec<String> = scored.into_iter().map(|(path, _)| path).collect();
+        write_paths(&mut out, &paths, zero_terminated)?;
+    } else {
+        write!(out, "{}", render_repos(storage, scored, format, columns))?;
+        out.flush()?;
+    }
+    println!("Wrote {}", file);
+    Ok(())
+}
+
+/// Renders scored repos in the given format
+fn render_repos(
+    storage: &Storage,
+    scored: Vec<(String, f64)>,
+    format: OutputFormat,
+    columns: PathColumns<'_>,
+) -> String {
+    match format {
+        OutputFormat::Paths | OutputFormat::Fzf | OutputFormat::Rofi => scored
+            .into_iter()
+            .map(|(path, score)| {
+                let mut row = String::new();
+                if columns.score {
+                    row += &format!("{:.1}\t", score);
+                }
+                if columns.tag_count {
+                    row += &format!("{:>3}\t", storage.repos[&path].tags.len());
+                }
+                if columns.priority {
+                    let priority = storage.repos[&path].priority;
+                    row += &format!("{:<3}\t", priority.map_or("", |p| p.indicator()));
+                }
+                if columns.last_access {
+                    let last_access = storage.repos[&path].access_times.iter().max();
+                    let ago = last_access.map_or("never".to_string(), |time| {
+                        format_time_ago(*time, Utc::now())
+                    });
+                    row += &format!("{:<8}\t", ago);
+                }
+                // No `[ARCHIVED]` marker, the path has to work in `cd $(mangit ...)`
+                row += &path;
+                if let Some(statuses) = columns.git_status {
+                    row += "\t";
+                    row += statuses.get(&path).map_or("", String::as_str);
+                }
+                row + "\n"
+            })
+            .collect(),
+        OutputFormat::Tsv => TsvRenderer::render(&to_repositories(storage, scored)),
+        OutputFormat::Json => JsonRenderer::render(&to_repositories(storage, scored)),
+        OutputFormat::Html => HtmlRenderer::render(&to_repositories(storage, scored), Utc::now()),
+        OutputFormat::Env | OutputFormat::Fish => {
+            let repos: Vec<Repository> = to_repositories(storage, scored)
+                .into_iter()
+                .map(|(repo, _)| repo)
+                .collect();
+            if format == OutputFormat::Env {
+                render_env_declarations(&repos)
+            } else {
+                render_fish_abbreviations(&repos)
+            }
+        }
+        OutputFormat::NvimTelescope | OutputFormat::NvimQuickfix => {
+            let repos: Vec<Repository> = to_repositories(storage, scored)
+                .into_iter()
+                .map(|(repo, _)| repo)
+                .collect();
+            if format == OutputFormat::NvimTelescope {
+                NvimTelescopeRenderer::render(&repos)
+            } else {
+                NvimQuickfixRenderer::render(&repos)
+            }
+        }
+    }
+}
+
+/// Sets a tracked repo's description from its README
+fn refresh_description(storage: &mut Storage, path: &str) -> Result<()> {
+    let description = extract_readme_description(Path::new(path))?;
+    let repo_access = storage
+        .get_repo_mut(path)?
+        .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+
+    match description {
+        Some(description) => {
+            println!("Description: {}", description);
+            repo_access.description = description;
+        }
+        None => println!("No description found in README of: {}", path),
+    }
+
+    Ok(())
+}
+
+/// How long a cached last commit date is trusted before asking git again
+const LAST_COMMIT_CACHE_MINUTES: i64 = 60;
+
+/// Keeps only the repos with commits after `since`, running git in parallel.
+/// Last commit dates are cached in storage so date checks can skip git
+fn filter_changed_after(
+    storage: &mut Storage,
+    paths: Vec<String>,
+    since: &ChangedAfter,
+    parallel: usize,
+) -> Vec<String> {
+    let now = Utc::now();
+    let cache_limit = now - Duration::minutes(LAST_COMMIT_CACHE_MINUTES);
+
+    // Cached answers for date checks, None means git has to be asked
+    let cached: Vec<Option<bool>> = paths
+        .iter()
+        .map(|path| match since {
+            ChangedAfter::Date(date) => storage
+                .repos
+                .get(path)
+                .filter(|repo_access| {
+                    repo_access
+                        .last_commit_check
+                        .is_some_and(|checked| checked > cache_limit)
+                })
+                .map(|repo_access| {
+                    repo_access
+                        .last_commit_date
+                        .is_some_and(|last| last > *date)
+                }),
+            ChangedAfter::Ref(_) => None,
+        })
+        .collect();
+
+    let to_check: Vec<&String> = paths
+        .iter()
+        .zip(&cached)
+        .filter(|(_, cached)| cached.is_none())
+        .map(|(path, _)| path)
+        .collect();
+
+    let checked = parallel_map(&to_check, parallel, |path| match since {
+        ChangedAfter::Date(_) => git::last_commit_date(path).map(CommitCheck::LastCommit),
+        ChangedAfter::Ref(git_ref) => {
+            git::has_commits_after_ref(path, git_ref).map(CommitCheck::HasCommits)
+        }
+    });
+
+    let mut changed: HashMap<&str, bool> = HashMap::new();
+    for (path, result) in to_check.iter().zip(checked) {
+        let is_changed = match result {
+            Ok(CommitCheck::LastCommit(last_commit)) => {
+                if let Some(repo_access) = storage.repos.get_mut(path.as_str()) {
+                    repo_access.last_commit_check = Some(now);
+                    repo_access.last_commit_date = last_commit;
+                }
+                match since {
+                    ChangedAfter::Date(date) => last_commit.is_some_and(|last| last > *date),
+                    ChangedAfter::Ref(_) => false,
+                }
+            }
+            Ok(CommitCheck::HasCommits(has_commits)) => has_commits,
+            Err(e) => {
+                eprintln!("Skipping {}: {}", path, e);
+                false
+            }
+        };
+        changed.insert(path.as_str(), is_changed);
+    }
+
+    paths
+        .iter()
+        .zip(cached)
+        .filter(|(path, cached)| cached.unwrap_or_else(|| changed[path.as_str()]))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+enum CommitCheck {
+    LastCommit(Option<DateTime<Utc>>),
+    HasCommits(bool),
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load()?;
+
+    match cli.command {
+        Commands::Init => {
+            config.ensure_mangit_dir()?;
+            let storage = Storage::new(&config)?;
+            storage.save(&config)?;
+            println!("Initialized mangit at {}", config.mangit_dir);
+            Ok(())
+        }
+
+        Commands::Add {
+            path,
+            tags,
+            set_description_from_readme,
+            priority,
+            require_projects_dir,
+            name,
+            infer_name_from_git,
+            link_to_project_management,
+            jira,
+            github_issue,
+            linear,
+            auto_set_priority,
+            check_ci_config,
+            check_duplicate_name,
+            allow_duplicate_names,
+            generate_id,
+            output,
+        } => {
+            let mut storage = Storage::new(&config)?;
+            let mut tags = config.merge_default_tags(parse_tags(&tags));
+
+            let project_url = [
+                (link_to_project_management, ProjectService::Any),
+                (jira, ProjectService::Jira),
+                (github_issue, ProjectService::GitHubIssue),
+                (linear, ProjectService::Linear),
+            ]
+            .into_iter()
+            .find_map(|(url, service)| url.map(|url| (url, service)));
+            if let Some((url, service)) = &project_url {
+                validate_project_url(url, *service)?;
+            }
+
+            match &config.default_projects_dir {
+                Some(projects_dir) => {
+                    let abs_path = Storage::to_absolute_path(&path)?;
+                    if !is_under_dir(&abs_path, projects_dir) {
+                        if require_projects_dir {
+                            return Err(anyhow!(
+                                "{} is not under the projects directory {}",
+                                abs_path,
+                                projects_dir
+                            ));
+                        }
+                        if config.warn_outside_projects_dir {
+                            eprintln!(
+                                "Warning: {} is not under the projects directory {}",
+                                abs_path, projects_dir
+                            );
+                        }
+                    }
+                }
+                None if require_projects_dir => {
+                    return Err(anyhow!(
+                        "--require-projects-dir needs default_projects_dir to be set in the config"
+                    ));
+                }
+                None => {}
+            }
+
+            // Point out tags that aren't in the vocabulary, usually typos
+            let vocab = TagVocabulary::load(&config)?;
+            if !vocab.tags.is_empty() {
+                let unknown: Vec<&str> = tags
+                    .iter()
+                    .filter(|tag| vocab.describe(tag).is_none())
+                    .map(String::as_str)
+                    .collect();
+                if !unknown.is_empty() {
+                    let mut known: Vec<&str> = vocab.tags.keys().map(String::as_str).collect();
+                    known.sort();
+                    eprintln!("Tags not in vocabulary: {}", unknown.join(", "));
+                    eprintln!("Known tags: {}", known.join(", "));
+                }
+            }
+
+            let abs_path = Storage::to_absolute_path(&path)?;
+            if check_ci_config || config.auto_tag_ci {
+                for tag in detect_ci(Path::new(&abs_path)) {
+                    if !tags.contains(&tag) {
+                        tags.push(tag);
+                    }
+                }
+            }
+
+            let remote_url = git::remote_origin_url(&abs_path);
+            let name = name.or_else(|| {
+                if infer_name_from_git || config.prefer_remote_name {
+                    remote_url.as_deref().and_then(infer_name_from_remote_url)
+                } else {
+                    None
+                }
+            });
+
+            let priority = priority.or_else(|| {
+                if auto_set_priority || config.auto_set_priority {
+                    match_priority_rules(&abs_path, &config.priority_rules)
+                } else {
+                    None
+                }
+            });
+
+            // A name given to the repo has to be unique, as with `rename`. Only the
+            // directory name may be shared, and only if allowed
+            if let Some(name) = &name {
+                storage
+                    .check_duplicate_name(name, &abs_path)
+                    .map_err(|e| anyhow!("{} (choose another with --name)", e))?;
+            } else if !allow_duplicate_names
+                && (check_duplicate_name || config.prevent_duplicate_names)
+            {
+                storage
+                    .check_duplicate_name(&repo_name_from_path(&abs_path), &abs_path)
+                    .map_err(|e| {
+                        anyhow!("{} (pass --allow-duplicate-names to add it anyway)", e)
+                    })?;
+            }
+
+            let mut result = storage
+                .add_repo(&path, tags)
+                .map_err(|e| anyhow!("Failed to add repo: {}", e))?;
+            if output == AddOutput::Text && !generate_id {
+                if result.is_new {
+                    println!("Added repo: {}", path);
+                } else {
+                    println!("Updated existing repo: {}", path);
+                }
+            }
+
+            if let Some(repo_access) = storage.get_repo_mut(&path)? {
+                if name.is_some() {
+                    repo_access.name = name;
+                }
+                if let Some((url, _)) = project_url {
+                    repo_access.project_url = Some(url);
+                }
+                if priority.is_some() {
+                    repo_access.priority = priority;
+                }
+                if remote_url.is_some() {
+                    repo_access.remote_url = remote_url;
+                }
+            }
+
+            if set_description_from_readme {
+                refresh_description(&mut storage, &path)?;
+            }
+
+            storage.save(&config)?;
+
+            if let Some(repo_access) = storage.get_repo(&result.path)? {
+                result.name = repo_access.display_name(&result.path);
+            }
+            match output {
+                AddOutput::Json => println!("{}", serde_json::to_string(&result)?),
+                AddOutput::Text if generate_id => println!("{}", result.id),
+                AddOutput::Text => {}
+            }
+            Ok(())
+        }
+
+        Commands::Delete {
+            path,
+            matching,
+            dry_run,
+            yes,
+        } => {
+            let mut storage = Storage::new(&config)?;
+
+            if let Some(query) = matching {
+                let matches = |path: &str, repo_access: &RepoAccess| {
+                    Repository::from((path, repo_access)).matches_query(&query)
+                };
+
+                let paths = storage.find_matching(matches);
+                if paths.is_empty() {
+                    println!("No repos match: {}", query);
+                    return Ok(());
+                }
+
+                if dry_run {
+                    print_paths(&paths, false)?;
+                    println!("Would remove {} repos", paths.len());
+                    return Ok(());
+                }
+
+                if paths.len() > 1 && !yes {
+                    return Err(anyhow!(
+                        "{} repos match, pass --yes to remove them all",
+                        paths.len()
+                    ));
+                }
+
+                let removed = storage.remove_matching(matches)?;
+                storage.save(&config)?;
+                println!("Removed {} repos", removed.len());
+                return Ok(());
+            }
+
+            let path = path.ok_or_else(|| anyhow!("A path or --matching is required"))?;
+
+            match storage.delete_repo(&path) {
+                Ok(true) => {
+                    println!("Deleted repo: {}", path);
+                    storage.save(&config)?;
+                    Ok(())
+                }
+                Ok(false) => Err(anyhow!("Repo not found: {}", path)),
+                Err(e) => Err(anyhow!("Failed to delete repo: {}", e)),
+            }
+        }
+
+        Commands::Update {
+            path,
+            tags,
+            fetch_first_commit,
+            refresh_description: refresh,
+            apply_tag_file,
+            refresh_all,
+            auto_generate_description: generate_descriptions,
+            replace_with_file_tags,
+        } => {
+            if refresh_all {
+                let mut storage = Storage::new(&config)?;
+                let mut paths: Vec<String> = storage.repos.keys().cloned().collect();
+                paths.sort();
+
+                let mut failed = 0;
+                for path in &paths {
+                    let repo_access = storage.repos.get_mut(path).expect("path from repos");
+                    match repo_access.full_refresh(path) {
+                        Ok(()) => println!("Refreshed: {}", path),
+                        Err(e) => {
+                            eprintln!("Failed to refresh {}: {}", path, e);
+                            failed += 1;
+                        }
+                    }
+                }
+
+                storage.save(&config)?;
+                println!(
+                    "{} repos refreshed, {} failed",
+                    paths.len() - failed,
+                    failed
+                );
+                return Ok(());
+            }
+
+            if generate_descriptions {
+                let mut storage = Storage::new(&config)?;
+                let mut paths: Vec<String> = storage
+                    .repos
+                    .iter()
+                    .filter(|(_, repo_access)| repo_access.description.is_empty())
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                paths.sort();
+
+                let mut updated = 0;
+                for path in &paths {
+                    let repo = Repository::from((path.as_str(), &storage.repos[path]));
+                    if let Some(description) = auto_generate_description(Path::new(path), &repo) {
+                        println!("{}: {}", path, description);
+                        storage
+                            .repos
+                            .get_mut(path)
+                            .expect("path from repos")
+                            .description = description;
+                        updated += 1;
+                    }
+                }
+
+                storage.save(&config)?;
+                println!(
+                    "{} of {} repos without a description updated",
+                    updated,
+                    paths.len()
+                );
+                return Ok(());
+            }
+
+            if apply_tag_file {
+                let mut storage = Storage::new(&config)?;
+                let report = storage.apply_tag_files(replace_with_file_tags);
+                for (path, e) in &report.errors {
+                    eprintln!("{}: {}", path, e);
+                }
+                storage.save(&config)?;
+                println!(
+                    "{} repos updated, {} repos skipped (no tag file), {} repos had errors",
+                    report.updated,
+                    report.skipped,
+                    report.errors.len()
+                );
+                return Ok(());
+            }
+
+            let path = path.ok_or_else(|| anyhow!("A path or --apply-tag-file is required"))?;
+            if tags.is_none() && !fetch_first_commit && !refresh {
+                return Err(anyhow!(
+                    "Nothing to update, pass --tags, --fetch-first-commit or --refresh-description"
+                ));
+            }
+
+            let mut storage = Storage::new(&config)?;
+            let path = storage.resolve_path(&path)?;
+
+            if let Some(tags) = tags {
+                match storage.update_repo(&path, parse_tags(&tags)) {
+                    Ok(true) => println!("Updated repo: {}", path),
+                    Ok(false) => return Err(anyhow!("Repo not found: {}", path)),
+                    Err(e) => return Err(anyhow!("Failed to update repo: {}", e)),
+                }
+            }
+
+            if fetch_first_commit {
+                if storage.get_repo(&path)?.is_none() {
+                    return Err(anyhow!("Repo not found: {}", path));
+                }
+
+                let first_commit = git::first_commit_date(&path)
+                    .map_err(|e| anyhow!("Failed to fetch first commit: {}", e))?;
+                if let Some(repo_access) = storage.get_repo_mut(&path)? {
+                    repo_access.first_commit_date = first_commit;
+                }
+
+                match first_commit {
+                    Some(date) => println!("First commit: {}", date.format("%Y-%m-%d")),
+                    None => println!("No commits found in: {}", path),
+                }
+            }
+
+            if refresh {
+                refresh_description(&mut storage, &path)?;
+            }
+
+            storage.save(&config)?;
+            Ok(())
+        }
+
+        Commands::Search {
+            tags,
+            and: _,
+            or,
+            exact,
+            not,
+            min_frecency,
+            max_frecency,
+            require_language,
+            language_unknown,
+            include_archived,
+            archived_only,
+            score_explain,
+            within_depth,
+            from,
+            within_same_project,
+            since,
+            before,
+            boost_recent,
+            auto_correct,
+            output,
+            zero_terminated,
+            template,
+            limit,
+            cluster_by_tags,
+            save_as,
+            run,
+        } => {
+            let template = template.as_deref().map(Template::parse).transpose()?;
+            let (tag_expr, limit) = match run {
+                Some(name) => {
+                    let searches = SavedSearches::load(&config)?;
+                    let saved = searches
+                        .get(&name)
+                        .ok_or_else(|| anyhow!("No saved search named {}", name))?;
+                    (saved.to_expr(), limit.or(saved.limit))
+                }
+                None => {
+                    let expr = SearchExpr {
+                        include: parse_tags(tags.as_deref().unwrap_or_default()),
+                        exclude: not.iter().flat_map(|tags| parse_tags(tags)).collect(),
+                        requirement: tag_requirement(or, exact),
+                        ..Default::default()
+                    };
+                    (expr, limit)
+                }
+            };
+            let tags = tag_expr.include.join(",");
+
+            if let Some(name) = save_as {
+                let mut searches = SavedSearches::load(&config)?;
+                searches.insert(SavedSearch {
+                    name: name.clone(),
+                    tags: tag_expr.include.clone(),
+                    requirement: tag_expr.requirement,
+                    exclude: tag_expr.exclude.clone(),
+                    limit,
+                });
+                searches.save(&config)?;
+                eprintln!("Saved search as {}", name);
+            }
+
+            let mut storage = Storage::new(&config)?;
+            let mut expr = SearchExpr {
+                min_score: Some(min_frecency.unwrap_or(config.default_search_min_frecency)),
+                max_score: max_frecency,
+                language: language_filter(require_language, language_unknown),
+                within: match within_depth {
+                    Some(depth) => {
+                        let base = match from {
+                            Some(dir) => Storage::to_absolute_path(&dir)?,
+                            None => Storage::to_absolute_path(".")?,
+                        };
+                        Some((Path::new(&base).components().collect(), depth))
+                    }
+                    None => None,
+                },
+                archived: archive_filter(include_archived, archived_only),
+                accessed: access_window(since.as_deref(), before.as_deref())?,
+                paths: match within_same_project {
+                    Some(path) => Some(storage.find_siblings(&path, 1)?.into_iter().collect()),
+                    None => None,
+                },
+                ..tag_expr
+            };
+
+            if expr.include.is_empty() {
+                println!("No tags specified for search");
+                return Ok(());
+            }
+
+            // Shown results record an access, so look at recent accesses first
+            let boosted = boost_recent
+                .map(|hours| storage.accessed_within(Duration::hours(hours as i64)))
+                .unwrap_or_default();
+            let mut matches = storage.score_search(&expr);
+            if matches.is_empty() && auto_correct {
+                let all_tags: Vec<String> = storage.get_all_tags().into_keys().collect();
+                if let Some(corrected) = suggest_correction(&tags, &all_tags) {
+                    println!("Did you mean: {}? (results below)", corrected);
+                    expr.include = parse_tags(&corrected);
+                    matches = storage.score_search(&expr);
+                }
+            }
+            if !boosted.is_empty() {
+                matches = boost_scores(matches, &boosted, config.recent_boost_factor);
+            }
+            if let Some(limit) = limit {
+                matches.truncate(limit);
+            }
+            // Repos cut off by --limit aren't shown, so they don't count as accessed
+            storage.record_search_accesses(&matches);
+
+            // An empty JSON array tells scripts that nothing matched
+            if matches.is_empty() && output != OutputFormat::Json {
+                if expr.include.len() == 1 {
+                    println!("No repos found with tag: {}", expr.include[0]);
+                } else if expr.requirement == TagRequirement::Any {
+                    println!("No repos found with any of tags: {}", tags);
+                } else if expr.requirement == TagRequirement::Exact {
+                    println!("No repos found with exactly tags: {}", tags);
+                } else {
+                    println!("No repos found with all tags: {}", tags);
+                }
+            } else if score_explain {
+                let matcher = SkimMatcherV2::default();
+                // The tags actually searched for, after any auto-correction
+                let query = expr.include.join(",");
+                for (repo, _) in to_repositories(&storage, matches) {
+                    println!("{} {}", score_breakdown(&repo, &query, &matcher), repo.path);
+                }
+                storage.save(&config)?;
+            } else if cluster_by_tags {
+                let repos = to_repositories(&storage, matches);
+                print!(
+                    "{}",
+                    render_clusters(&output::cluster::cluster_by_tags(&repos))
+                );
+                storage.save(&config)?;
+            } else if let Some(template) = &template {
+                print_with_template(&storage, matches, template);
+                storage.save(&config)?;
+            } else {
+                // Paths by default, one per line for easy integration with tools like fzf
+                print_repos(
+                    &storage,
+                    matches,
+                    output,
+                    zero_terminated,
+                    None,
+                    PathColumns::default(),
+                )?;
+                // Save after search to update frecency data
+                storage.save(&config)?;
+            }
+
+            Ok(())
+        }
+
+        Commands::SavedSearches { action } => {
+            let mut searches = SavedSearches::load(&config)?;
+            match action {
+                SavedSearchAction::List => {
+                    if searches.searches.is_empty() {
+                        println!("No saved searches");
+                    }
+                    for search in searches.searches.values() {
+                        let mut line = format!(
+                            "{}\t{}",
+                            search.name,
+                            search.tags.join(match search.requirement {
+                                TagRequirement::Any => " or ",
+                                _ => ",",
+                            })
+                        );
+                        if search.requirement == TagRequirement::Exact {
+                            line += " (exactly)";
+                        }
+                        if !search.exclude.is_empty() {
+                            line += &format!(" not {}", search.exclude.join(","));
+                        }
+                        if let Some(limit) = search.limit {
+                            line += &format!(" (limit {})", limit);
+                        }
+                        println!("{}", line);
+                    }
+                }
+                SavedSearchAction::Delete { name } => {
+                    if !searches.remove(&name) {
+                        return Err(anyhow!("No saved search named {}", name));
+                    }
+                    searches.save(&config)?;
+                    println!("Deleted saved search {}", name);
+                }
+            }
+            Ok(())
+        }
+
+        Commands::List {
+            sort,
+            sort_expr,
+            changed_after,
+            max_ignored,
+            parallel,
+            changed,
+            changed_type,
+            output,
+            file,
+            zero_terminated,
+            show_frecency_score,
+            with_score,
+            show_tag_count,
+            show_last_access,
+            last_accessed,
+            tags,
+            random,
+            seed,
+            require_language,
+            language_unknown,
+            include_archived,
+            archived_only,
+            since,
+            before,
+            with_remote_url,
+            without_remote_url,
+            matching_remote,
+            export_env,
+            export_fish,
+            count_by_language,
+            priority,
+            show_priority,
+            by_activity,
+            group_by,
+            show_version,
+            template,
+            show_git_status,
+            has_project_url,
+            timeout_ms,
+        } => {
+            let template = template.as_deref().map(Template::parse).transpose()?;
+            let sort_expr = match (sort, sort_expr) {
+                (ListSort::Custom, Some(expr)) => Some(SortExpr::parse(&expr)?),
+                (_, Some(_)) => return Err(anyhow!("--sort-expr needs --sort custom")),
+                (_, None) => None,
+            };
+            let mut storage = Storage::new(&config)?;
+            if let Some(n) = last_accessed {
+                let scored = storage
+                    .most_recently_accessed(n)
+                    .into_iter()
+                    .map(|path| {
+                        let score = storage.frecency(&storage.repos[&path]);
+                        (path, score)
+                    })
+                    .collect();
+                return print_repos(
+                    &storage,
+                    scored,
+                    output,
+                    zero_terminated,
+                    file.as_deref(),
+                    PathColumns {
+                        score: show_frecency_score,
+                        tag_count: show_tag_count,
+                        priority: show_priority,
+                        last_access: !zero_terminated,
+                        git_status: None,
+                    },
+                );
+            }
+
+            let sort = if with_score { ListSort::Frecency } else { sort };
+            let mut paths = match sort {
+                ListSort::Frecency => storage.get_repos_by_frecency(),
+                ListSort::Age => storage.get_repos_by_age(),
+                ListSort::TagCount => storage.get_repos_by_tag_count(),
+                ListSort::Priority => storage.get_repos_by_priority(),
+                ListSort::LastAccess => storage.get_repos_by_last_access(),
+                ListSort::Custom => match &sort_expr {
+                    Some(expr) => storage.get_repos_by_expr(expr),
+                    None => unreachable!("clap requires --sort-expr with --sort custom"),
+                },
+            };
+
+            if let Some(priority) = priority {
+                paths.retain(|path| storage.repos[path].priority == Some(priority));
+            }
+
+            if has_project_url {
+                paths.retain(|path| storage.repos[path].project_url.is_some());
+            }
+
+            if let Some(tags) = tags {
+                let expr = SearchExpr {
+                    include: parse_tags(&tags),
+                    ..Default::default()
+                };
+                paths.retain(|path| expr.matches(&storage.repos[path].tags));
+            }
+
+            let language = language_filter(require_language, language_unknown);
+            paths.retain(|path| language.matches(path, &storage.repos[path]));
+
+            let archived = archive_filter(include_archived, archived_only);
+            paths.retain(|path| archived.matches(storage.repos[path].archived));
+
+            let accessed = access_window(since.as_deref(), before.as_deref())?;
+            let now = Utc::now();
+            paths.retain(|path| accessed.contains(&storage.repos[path], now));
+
+            let remote_url = match matching_remote {
+                Some(text) => Some(RemoteUrlFilter::Matching(text)),
+                None if with_remote_url => Some(RemoteUrlFilter::Present),
+                None if without_remote_url => Some(RemoteUrlFilter::Missing),
+                None => None,
+            };
+            if let Some(remote_url) = remote_url {
+                paths.retain(|path| remote_url.matches(storage.repos[path].remote_url.as_deref()));
+            }
+
+            if let Some(changed_after) = changed_after {
+                let since = ChangedAfter::parse(&changed_after);
+                paths = filter_changed_after(&mut storage, paths, &since, parallel);
+                // Keep the cached commit dates for the next run
+                storage.save(&config)?;
+            }
+
+            if let Some(max_ignored) = max_ignored {
+                let counts = parallel_map(&paths, parallel, |path| git::count_ignored_files(path));
+                paths = paths
+                    .into_iter()
+                    .zip(counts)
+                    .filter_map(|(path, count)| match count {
+                        Ok(count) => (count > max_ignored).then_some(path),
+                        Err(e) => {
+                            eprintln!("Skipping {}: {}", path, e);
+                            None
+                        }
+                    })
+                    .collect();
+            }
+
+            if let Some(change_type) = changed_type.and_then(|t| t.chars().next()) {
+                let types = parallel_map(&paths, parallel, |path| git::get_change_types(path));
+                paths = paths
+                    .into_iter()
+                    .zip(types)
+                    .filter_map(|(path, types)| match types {
+                        Ok(types) => types.contains(&change_type).then_some(path),
+                        Err(e) => {
+                            eprintln!("Skipping {}: {}", path, e);
+                            None
+                        }
+                    })
+                    .collect();
+            } else if changed {
+                let changes =
+                    parallel_map(&paths, parallel, |path| git::has_uncommitted_changes(path));
+                paths = paths
+                    .into_iter()
+                    .zip(changes)
+                    .filter_map(|(path, changed)| match changed {
+                        Ok(changed) => changed.then_some(path),
+                        Err(e) => {
+                            eprintln!("Skipping {}: {}", path, e);
+                            None
+                        }
+                    })
+                    .collect();
+            }
+
+            if let Some(amount) = random {
+                let mut rng = seed.map_or_else(Rng::from_entropy, Rng::with_seed);
+                paths = rng.choose_multiple(&paths, amount);
+            }
+
+            if count_by_language {
+                let mut counts: HashMap<String, usize> = HashMap::new();
+                for path in &paths {
+                    let language = storage.repos[path]
+                        .language
+                        .clone()
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    *counts.entry(language).or_default() += 1;
+                }
+                let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+                counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                print!("{}", LanguageHistogram::render(&counts, terminal_width()));
+                return Ok(());
+            }
+
+            let mut scored: Vec<(String, f64)> = paths
+                .into_iter()
+                .map(|path| {
+                    let score = storage.frecency(&storage.repos[&path]);
+                    (path, score)
+                })
+                .collect();
+            if with_score {
+                let scores: Vec<f64> = scored.iter().map(|(_, score)| *score).collect();
+                for ((_, score), normalized) in scored.iter_mut().zip(normalize_scores(&scores)) {
+                    *score = normalized;
+                }
+            }
+            if by_activity {
+                let mut sections: HashMap<ActivityTier, Vec<(String, f64)>> = HashMap::new();
+                for (path, score) in scored {
+                    let tier = classify_activity(&storage.repos[&path].access_times);
+                    sections.entry(tier).or_default().push((path, score));
+                }
+
+                let mut first = true;
+                for tier in ActivityTier::ALL {
+                    let Some(repos) = sections.remove(&tier) else {
+                        continue;
+                    };
+                    if !first {
+                        println!();
+                    }
+                    first = false;
+                    println!("{:?}:", tier);
+                    // Most frecent first within each group, whatever --sort says
+                    let mut repos = repos;
+                    repos.sort_by(|a, b| b.1.total_cmp(&a.1));
+                    for (path, _) in repos {
+                        println!("  {}", path);
+                    }
+                }
+                return Ok(());
+            }
+
+            if let Some(ListGroup::Language) = group_by {
+                let mut repos = to_repositories(&storage, scored);
+                if show_version {
+                    let versions = parallel_map(&repos, parallel, |(repo, _)| {
+                        let language = repo.language.as_deref()?;
+                        detect_language_version(Path::new(&repo.path), language)
+                    });
+                    for ((repo, _), version) in repos.iter_mut().zip(versions) {
+                        repo.language_version = version;
+                    }
+                }
+                print_language_groups(&repos);
+                return Ok(());
+            }
+
+            if let Some(template) = &template {
+                print_with_template(&storage, scored, template);
+                return Ok(());
+            }
+
+            let git_status: Option<HashMap<String, String>> = show_git_status.then(|| {
+                let timeout = StdDuration::from_millis(timeout_ms);
+                let paths: Vec<&String> = scored.iter().map(|(path, _)| path).collect();
+                let statuses =
+                    parallel_map(&paths, parallel, |path| {
+                        match git::get_compact_status(path, timeout) {
+                            Ok(status) => status.to_string(),
+                            Err(e) => format!("error: {}", e),
+                        }
+                    });
+                paths.into_iter().cloned().zip(statuses).collect()
+            });
+
+            let output = if export_env {
+                OutputFormat::Env
+            } else if export_fish {
+                OutputFormat::Fish
+            } else {
+                output
+            };
+            print_repos(
+                &storage,
+                scored,
+                output,
+                zero_terminated,
+                file.as_deref(),
+                PathColumns {
+                    score: show_frecency_score || with_score,
+                    tag_count: show_tag_count,
+                    priority: show_priority,
+                    last_access: show_last_access,
+                    git_status: git_status.as_ref(),
+                },
+            )
+        }
+
+        Commands::Active { tags, days } => {
+            let storage = Storage::new(&config)?;
+            let tags = tags.as_deref().map(parse_tags).unwrap_or_default();
+            print_paths(&storage.active_repos(&tags, days), false)
+        }
+
+        Commands::Top { count, output } => {
+            let storage = Storage::new(&config)?;
+            let top = storage.top_repos(count);
+            match output {
+                TopOutput::Text => {
+                    for (path, score) in &top {
+                        println!("{} ({:.1})", path, score);
+                    }
+                }
+                TopOutput::Json => {
+                    print!("{}", JsonRenderer::render(&to_repositories(&storage, top)))
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Recent { count } => {
+            let storage = Storage::new(&config)?;
+            let now = Utc::now();
+            for (path, last_access) in storage.recent_repos(count) {
+                println!("{:<8}\t{}", format_time_ago(last_access, now), path);
+            }
+            Ok(())
+        }
+
+        Commands::Info {
+            path,
+            show_ignored,
+            show_stash,
+            show_ci_status,
+            open_project,
+            format,
+        } => {
+            let mut storage = Storage::new(&config)?;
+            let repo_access = storage
+                .get_repo(&path)?
+                .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+
+            let abs_path = storage.resolve_path(&path)?;
+            let mut printer =
+                RepoInfoPrinter::new(&abs_path, repo_access, storage.frecency_weights());
+            if show_ignored {
+                printer.push(InfoLine::IgnoredCount(git::count_ignored_files(&abs_path)?));
+            }
+            if show_stash {
+                printer.push(InfoLine::StashCount(git::count_stash_entries(&abs_path)?));
+            }
+
+            if show_ci_status {
+                let remote_url = repo_access
+                    .remote_url
+                    .clone()
+                    .or_else(|| git::remote_origin_url(&abs_path));
+                let status = match (repo_access.cached_ci_status(), remote_url) {
+                    (Some(status), _) => status,
+                    (None, None) => CiStatus::Unknown,
+                    (None, Some(remote_url)) => {
+                        match ci::fetch_ci_status(&ci::CurlClient, &remote_url, &config) {
+                            Ok(status) => {
+                                if let Some(repo_access) = storage.get_repo_mut(&abs_path)? {
+                                    repo_access.ci_status = Some(status);
+                                    repo_access.last_ci_check = Some(Utc::now());
+                                }
+                                storage.save(&config)?;
+                                status
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to fetch CI status: {}", e);
+                                CiStatus::Unknown
+                            }
+                        }
+                    }
+                };
+                printer.push(InfoLine::CiStatus(status));
+            }
+            printer.print_info(format)?;
+
+            if open_project {
+                let repo_access = storage
+                    .get_repo(&abs_path)?
+                    .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+                let url = repo_access
+                    .project_url
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("No project URL set for {}", abs_path))?;
+                open_url(url)?;
+            }
+
+            Ok(())
+        }
+
+        Commands::ImportFromVscode { workspace, tags } => {
+            let mut storage = Storage::new(&config)?;
+            let tags = parse_tags(&tags);
+            let folders = vscode::parse_workspace(Path::new(&workspace))?;
+
+            let mut added = 0;
+            let mut tracked = 0;
+            let mut skipped = 0;
+            for folder in folders {
+                let folder_path = folder.to_string_lossy().to_string();
+
+                if !is_git_repo(&folder) {
+                    println!("Skipping (not a git repo): {}", folder_path);
+                    skipped += 1;
+                } else if storage.get_repo(&folder_path)?.is_some() {
+                    println!("Already tracked: {}", folder_path);
+                    tracked += 1;
+                } else {
+                    storage.add_repo(&folder_path, tags.clone())?;
+                    println!("Added repo: {}", folder_path);
+                    added += 1;
+                }
+            }
+
+            if added > 0 {
+                storage.save(&config)?;
+            }
+            println!(
+                "{} repos added, {} already tracked, {} skipped",
+                added, tracked, skipped
+            );
+            Ok(())
+        }
+
+        Commands::ImportFromTmux {
+            tags,
+            dry_run,
+            verbose,
+        } => {
+            let mut storage = Storage::new(&config)?;
+            let tags = tags.as_deref().map(parse_tags).unwrap_or_default();
+
+            let mut added = 0;
+            for pane_path in tmux::get_tmux_pane_paths()? {
+                let pane_path_str = pane_path.to_string_lossy().to_string();
+
+                if !is_git_repo(&pane_path) {
+                    if verbose {
+                        println!("Skipping (not a git repo): {}", pane_path_str);
+                    }
+                } else if storage.get_repo(&pane_path_str)?.is_some() {
+                    if verbose {
+                        println!("Already tracked: {}", pane_path_str);
+                    }
+                } else {
+                    if dry_run {
+                        println!("Would add repo: {}", pane_path_str);
+                    } else {
+                        storage.add_repo(&pane_path_str, tags.clone())?;
+                        println!("Added repo: {}", pane_path_str);
+                    }
+                    added += 1;
+                }
+            }
+
+            if dry_run {
+                println!("Would add {} repos", added);
+                return Ok(());
+            }
+            if added > 0 {
+                storage.save(&config)?;
+            }
+            println!("{} repos added", added);
+            Ok(())
+        }
+
+        Commands::Watch {
+            interval,
+            daemon,
+            stop,
+        } => {
+            let pid_path = config.cleanup_pid_path();
+
+            if stop {
+                // The running watcher exits once its PID file is gone
+                if pid_path.exists() {
+                    fs::remove_file(&pid_path)?;
+                    println!("Stopping cleanup daemon");
+                } else {
+                    println!("No cleanup daemon running");
+                }
+                return Ok(());
+            }
+
+            if daemon {
+                // Re-run ourselves without --daemon, detached from the terminal
+                let child = process::Command::new(env::current_exe()?)
+                    .args(["watch", "--interval", &interval.to_string()])
+                    .stdin(process::Stdio::null())
+                    .stdout(process::Stdio::null())
+                    .stderr(process::Stdio::null())
+                    .spawn()
+                    .map_err(|e| anyhow!("Failed to start cleanup daemon: {}", e))?;
+                println!("Started cleanup daemon (pid {})", child.id());
+                return Ok(());
+            }
+
+            config.ensure_mangit_dir()?;
+            let pid = process::id().to_string();
+            fs::write(&pid_path, &pid)?;
+
+            let task = Storage::start_cleanup_task(
+                Arc::new(config.clone()),
+                StdDuration::from_secs(interval),
+            );
+
+            // Run until the PID file is removed or taken over by another watcher
+            while fs::read_to_string(&pid_path).is_ok_and(|content| content.trim() == pid) {
+                thread::sleep(StdDuration::from_secs(1));
+            }
+            task.stop();
+            Ok(())
+        }
+
+        Commands::Open {
+            repo,
+            tmux,
+            split,
+            editor,
+        } => {
+            let mut storage = Storage::new(&config)?;
+            let path = storage.resolve_repo(&repo)?;
+            storage.record_access(&path)?;
+            storage.save(&config)?;
+
+            if editor {
+                let editor = resolve_editor(config.default_editor.as_deref()).ok_or_else(|| {
+                    anyhow!("No editor set, choose one with: mangit config set-editor")
+                })?;
+                open_in_editor(&editor, &path)
+            } else if tmux && inside_tmux() {
+                let mode = if split {
+                    TmuxOpenMode::HSplit
+                } else {
+                    config.tmux_open_mode
+                };
+                open_in_tmux(&path, mode)
+            } else {
+                if tmux {
+                    eprintln!("Not inside tmux, opening a shell in {}", path);
+                }
+                open_shell_in(&path)
+            }
+        }
+
+        Commands::Access { path } => {
+            let mut storage = Storage::new(&config)?;
+
+            match storage.record_access(&path) {
+                Ok(true) => {
+                    storage.save(&config)?;
+                    Ok(())
+                }
+                Ok(false) => Err(anyhow!("Repo not found: {}", path)),
+                Err(e) => Err(anyhow!("Failed to access repo: {}", e)),
+            }
+        }
+
+        Commands::Reset {
+            path,
+            frecency,
+            tags,
+            all_data,
+            to_default_tag,
+        } => {
+            let mut storage = Storage::new(&config)?;
+            let reset_tags = tags || all_data || to_default_tag.is_some();
+            let reset_frecency = frecency || all_data || !reset_tags;
+
+            let mut count = 0;
+            if reset_frecency {
+                count = storage
+                    .reset_frequency(path.as_deref())
+                    .map_err(|e| anyhow!("Failed to reset frequency: {}", e))?;
+            }
+            if reset_tags {
+                count = match to_default_tag {
+                    Some(tag) => storage.replace_tags(path.as_deref(), vec![tag]),
+                    None => storage.reset_tags(path.as_deref()),
+                }
+                .map_err(|e| anyhow!("Failed to reset tags: {}", e))?;
+            }
+
+            if let Some(p) = path {
+                if count == 0 {
+                    println!("Repo not found: {}", p);
+                    return Ok(());
+                }
+            } else {
+                let what = match (reset_frecency, reset_tags) {
+                    (true, true) => "frequency and tags",
+                    (false, true) => "tags",
+                    _ => "frequency",
+                };
+                println!("Reset {} for {} repos", what, count);
+            }
+            storage.save(&config)?;
+            Ok(())
+        }
+
+        Commands::Tags {
+            graph,
+            threshold,
+            with_descriptions,
+            sort,
+            prune_orphaned,
+            export,
+            import,
+            merge: _,
+            replace,
+            output,
+        } => {
+            if let Some(file) = export {
+                return commands::tags_io::run_export(&config, &file);
+            }
+            if let Some(file) = import {
+                return commands::tags_io::run_import(&config, &file, replace);
+            }
+
+            let storage = Storage::new(&config)?;
+
+            if prune_orphaned {
+                let mut vocab = TagVocabulary::load(&config)?;
+                let orphaned = storage.get_orphaned_tag_defs(&vocab);
+                for tag in &orphaned {
+                    println!("Removing unused tag: {}", tag);
+                }
+                let removed = vocab.remove(&orphaned);
+                if removed > 0 {
+                    vocab.save(&config)?;
+                }
+                println!("Pruned {} orphaned tag definitions", removed);
+
+                let undefined = storage.validate_tag_consistency(&vocab);
+                if !undefined.is_empty() {
+                    println!("Tags not in vocabulary: {}", undefined.join(", "));
+                }
+                return Ok(());
+            }
+
+            if graph {
+                let edges = build_tag_graph(&storage.get_tag_cooccurrence(), threshold);
+                if edges.is_empty() {
+                    println!("No tags are used together on {} or more repos", threshold);
+                } else {
+                    println!("{}", render_tag_graph(&edges));
+                }
+                return Ok(());
+            }
+
+            let all_tags = storage.get_all_tags();
+
+            if all_tags.is_empty() && output == TagsOutput::Text {
+                println!("No tags found in any repositories");
+                return Ok(());
+            }
+
+            // Convert to sorted vec for consistent output
+            let mut tag_counts: Vec<(String, usize)> = all_tags.into_iter().collect();
+            match sort {
+                TagSort::Count => {
+                    tag_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+                }
+                TagSort::Name => tag_counts.sort_by(|a, b| a.0.cmp(&b.0)),
+                TagSort::Frecency => {
+                    let scores =
+                        compute_tag_frecency_scores(&storage.repos, storage.frecency_weights());
+                    let score = |tag: &String| scores.get(tag).copied().unwrap_or(0.0);
+                    tag_counts.sort_by(|a, b| {
+                        score(&b.0)
+                            .total_cmp(&score(&a.0))
+                            .then_with(|| a.0.cmp(&b.0))
+                    });
+                }
+            }
 
-        /// Tags for the repository (comma separated)
-        #[clap(short, long)]
-        tags: String,
-    },
+            if output == TagsOutput::Json {
+                print!("{}", JsonRenderer::render_tags(&tag_counts));
+                return Ok(());
+            }
 
-    /// Delete a repo
-    Delete {
-        /// Path to repository
-        path: String,
-    },
+            if with_descriptions {
+                let vocab = TagVocabulary::load(&config)?;
+                println!("All tags (tag: count - description):");
+                for (tag, count) in tag_counts {
+                    match vocab.describe(&tag) {
+                        Some(description) => println!("{}: {} - {}", tag, count, description),
+                        None => println!("{}: {}", tag, count),
+                    }
+                }
+                return Ok(());
+            }
 
-    /// Update a repo's tags
-    Update {
-        /// Path to repository
-        path: String,
+            println!("All tags (tag: count):");
+            for (tag, count) in tag_counts {
+                println!("{}: {}", tag, count);
+            }
 
-        /// New tags for the repository (comma separated)
-        #[clap(short, long)]
-        tags: String,
-    },
+            Ok(())
+        }
 
-    /// Search for repos by tag or multiple tags
-    Search {
-        /// Tag(s) to search for (comma separated)
-        tags: String,
-    },
+        Commands::CloneAll { to_dir, parallel } => {
+            commands::clone_all::run(&config, &to_dir, parallel)
+        }
 
-    /// Access a repo (updates frecency)
-    Access {
-        /// Path to repository
-        path: String,
-    },
+        Commands::PathNormalize => {
+            let mut storage = Storage::new(&config)?;
+            let result = storage.canonicalize_paths()?;
 
-    /// Reset frequency data for a repo or all repos
-    Reset {
-        /// Path to repository (if not provided, resets all repos)
-        #[clap(short, long)]
-        path: Option<String>,
-    },
+            for (old, new) in &result.changed {
+                println!("Warning: {} is now tracked as {}", old, new);
+            }
+            for path in &result.failed {
+                eprintln!("Path not found: {}", path);
+            }
 
-    /// List all tags with their usage counts
-    Tags,
-}
+            storage.save(&config)?;
+            println!(
+                "{} paths normalized, {} unchanged, {} failed (path not found)",
+                result.changed.len(),
+                result.unchanged,
+                result.failed.len()
+            );
+            Ok(())
+        }
 
-fn parse_tags(tags_str: &str) -> Vec<String> {
-    tags_str
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
-}
+        Commands::Clone { url, path, tags } => {
+            let tags = tags.as_deref().map(parse_tags).unwrap_or_default();
+            commands::clone::run(&config, &url, path.as_deref(), tags)
+        }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
-    let config = Config::default();
+        Commands::Pull {
+            name,
+            fetch_only,
+            parallel,
+            timeout,
+        } => commands::pull::run(
+            &config,
+            name.as_deref(),
+            fetch_only,
+            parallel,
+            StdDuration::from_secs(timeout),
+        ),
 
-    match cli.command {
-        Commands::Init => {
-            config.ensure_mangit_dir()?;
+        Commands::Status {
+            dirty_only,
+            parallel,
+        } => {
             let storage = Storage::new(&config)?;
-            storage.save(&config)?;
-            println!("Initialized mangit at {}", config.mangit_dir);
+            let paths = storage.all_paths();
+            let statuses = parallel_map(&paths, parallel, |path| git::git_status(path));
+
+            let mut rows = Vec::new();
+            for (path, status) in paths.iter().zip(statuses) {
+                match status {
+                    Ok(status) if dirty_only && status.is_clean() => {}
+                    Ok(status) => rows.push((storage.repos[path].display_name(path), status)),
+                    Err(e) => eprintln!("Warning: {}: {}", path, e),
+                }
+            }
+            print!("{}", StatusTable::render(&rows));
             Ok(())
         }
 
-        Commands::Add { path, tags } => {
+        Commands::Verify {
+            remote_exists,
+            fix,
+            timeout,
+            parallel,
+        } => {
+            if !remote_exists {
+                return Err(anyhow!("Nothing to verify, pass --remote-exists"));
+            }
+
             let mut storage = Storage::new(&config)?;
-            let tags = parse_tags(&tags);
+            let mut remotes: Vec<(String, String)> = storage
+                .repos
+                .iter()
+                .filter_map(|(path, repo_access)| {
+                    let url = repo_access.remote_url.clone()?;
+                    Some((path.clone(), url))
+                })
+                .collect();
+            remotes.sort();
 
-            match storage.add_repo(&path, tags) {
-                Ok(true) => {
-                    println!("Added repo: {}", path);
-                    storage.save(&config)?;
-                    Ok(())
+            let statuses = parallel_map(&remotes, parallel, |(_, url)| {
+                git::check_remote_reachable(url, timeout)
+            });
+
+            let mut reachable = 0;
+            let mut cleared = 0;
+            for ((path, url), status) in remotes.iter().zip(&statuses) {
+                println!("{}\t{}\t{}", status, path, url);
+                match status {
+                    RemoteStatus::Reachable => reachable += 1,
+                    RemoteStatus::NotFound if fix => {
+                        if let Some(repo_access) = storage.repos.get_mut(path) {
+                            repo_access.remote_url = None;
+                            cleared += 1;
+                        }
+                    }
+                    _ => {}
                 }
-                Ok(false) => {
-                    println!("Updated existing repo: {}", path);
-                    storage.save(&config)?;
-                    Ok(())
+            }
+
+            if cleared > 0 {
+                storage.save(&config)?;
+            }
+            println!(
+                "{} remotes reachable, {} failed{}",
+                reachable,
+                remotes.len() - reachable,
+                if fix {
+                    format!(", {} remote URLs cleared", cleared)
+                } else {
+                    String::new()
                 }
-                Err(e) => Err(anyhow!("Failed to add repo: {}", e)),
+            );
+            Ok(())
+        }
+
+        Commands::Validate { fix } => {
+            // Not Storage::new, which silently drops missing paths. Show the whole
+            // error chain so parse errors include serde's line and column
+            let mut storage = Storage::load_all(&config).map_err(|e| anyhow!("{:#}", e))?;
+            let report = storage.validate();
+            for path in &report.missing {
+                println!("Missing: {}", path);
+            }
+            for path in &report.not_git {
+                println!("Not a git repo: {}", path);
+            }
+            for (first, second) in &report.duplicates {
+                println!("Duplicate: {} and {}", first, second);
+            }
+
+            let mut remaining = report.problem_count();
+            if fix && !report.missing.is_empty() {
+                storage.cleanup();
+                storage.save(&config)?;
+                println!("Removed {} missing repos", report.missing.len());
+                remaining -= report.missing.len();
             }
+            if remaining > 0 {
+                return Err(anyhow!("{} problems found", remaining));
+            }
+            println!("Storage is valid ({} repos)", storage.repos.len());
+            Ok(())
         }
 
-        Commands::Delete { path } => {
+        Commands::BatchRemove {
+            from_file,
+            dry_run,
+            ignore_not_found,
+        } => commands::batch_remove::run(&config, &from_file, dry_run, ignore_not_found),
+
+        Commands::Archive {
+            older_than,
+            dry_run,
+        } => {
+            let max_age = parse_duration(&older_than)?;
             let mut storage = Storage::new(&config)?;
+            let archived = storage.archive_by_age(max_age)?;
+            let verb = if dry_run { "Would archive" } else { "Archived" };
+            if archived.is_empty() {
+                println!("No repos to archive");
+            } else {
+                println!("{} {} repos: {}", verb, archived.len(), archived.join(", "));
+            }
+            if !dry_run {
+                storage.save(&config)?;
+            }
+            Ok(())
+        }
 
-            match storage.delete_repo(&path) {
-                Ok(true) => {
-                    println!("Deleted repo: {}", path);
-                    storage.save(&config)?;
-                    Ok(())
+        Commands::Siblings { path, depth } => {
+            let storage = Storage::new(&config)?;
+            let siblings = storage.find_siblings(&path, depth)?;
+            print_paths(&siblings, false)
+        }
+
+        Commands::Export { format, file } => {
+            let storage = Storage::new(&config)?;
+            let mut paths: Vec<&String> = storage.repos.keys().collect();
+            paths.sort();
+            let repos: Vec<Repository> = paths
+                .into_iter()
+                .map(|path| Repository::from((path.as_str(), &storage.repos[path])))
+                .collect();
+            let output = match format {
+                ExportFormat::Dot => {
+                    DotRenderer::render(&storage.repos, storage.frecency_weights())
                 }
-                Ok(false) => Err(anyhow!("Repo not found: {}", path)),
-                Err(e) => Err(anyhow!("Failed to delete repo: {}", e)),
+                ExportFormat::Csv => CsvRenderer::render(&repos),
+                ExportFormat::Json => JsonRenderer::render_repos(&repos),
+                ExportFormat::Toml => TomlRenderer::render(&repos),
+            };
+            match file {
+                Some(file) => {
+                    fs::write(&file, output)
+                        .map_err(|e| anyhow!("Failed to write {}: {}", file, e))?;
+                    println!("Wrote {}", file);
+                }
+                None => print!("{}", output),
+            }
+            Ok(())
+        }
+
+        Commands::Import {
+            format,
+            file,
+            dry_run,
+        } => commands::import::run(&config, &file, format, dry_run),
+
+        Commands::Recover {
+            scan_dir,
+            max_depth,
+        } => {
+            let scan_dir = Storage::to_absolute_path(&scan_dir)?;
+            let found = find_git_repos(Path::new(&scan_dir), max_depth);
+            if found.is_empty() {
+                return Err(anyhow!("No git repos found in {}", scan_dir));
+            }
+
+            let paths: Vec<String> = found
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            let storage = Storage::rebuild_from_paths(&paths)?;
+            for path in &paths {
+                eprintln!(
+                    "Warning: {} recovered without its tags, access history, description, priority or name",
+                    path
+                );
+            }
+
+            config.ensure_mangit_dir()?;
+            let repos_path = config.repos_path();
+            if repos_path.exists() {
+                let backup = repos_path.with_extension("json.bak");
+                fs::copy(&repos_path, &backup)?;
+                println!("Backed up the old repos file to {}", backup.display());
             }
+            storage.save(&config)?;
+            println!("Recovered {} repos", paths.len());
+            Ok(())
         }
 
-        Commands::Update { path, tags } => {
+        Commands::Scan { root, tags, depth } => {
             let mut storage = Storage::new(&config)?;
-            let tags = parse_tags(&tags);
+            let tags = parse_tags(tags.as_deref().unwrap_or_default());
+            let report = storage.scan_repos(&root, &tags, depth)?;
+            storage.save(&config)?;
 
-            match storage.update_repo(&path, tags) {
-                Ok(true) => {
-                    println!("Updated repo: {}", path);
-                    storage.save(&config)?;
-                    Ok(())
-                }
-                Ok(false) => Err(anyhow!("Repo not found: {}", path)),
-                Err(e) => Err(anyhow!("Failed to update repo: {}", e)),
+            for path in &report.added {
+                println!("Added: {}", path);
             }
+            for path in &report.already_tracked {
+                println!("Already tracked: {}", path);
+            }
+            println!(
+                "{} repos added, {} already tracked, {} skipped (not git)",
+                report.added.len(),
+                report.already_tracked.len(),
+                report.not_git
+            );
+            Ok(())
         }
 
-        Commands::Search { tags } => {
+        Commands::ScanConflicts { rename_interactive } => {
             let mut storage = Storage::new(&config)?;
-            let tag_list = parse_tags(&tags);
+            let conflicts = storage.find_name_conflicts();
 
-            if tag_list.is_empty() {
-                println!("No tags specified for search");
+            if conflicts.is_empty() {
+                println!("No name conflicts found");
                 return Ok(());
             }
 
-            let matches = storage.search_by_tags(&tag_list);
-
-            if matches.is_empty() {
-                if tag_list.len() == 1 {
-                    println!("No repos found with tag: {}", tag_list[0]);
-                } else {
-                    println!("No repos found with all tags: {}", tags);
+            for conflict in &conflicts {
+                println!("{}:", conflict.name);
+                for path in &conflict.paths {
+                    println!("  {}", path);
                 }
-            } else {
-                // Simple output, one path per line for easy integration with tools like fzf
-                for path in matches {
-                    println!("{}", path);
+            }
+
+            if !rename_interactive {
+                return Ok(());
+            }
+
+            let stdin = io::stdin();
+            for path in conflicts.iter().flat_map(|conflict| &conflict.paths) {
+                loop {
+                    print!("New name for {} (empty to keep): ", path);
+                    io::stdout().flush()?;
+
+                    let mut name = String::new();
+                    if stdin.read_line(&mut name)? == 0 {
+                        // End of input, keep what has been renamed so far
+                        storage.save(&config)?;
+                        return Ok(());
+                    }
+
+                    let name = name.trim();
+                    if name.is_empty() {
+                        break;
+                    }
+                    match storage.set_repo_name(path, name) {
+                        Ok(()) => break,
+                        Err(e) => println!("{}", e),
+                    }
                 }
-                // Save after search to update frecency data
+            }
+
+            storage.save(&config)?;
+            let remaining = storage.find_name_conflicts().len();
+            println!("{} name conflicts remaining", remaining);
+            Ok(())
+        }
+
+        Commands::RepoId { path } => {
+            let storage = Storage::new(&config)?;
+            let repo_access = storage
+                .get_repo(&path)?
+                .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+            println!("{}", repo_access.repo_id);
+            Ok(())
+        }
+
+        Commands::TagRename { old, new } => {
+            let new = new.trim();
+            if new.is_empty() || new.contains(',') {
+                return Err(anyhow!("Invalid tag name: {:?}", new));
+            }
+
+            let mut storage = Storage::new(&config)?;
+            let updated = storage.rename_tag(&old, new);
+            if updated > 0 {
                 storage.save(&config)?;
             }
+            println!("Renamed {} to {} on {} repos", old, new, updated);
+            Ok(())
+        }
+
+        Commands::Rename { old_name, new_name } => {
+            let new_name = new_name.trim();
+            if new_name.is_empty() {
+                return Err(anyhow!("The new name can't be empty"));
+            }
 
+            let mut storage = Storage::new(&config)?;
+            if !storage.rename_repo(&old_name, new_name)? {
+                return Err(anyhow!("Repo not found: {}", old_name));
+            }
+            storage.save(&config)?;
+            println!("Renamed '{}' to '{}'", old_name, new_name);
             Ok(())
         }
 
-        Commands::Access { path } => {
+        Commands::TagDelete { tag, remove_empty } => {
             let mut storage = Storage::new(&config)?;
+            let report = storage.delete_tag(&tag, remove_empty);
+            if report.updated == 0 {
+                println!("No repos have the tag {}", tag);
+                return Ok(());
+            }
 
-            match storage.record_access(&path) {
-                Ok(true) => {
-                    storage.save(&config)?;
-                    Ok(())
+            storage.save(&config)?;
+            for path in &report.untagged {
+                if remove_empty {
+                    println!("Removed repo without tags: {}", path);
+                } else {
+                    eprintln!("Warning: {} has no tags left", path);
                 }
-                Ok(false) => Err(anyhow!("Repo not found: {}", path)),
-                Err(e) => Err(anyhow!("Failed to access repo: {}", e)),
             }
+            println!("Removed tag {} from {} repos", tag, report.updated);
+            Ok(())
         }
 
-        Commands::Reset { path } => {
-            let mut storage = Storage::new(&config)?;
+        Commands::TagImport { file } => {
+            let content =
+                fs::read_to_string(&file).map_err(|e| anyhow!("Failed to read {}: {}", file, e))?;
+            let imported = TagVocabulary::parse(&content)?;
 
-            match storage.reset_frequency(path.as_deref()) {
-                Ok(count) => {
-                    if let Some(p) = path {
-                        if count > 0 {
-                            println!("Repo not found: {}", p);
-                        }
-                    } else {
-                        println!("Reset frequency for {} repos", count);
+            let mut vocab = TagVocabulary::load(&config)?;
+            let count = vocab.merge(imported);
+            vocab.save(&config)?;
+
+            println!("Imported {} tag definitions", count);
+            Ok(())
+        }
+
+        Commands::Note { action } => {
+            let mut storage = Storage::new(&config)?;
+            match action {
+                NoteAction::Set { path, text } => {
+                    if text.trim().is_empty() {
+                        return Err(anyhow!("The note is empty, use `note clear` to remove it"));
                     }
+                    storage.set_note(&path, Some(text))?;
                     storage.save(&config)?;
-                    Ok(())
+                    println!("Note set for {}", path);
+                }
+                NoteAction::Get { path } => {
+                    let repo_access = storage
+                        .get_repo(&path)?
+                        .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+                    match &repo_access.note {
+                        Some(note) => println!("{}", note),
+                        None => println!("No note set"),
+                    }
+                }
+                NoteAction::Clear { path } => {
+                    storage.set_note(&path, None)?;
+                    storage.save(&config)?;
+                    println!("Note cleared for {}", path);
                 }
-                Err(e) => Err(anyhow!("Failed to reset frequency: {}", e)),
             }
+            Ok(())
         }
 
-        Commands::Tags => {
-            let storage = Storage::new(&config)?;
-            let all_tags = storage.get_all_tags();
+        Commands::Config { action } => match action {
+            ConfigAction::Validate => {
+                let issues = config.validate();
 
-            if all_tags.is_empty() {
-                println!("No tags found in any repositories");
-                return Ok(());
-            }
+                if issues.is_empty() {
+                    println!("Config is valid");
+                    return Ok(());
+                }
 
-            // Convert to sorted vec for consistent output
-            let mut tag_counts: Vec<(String, usize)> = all_tags.into_iter().collect();
-            tag_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                for issue in &issues {
+                    let level = match issue.severity {
+                        IssueSeverity::Warning => "warning",
+                        IssueSeverity::Error => "error",
+                    };
+                    println!(
+                        "{}: {} = {:?}: {}",
+                        level, issue.field, issue.value, issue.issue
+                    );
+                }
 
-            println!("All tags (tag: count):");
-            for (tag, count) in tag_counts {
-                println!("{}: {}", tag, count);
-            }
+                let errors = issues
+                    .iter()
+                    .filter(|issue| issue.severity == IssueSeverity::Error)
+                    .count();
+                if errors > 0 {
+                    return Err(anyhow!("Config has {} error(s)", errors));
+                }
 
-            Ok(())
-        }
+                Ok(())
+            }
+            ConfigAction::SetEditor { editor } => {
+                let editor = match editor {
+                    Some(editor) => editor,
+                    None => choose_editor(&find_available_editors())?,
+                };
+                let mut config = config;
+                config.default_editor = Some(editor.clone());
+                config.save()?;
+                println!("Default editor set to {}", editor);
+                Ok(())
+            }
+            ConfigAction::Set { key, value } => {
+                let mut config = config;
+                match key {
+                    ConfigKey::MaxAccessHistory => {
+                        let limit = value
+                            .parse()
+                            .map_err(|_| anyhow!("Not a number: {}", value))?;
+                        config
+                            .set_max_access_history(limit)
+                            .map_err(|e| anyhow!("max-access-history {}", e))?;
+                    }
+                    ConfigKey::FrecencyWeightMinute
+                    | ConfigKey::FrecencyWeightHalfHour
+                    | ConfigKey::FrecencyWeightHour
+                    | ConfigKey::FrecencyWeightDay
+                    | ConfigKey::FrecencyWeightWeek
+                    | ConfigKey::FrecencyWeightOlder => {
+                        let weight = Config::parse_frecency_weight(&value).map_err(|e| {
+                            anyhow!("{} {}", key.to_possible_value().unwrap().get_name(), e)
+                        })?;
+                        let weights = &mut config.frecency;
+                        let field = match key {
+                            ConfigKey::FrecencyWeightMinute => &mut weights.weight_minute,
+                            ConfigKey::FrecencyWeightHalfHour => &mut weights.weight_half_hour,
+                            ConfigKey::FrecencyWeightHour => &mut weights.weight_hour,
+                            ConfigKey::FrecencyWeightDay => &mut weights.weight_day,
+                            ConfigKey::FrecencyWeightWeek => &mut weights.weight_week,
+                            _ => &mut weights.weight_older,
+                        };
+                        *field = weight;
+                    }
+                }
+                config.save()?;
+                println!(
+                    "Set {} to {}",
+                    key.to_possible_value().unwrap().get_name(),
+                    value
+                );
+                Ok(())
+            }
+        },
     }
 }
 
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        process::exit(1);
     }
 }
 
@@ -249,4 +3256,98 @@ mod tests_main {
         let tags = parse_tags("");
         assert_eq!(tags.len(), 0);
     }
+
+    #[test]
+    fn test_update_rejects_path_flags_with_bulk_updates() {
+        for bulk in [
+            "--apply-tag-file",
+            "--refresh-all",
+            "--auto-generate-description",
+        ] {
+            for flag in [
+                &["--tags", "rust"][..],
+                &["--fetch-first-commit"],
+                &["--refresh-description"],
+            ] {
+                let args = [&["mangit", "update", bulk][..], flag].concat();
+                assert!(Cli::try_parse_from(&args).is_err(), "{:?}", args);
+            }
+        }
+        assert!(Cli::try_parse_from(["mangit", "update", "/repo", "--tags", "rust"]).is_ok());
+    }
+
+    #[test]
+    fn test_watch_rejects_zero_interval() {
+        assert!(Cli::try_parse_from(["mangit", "watch", "--interval", "0"]).is_err());
+        assert!(Cli::try_parse_from(["mangit", "watch", "--interval", "1"]).is_ok());
+        assert!(Cli::try_parse_from(["mangit", "watch"]).is_ok());
+    }
+
+    #[test]
+    fn test_write_paths() {
+        let paths = vec!["/repos/one".to_string(), "/repos/line\nbreak".to_string()];
+
+        let mut buffer = Vec::new();
+        write_paths(&mut buffer, &paths, true).unwrap();
+        assert_eq!(buffer, b"/repos/one\0/repos/line\nbreak\0");
+
+        let mut buffer = Vec::new();
+        write_paths(&mut buffer, &paths, false).unwrap();
+        assert_eq!(buffer, b"/repos/one\n/repos/line\nbreak\n");
+    }
+
+    #[test]
+    fn test_render_repos_tag_count_column() {
+        let mut storage = Storage::default();
+        for (path, tags) in [
+            ("/repos/none", vec![]),
+            ("/repos/one", vec!["rust"]),
+            ("/repos/many", vec!["rust", "cli", "tool"]),
+        ] {
+            let tags = tags.into_iter().map(String::from).collect();
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(tags));
+        }
+
+        let scored = storage
+            .get_repos_by_tag_count()
+            .into_iter()
+            .map(|path| (path, 0.0))
+            .collect();
+        let columns = PathColumns {
+            tag_count: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render_repos(&storage, scored, OutputFormat::Paths, columns),
+            "  3\t/repos/many\n  1\t/repos/one\n  0\t/repos/none\n"
+        );
+    }
+
+    #[test]
+    fn test_render_repos_archived_marker() {
+        let mut storage = Storage::default();
+        let mut archived = RepoAccess::new(vec![]);
+        archived.archived = true;
+        storage.repos.insert("/repos/old".to_string(), archived);
+        storage
+            .repos
+            .insert("/repos/new".to_string(), RepoAccess::new(vec![]));
+
+        let scored = vec![
+            ("/repos/new".to_string(), 0.0),
+            ("/repos/old".to_string(), 0.0),
+        ];
+        let render =
+            |format| render_repos(&storage, scored.clone(), format, PathColumns::default());
+
+        // Paths stay bare for `cd $(mangit ...)` and `xargs`
+        assert_eq!(render(OutputFormat::Paths), "/repos/new\n/repos/old\n");
+        assert_eq!(render(OutputFormat::Html).matches("[ARCHIVED]").count(), 1);
+        let json: serde_json::Value = serde_json::from_str(&render(OutputFormat::Json)).unwrap();
+        assert_eq!(json[0]["archived"], false);
+        assert_eq!(json[1]["archived"], true);
+    }
 }
@@ -0,0 +1,43 @@
+pub mod cluster;
+pub mod csv;
+pub mod dot;
+pub mod env;
+pub mod fish;
+pub mod histogram;
+pub mod html;
+pub mod info;
+pub mod json;
+pub mod nvim;
+pub mod status;
+pub mod tag_graph;
+pub mod toml;
+pub mod tsv;
+pub mod yaml;
+
+use clap::ValueEnum;
+
+/// Output formats for commands that print repos
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// One path per line
+    #[default]
+    Paths,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// JSON array of repos with their path, tags, other fields and frecency score
+    Json,
+    /// Self-contained HTML page with a filterable table
+    Html,
+    /// Pick one repo interactively with fzf, falls back to paths without fzf
+    Fzf,
+    /// Pick one repo with `rofi -dmenu`, falls back to paths without rofi
+    Rofi,
+    /// `export MANGIT_REPO_<NAME>=<path>` lines for `eval`
+    Env,
+    /// `abbr -a cd_<name> 'cd <path>'` lines for the fish shell
+    Fish,
+    /// Lua table of `{ display, value }` entries for a Neovim Telescope picker
+    NvimTelescope,
+    /// Lua table of `{ filename, text }` entries for Neovim's quickfix list
+    NvimQuickfix,
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::repository::Repository;
+
+/// Repos that have exactly the same tags
+#[derive(Debug, Clone)]
+pub struct TagCluster {
+    // Sorted alphabetically
+    pub tags: Vec<String>,
+    pub repos: Vec<Repository>,
+    pub avg_score: f64,
+}
+
+/// Groups scored repos by their tag set, ignoring tag order. Repos keep their
+/// input order within a cluster, clusters are sorted by average score, highest first
+pub fn cluster_by_tags(repos: &[(Repository, f64)]) -> Vec<TagCluster> {
+    let mut clusters: Vec<TagCluster> = Vec::new();
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut totals: Vec<f64> = Vec::new();
+
+    for (repo, score) in repos {
+        let mut tags = repo.tags.clone();
+        tags.sort();
+        tags.dedup();
+
+        let i = *index.entry(tags.clone()).or_insert_with(|| {
+            clusters.push(TagCluster {
+                tags,
+                repos: Vec::new(),
+                avg_score: 0.0,
+            });
+            totals.push(0.0);
+            clusters.len() - 1
+        });
+        clusters[i].repos.push(repo.clone());
+        totals[i] += score;
+    }
+
+    for (cluster, total) in clusters.iter_mut().zip(totals) {
+        cluster.avg_score = total / cluster.repos.len() as f64;
+    }
+    clusters.sort_by(|a, b| {
+        b.avg_score
+            .total_cmp(&a.avg_score)
+            .then_with(|| a.tags.cmp(&b.tags))
+    });
+    clusters
+}
+
+/// Renders each cluster as a `tags (avg score):` header followed by its repo paths
+pub fn render_clusters(clusters: &[TagCluster]) -> String {
+    clusters
+        .iter()
+        .map(|cluster| {
+            let tags = if cluster.tags.is_empty() {
+                "(no tags)".to_string()
+            } else {
+                cluster.tags.join(", ")
+            };
+            let mut section = format!("{} (avg {:.1}):\n", tags, cluster.avg_score);
+            for repo in &cluster.repos {
+                section += &format!("  {}\n", repo.path);
+            }
+            section
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests_cluster {
+    use super::*;
+
+    fn repo(path: &str, tags: &[&str]) -> Repository {
+        Repository::new(
+            path.trim_start_matches('/').to_string(),
+            path.to_string(),
+            tags.iter().map(|tag| tag.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_identical_tags_form_one_cluster() {
+        let repos = vec![
+            (repo("/a", &["rust", "cli"]), 10.0),
+            (repo("/b", &["web"]), 40.0),
+            (repo("/c", &["cli", "rust"]), 30.0),
+            (repo("/d", &["rust"]), 5.0),
+        ];
+
+        let clusters = cluster_by_tags(&repos);
+        assert_eq!(clusters.len(), 3);
+
+        assert_eq!(clusters[0].tags, vec!["web"]);
+        assert_eq!(clusters[0].avg_score, 40.0);
+
+        assert_eq!(clusters[1].tags, vec!["cli", "rust"]);
+        let paths: Vec<&str> = clusters[1].repos.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["/a", "/c"]);
+        assert_eq!(clusters[1].avg_score, 20.0);
+
+        assert_eq!(clusters[2].tags, vec!["rust"]);
+        assert_eq!(clusters[2].avg_score, 5.0);
+    }
+
+    #[test]
+    fn test_render_clusters() {
+        let repos = vec![
+            (repo("/a", &["rust", "cli"]), 10.0),
+            (repo("/b", &[]), 2.0),
+            (repo("/c", &["cli", "rust"]), 15.0),
+        ];
+        assert_eq!(
+            render_clusters(&cluster_by_tags(&repos)),
+            "cli, rust (avg 12.5):\n  /a\n  /c\n\n(no tags) (avg 2.0):\n  /b\n"
+        );
+        assert_eq!(render_clusters(&[]), "");
+    }
+}
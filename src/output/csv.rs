@@ -0,0 +1,79 @@
+use crate::repository::Repository;
+
+const HEADER: [&str; 6] = [
+    "path",
+    "name",
+    "tags",
+    "description",
+    "language",
+    "last_modified",
+];
+
+/// Renders repos as comma-separated values for `export --format csv`
+pub struct CsvRenderer;
+
+impl CsvRenderer {
+    /// Renders a header row and one row per repo. Tags are joined with `;` and
+    /// `last_modified` is an RFC 3339 time
+    pub fn render(repos: &[Repository]) -> String {
+        let mut output = HEADER.join(",");
+        output.push('\n');
+
+        for repo in repos {
+            let fields = [
+                quote_field(&repo.path),
+                quote_field(&repo.name),
+                quote_field(&repo.tags.join(";")),
+                quote_field(&repo.description),
+                quote_field(repo.language.as_deref().unwrap_or("")),
+                repo.last_modified.to_rfc3339(),
+            ];
+            output.push_str(&fields.join(","));
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Quotes a field if it contains a separator, quote or line break (RFC 4180)
+fn quote_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests_csv {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_render() {
+        let mut repo = Repository::new(
+            "mangit".to_string(),
+            "/src/mangit".to_string(),
+            vec!["rust".to_string(), "cli".to_string()],
+            "Tags, \"frecency\" and\nmore".to_string(),
+        );
+        repo.language = Some("Rust".to_string());
+        repo.last_modified = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut plain = repo.clone();
+        plain.path = "/src/notes".to_string();
+        plain.name = "notes".to_string();
+        plain.tags = Vec::new();
+        plain.description = String::new();
+        plain.language = None;
+
+        let output = CsvRenderer::render(&[repo, plain]);
+        assert_eq!(
+            output,
+            "path,name,tags,description,language,last_modified\n\
+             /src/mangit,mangit,rust;cli,\"Tags, \"\"frecency\"\" and\nmore\",Rust,2024-05-01T12:00:00+00:00\n\
+             /src/notes,notes,,,,2024-05-01T12:00:00+00:00\n"
+        );
+        assert_eq!(CsvRenderer::render(&[]), format!("{}\n", HEADER.join(",")));
+    }
+}
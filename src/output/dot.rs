@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::FrecencyConfig;
+use crate::storage::RepoAccess;
+
+// Width in inches of the least and most frecent repo nodes
+const MIN_REPO_WIDTH: f64 = 0.75;
+const MAX_REPO_WIDTH: f64 = 2.5;
+
+/// Renders repos and their tags as a Graphviz graph for `dot -Tpng`
+pub struct DotRenderer;
+
+impl DotRenderer {
+    /// Repos are circles sized by frecency, tags are boxes colored from yellow
+    /// (used by one repo) to red (used by the most repos). Edges link repos to tags
+    pub fn render(repos: &HashMap<String, RepoAccess>, weights: &FrecencyConfig) -> String {
+        let mut sorted: Vec<(&String, &RepoAccess)> = repos.iter().collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for (_, repo_access) in &sorted {
+            for tag in &repo_access.tags {
+                *tag_counts.entry(tag).or_insert(0) += 1;
+            }
+        }
+        let max_count = tag_counts.values().copied().max().unwrap_or(1);
+        let frecencies: Vec<f64> = sorted
+            .iter()
+            .map(|(_, repo_access)| repo_access.calculate_frecency(weights))
+            .collect();
+        let max_frecency = frecencies.iter().copied().fold(0.0, f64::max);
+
+        let mut out = String::from("graph mangit {\n");
+        out += "  rankdir=LR;\n  node [style=filled, fontname=\"Helvetica\"];\n\n";
+
+        for ((path, repo_access), frecency) in sorted.iter().zip(&frecencies) {
+            let share = if max_frecency > 0.0 {
+                frecency / max_frecency
+            } else {
+                0.0
+            };
+            let width = MIN_REPO_WIDTH + (MAX_REPO_WIDTH - MIN_REPO_WIDTH) * share;
+            out += &format!(
+                "  {} [shape=circle, label={}, tooltip={}, width={:.2}, fillcolor=\"lightblue\"];\n",
+                repo_node(path),
+                quote(&repo_access.display_name(path)),
+                quote(path),
+                width
+            );
+        }
+        out.push('\n');
+
+        for (tag, count) in &tag_counts {
+            out += &format!(
+                "  {} [shape=box, label={}, tooltip=\"{} repos\", fillcolor={}];\n",
+                tag_node(tag),
+                quote(tag),
+                count,
+                heat_color(*count, max_count)
+            );
+        }
+        out.push('\n');
+
+        for (path, repo_access) in &sorted {
+            for tag in &repo_access.tags {
+                out += &format!("  {} -- {};\n", repo_node(path), tag_node(tag));
+            }
+        }
+
+        out += "}\n";
+        out
+    }
+}
+
+fn repo_node(path: &str) -> String {
+    quote(&format!("repo:{}", path))
+}
+
+fn tag_node(tag: &str) -> String {
+    quote(&format!("tag:{}", tag))
+}
+
+/// HSV color between yellow (rarely used tags) and red (the most used tags)
+fn heat_color(count: usize, max_count: usize) -> String {
+    let share = if max_count > 1 {
+        (count - 1) as f64 / (max_count - 1) as f64
+    } else {
+        0.0
+    };
+    format!("\"{:.3} 0.800 1.000\"", 0.15 * (1.0 - share))
+}
+
+/// Quotes a DOT ID, escaping quotes and backslashes
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests_dot {
+    use super::*;
+
+    fn repo(tags: &[&str], accesses: usize) -> RepoAccess {
+        let mut repo_access = RepoAccess::new(tags.iter().map(|tag| tag.to_string()).collect());
+        repo_access.access_times = vec![chrono::Utc::now(); accesses];
+        repo_access
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let mut repos = HashMap::new();
+        repos.insert("/src/mangit".to_string(), repo(&["rust", "cli"], 4));
+        repos.insert("/src/blog".to_string(), repo(&["web"], 1));
+        repos.insert("/src/tool".to_string(), repo(&["rust"], 2));
+
+        let dot = DotRenderer::render(&repos, &FrecencyConfig::default());
+        assert!(dot.starts_with("graph mangit {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains(
+            "\"repo:/src/mangit\" [shape=circle, label=\"mangit\", tooltip=\"/src/mangit\", width=2.50"
+        ));
+        assert!(dot.contains("\"repo:/src/blog\" [shape=circle, label=\"blog\""));
+        // Every repo was accessed, so none gets the minimum size
+        assert!(!dot.contains("width=0.75"));
+        assert!(dot.contains(
+            "\"tag:rust\" [shape=box, label=\"rust\", tooltip=\"2 repos\", fillcolor=\"0.000 0.800 1.000\"]"
+        ));
+        assert!(dot.contains("\"tag:cli\" [shape=box, label=\"cli\", tooltip=\"1 repos\", fillcolor=\"0.150 0.800 1.000\"]"));
+        assert!(dot.contains("  \"repo:/src/mangit\" -- \"tag:rust\";\n"));
+        assert!(dot.contains("  \"repo:/src/mangit\" -- \"tag:cli\";\n"));
+        assert!(dot.contains("  \"repo:/src/tool\" -- \"tag:rust\";\n"));
+        assert_eq!(dot.matches(" -- ").count(), 4);
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+    }
+
+    #[test]
+    fn test_ids_are_quoted() {
+        let mut repos = HashMap::new();
+        repos.insert("/src/we\"ird".to_string(), repo(&["a\\b"], 0));
+        let dot = DotRenderer::render(&repos, &FrecencyConfig::default());
+        assert!(dot.contains("\"repo:/src/we\\\"ird\" -- \"tag:a\\\\b\";"));
+        // Without any accesses every repo gets the minimum size
+        assert!(dot.contains("width=0.75"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::repository::Repository;
+
+const VAR_PREFIX: &str = "MANGIT_REPO_";
+
+/// Builds one `(variable, path)` pair per repo. Names are the uppercased repo name
+/// with everything but ASCII letters and digits replaced by `_`. Repos whose names
+/// collide get a `_2`, `_3`, ... suffix in the order they are given
+pub fn build_env_declarations(repos: &[Repository]) -> Vec<(String, String)> {
+    let mut used = HashSet::new();
+
+    repos
+        .iter()
+        .map(|repo| {
+            let base = env_var_name(&repo.name);
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while used.contains(&name) {
+                name = format!("{}_{}", base, suffix);
+                suffix += 1;
+            }
+            used.insert(name.clone());
+            (name, repo.path.clone())
+        })
+        .collect()
+}
+
+/// Renders the declarations as `export` lines for `eval` in sh compatible shells
+pub fn render_env_declarations(repos: &[Repository]) -> String {
+    build_env_declarations(repos)
+        .into_iter()
+        .map(|(name, path)| format!("export {}={}\n", name, shell_quote(&path)))
+        .collect()
+}
+
+fn env_var_name(repo_name: &str) -> String {
+    let normalized: String = repo_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", VAR_PREFIX, normalized)
+}
+
+/// Wraps a value in single quotes, escaping any single quotes inside it
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests_env {
+    use super::*;
+
+    fn repo(name: &str, path: &str) -> Repository {
+        Repository::new(name.to_string(), path.to_string(), vec![], String::new())
+    }
+
+    #[test]
+    fn test_name_normalization() {
+        let repos = vec![
+            repo("mangit", "/src/mangit"),
+            repo("my-project.rs", "/src/my-project.rs"),
+            repo("café 2", "/src/café 2"),
+        ];
+
+        let names: Vec<String> = build_env_declarations(&repos)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "MANGIT_REPO_MANGIT",
+                "MANGIT_REPO_MY_PROJECT_RS",
+                "MANGIT_REPO_CAF__2"
+            ]
+        );
+        for name in names {
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        }
+    }
+
+    #[test]
+    fn test_collisions_get_suffixes() {
+        let repos = vec![
+            repo("api", "/work/api"),
+            repo("API", "/personal/api"),
+            repo("api_2", "/old/api_2"),
+            repo("a.p.i", "/tmp/a.p.i"),
+        ];
+
+        let declarations = build_env_declarations(&repos);
+
+        assert_eq!(
+            declarations[0],
+            ("MANGIT_REPO_API".into(), "/work/api".into())
+        );
+        assert_eq!(declarations[1].0, "MANGIT_REPO_API_2");
+        // The literal `api_2` repo can't take the name again
+        assert_eq!(declarations[2].0, "MANGIT_REPO_API_2_2");
+        assert_eq!(declarations[3].0, "MANGIT_REPO_A_P_I");
+    }
+
+    #[test]
+    fn test_render_quotes_paths() {
+        let repos = vec![repo("notes", "/home/me/it's notes")];
+        assert_eq!(
+            render_env_declarations(&repos),
+            "export MANGIT_REPO_NOTES='/home/me/it'\\''s notes'\n"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+use crate::repository::Repository;
+
+/// Renders `abbr` lines for fish: `cd_<name>` changes to the repo and
+/// `mangit_<name>` also records the access. Names are lowercased with everything
+/// but ASCII letters, digits, `-` and `_` replaced by `_`. Repos whose names
+/// collide get a `_2`, `_3`, ... suffix in the order they are given
+pub fn render_fish_abbreviations(repos: &[Repository]) -> String {
+    let mut used = HashSet::new();
+    let mut out = String::new();
+
+    for repo in repos {
+        let base = abbr_name(&repo.name);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while used.contains(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        used.insert(name.clone());
+
+        let path = fish_quote(&repo.path);
+        out += &format!(
+            "abbr -a cd_{} {}\n",
+            name,
+            fish_quote(&format!("cd {}", path))
+        );
+        out += &format!(
+            "abbr -a mangit_{} {}\n",
+            name,
+            fish_quote(&format!("mangit access {}; and cd {}", path, path))
+        );
+    }
+    out
+}
+
+fn abbr_name(repo_name: &str) -> String {
+    repo_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Wraps a value in single quotes, fish only treats `\\` and `\'` specially inside them
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests_fish {
+    use super::*;
+
+    fn repo(name: &str, path: &str) -> Repository {
+        Repository::new(name.to_string(), path.to_string(), vec![], String::new())
+    }
+
+    #[test]
+    fn test_abbreviations() {
+        let output = render_fish_abbreviations(&[repo("mangit", "/src/mangit")]);
+        assert_eq!(
+            output,
+            "abbr -a cd_mangit 'cd \\'/src/mangit\\''\nabbr -a mangit_mangit 'mangit access \\'/src/mangit\\'; and cd \\'/src/mangit\\''\n"
+        );
+    }
+
+    #[test]
+    fn test_paths_with_spaces_and_quotes() {
+        let output = render_fish_abbreviations(&[repo("My Notes", "/home/me/it's my notes")]);
+        let first = output.lines().next().unwrap();
+        assert_eq!(
+            first,
+            r"abbr -a cd_my_notes 'cd \'/home/me/it\\\'s my notes\''"
+        );
+    }
+
+    #[test]
+    fn test_collisions_get_suffixes() {
+        let output = render_fish_abbreviations(&[
+            repo("api", "/work/api"),
+            repo("API", "/personal/api"),
+            repo("a.p.i", "/tmp/a.p.i"),
+        ]);
+        let names: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.split(' ').nth(2))
+            .filter(|name| name.starts_with("cd_"))
+            .collect();
+        assert_eq!(names, vec!["cd_api", "cd_api_2", "cd_a_p_i"]);
+    }
+}
@@ -0,0 +1,85 @@
+/// Width used when the terminal width is unknown
+pub const DEFAULT_TERM_WIDTH: u16 = 80;
+
+/// Horizontal bar chart of how many repos use each language
+pub struct LanguageHistogram;
+
+impl LanguageHistogram {
+    /// Renders one `Name│ ████ count` row per entry, in the given order. Bars are
+    /// scaled so the largest count fills the width left over by the labels, and
+    /// every non-zero count gets at least one block
+    pub fn render(counts: &[(String, usize)], term_width: u16) -> String {
+        let label_width = counts
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let count_width = counts
+            .iter()
+            .map(|(_, count)| count.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let max = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        // "│ " before the bar and " " before the count
+        let bar_width = (term_width as usize)
+            .saturating_sub(label_width + count_width + 3)
+            .max(1);
+
+        let mut out = String::new();
+        for (name, count) in counts {
+            let blocks = if *count == 0 {
+                0
+            } else {
+                (count * bar_width / max).max(1)
+            };
+            out += &format!("{:<label_width$}│ {} {}\n", name, "█".repeat(blocks), count);
+        }
+        out
+    }
+}
+
+/// Width of the terminal from `$COLUMNS`, or [`DEFAULT_TERM_WIDTH`]
+pub fn terminal_width() -> u16 {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(DEFAULT_TERM_WIDTH)
+}
+
+#[cfg(test)]
+mod tests_histogram {
+    use super::*;
+
+    fn counts(entries: &[(&str, usize)]) -> Vec<(String, usize)> {
+        entries
+            .iter()
+            .map(|(name, count)| (name.to_string(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_scales_to_width() {
+        let counts = counts(&[("Rust", 12), ("Python", 4), ("Unknown", 1)]);
+        // 7 label + 2 count + 3 separators leaves 12 blocks for the largest bar
+        assert_eq!(
+            LanguageHistogram::render(&counts, 24),
+            "Rust   │ ████████████ 12\n\
+             Python │ ████ 4\n\
+             Unknown│ █ 1\n"
+        );
+        assert_eq!(
+            LanguageHistogram::render(&counts, 18),
+            "Rust   │ ██████ 12\n\
+             Python │ ██ 4\n\
+             Unknown│ █ 1\n"
+        );
+    }
+
+    #[test]
+    fn test_render_narrow_and_empty() {
+        let counts = counts(&[("Go", 3), ("C", 1)]);
+        assert_eq!(LanguageHistogram::render(&counts, 1), "Go│ █ 3\nC │ █ 1\n");
+        assert_eq!(LanguageHistogram::render(&[], 80), "");
+    }
+}
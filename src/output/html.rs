@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+
+use crate::repository::Repository;
+
+const STYLE: &str = r##"body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #24292f; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+.meta { color: #57606a; margin-bottom: 1rem; }
+#filter { width: 100%; max-width: 30rem; padding: 0.4rem; margin-bottom: 1rem; font-size: 1rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.4rem 0.6rem; border-bottom: 1px solid #d0d7de; }
+th { cursor: pointer; user-select: none; background: #f6f8fa; }
+td.score { text-align: right; font-variant-numeric: tabular-nums; }
+.tag { display: inline-block; padding: 0 0.5rem; margin: 0 0.2rem 0.2rem 0; border-radius: 1rem; background: #ddf4ff; color: #0969da; cursor: pointer; font-size: 0.85rem; }
+.path { font-family: monospace; color: #57606a; }
+.archived { color: #57606a; font-size: 0.85rem; }"##;
+
+const SCRIPT: &str = r##"const filter = document.getElementById("filter");
+const rows = Array.from(document.querySelectorAll("#repos tbody tr"));
+function applyFilter() {
+  const query = filter.value.trim().toLowerCase();
+  rows.forEach(row => {
+    const tagQuery = query.startsWith("tag:") ? query.slice(4) : null;
+    const visible = tagQuery !== null
+      ? row.dataset.tags.split(" ").includes(tagQuery)
+      : row.dataset.name.includes(query) || row.dataset.tags.includes(query);
+    row.style.display = visible ? "" : "none";
+  });
+}
+filter.addEventListener("input", applyFilter);
+document.querySelectorAll(".tag").forEach(chip => chip.addEventListener("click", () => {
+  filter.value = "tag:" + chip.textContent.toLowerCase();
+  applyFilter();
+}));
+document.querySelectorAll("#repos th").forEach((header, column) => {
+  let ascending = true;
+  header.addEventListener("click", () => {
+    const body = document.querySelector("#repos tbody");
+    const numeric = header.dataset.type === "number";
+    rows.sort((a, b) => {
+      const x = a.children[column].textContent;
+      const y = b.children[column].textContent;
+      const order = numeric ? parseFloat(x) - parseFloat(y) : x.localeCompare(y);
+      return ascending ? order : -order;
+    });
+    ascending = !ascending;
+    rows.forEach(row => body.appendChild(row));
+  });
+});"##;
+
+/// Renders repos as a self-contained HTML page with live filtering and sortable columns
+pub struct HtmlRenderer;
+
+impl HtmlRenderer {
+    pub fn render(repos: &[(Repository, f64)], generated_at: DateTime<Utc>) -> String {
+        let mut rows = String::new();
+        for (repo, score) in repos {
+            let tags_attr = repo
+                .tags
+                .iter()
+                .map(|tag| tag.to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let chips: String = repo
+                .tags
+                .iter()
+                .map(|tag| format!(r#"<span class="tag">{}</span>"#, escape_html(tag)))
+                .collect();
+
+            rows.push_str(&format!(
+                r#"      <tr data-name="{}" data-tags="{}">
+        <td>{}{}</td>
+        <td>{}</td>
+        <td>{}</td>
+        <td class="path">{}</td>
+        <td class="score">{:.1}</td>
+      </tr>
+"#,
+                escape_html(&repo.name.to_lowercase()),
+                escape_html(&tags_attr),
+                escape_html(&repo.name),
+                if repo.archived {
+                    r#" <span class="archived">[ARCHIVED]</span>"#
+                } else {
+                    ""
+                },
+                escape_html(repo.language.as_deref().unwrap_or("")),
+                chips,
+                escape_html(&repo.path),
+                score,
+            ));
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>mangit repositories</title>
+  <style>
+{style}
+  </style>
+</head>
+<body>
+  <h1>mangit repositories</h1>
+  <p class="meta">{count} repositories &middot; generated {generated}</p>
+  <input id="filter" type="search" placeholder="Filter by name or tag (click a tag to filter by it)">
+  <table id="repos">
+    <thead>
+      <tr><th>Name</th><th>Language</th><th>Tags</th><th>Path</th><th data-type="number">Score</th></tr>
+    </thead>
+    <tbody>
+{rows}    </tbody>
+  </table>
+  <script>
+{script}
+  </script>
+</body>
+</html>
+"#,
+            style = STYLE,
+            count = repos.len(),
+            generated = generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            rows = rows,
+            script = SCRIPT,
+        )
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests_html {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn repo(name: &str, tags: &[&str]) -> Repository {
+        Repository::new(
+            name.to_string(),
+            format!("/src/{}", name),
+            tags.iter().map(|t| t.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_render_structure() {
+        let repos = vec![
+            (repo("mangit", &["rust", "cli"]), 180.0),
+            (repo("notes", &[]), 10.0),
+        ];
+        let generated_at = Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+
+        let html = HtmlRenderer::render(&repos, generated_at);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</html>"));
+        assert!(html.contains("2 repositories"));
+        assert!(html.contains("generated 2025-03-01 12:00:00 UTC"));
+        assert!(html.contains(r#"<input id="filter""#));
+        assert_eq!(html.matches("<tr data-name=").count(), 2);
+        assert!(html.contains("<td>mangit</td>"));
+        assert!(html.contains("<td>notes</td>"));
+        assert!(html.contains(r#"<span class="tag">rust</span>"#));
+        assert!(html.contains(r#"data-tags="rust cli""#));
+    }
+
+    #[test]
+    fn test_render_escapes_html() {
+        let repos = vec![(repo("<script>", &["a&b"]), 1.0)];
+        let html = HtmlRenderer::render(&repos, Utc::now());
+
+        assert!(html.contains("<td>&lt;script&gt;</td>"));
+        assert!(html.contains(r#"<span class="tag">a&amp;b</span>"#));
+    }
+}
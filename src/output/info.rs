@@ -0,0 +1,221 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::FrecencyConfig;
+use crate::output::yaml::to_yaml;
+use crate::storage::{Priority, RepoAccess};
+use crate::util::ci::CiStatus;
+
+/// Output formats of `mangit info`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum InfoFormat {
+    /// One `Field: value` line per field
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+/// All info fields of a repo, for the structured output formats. Fields whose
+/// information wasn't collected are null
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct RepoInfoOutput {
+    pub path: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub accesses: usize,
+    pub last_access: Option<DateTime<Utc>>,
+    pub frecency: f64,
+    pub priority: Option<Priority>,
+    pub project_url: Option<String>,
+    pub first_commit: Option<DateTime<Utc>>,
+    pub ignored_files: Option<u64>,
+    pub stash_entries: Option<usize>,
+    pub ci_status: Option<CiStatus>,
+}
+
+/// One line of `mangit info` output
+#[derive(Debug, Clone, PartialEq)]
+pub enum InfoLine {
+    Path(String),
+    Description(String),
+    Tags(Vec<String>),
+    Accesses(usize),
+    LastAccess(DateTime<Utc>),
+    Frecency(f64),
+    FirstCommit(DateTime<Utc>),
+    IgnoredCount(u64),
+    StashCount(usize),
+    Priority(Priority),
+    CiStatus(CiStatus),
+    ProjectUrl(String),
+}
+
+impl fmt::Display for InfoLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InfoLine::Path(path) => write!(f, "Path: {}", path),
+            InfoLine::Description(description) => write!(f, "Description: {}", description),
+            InfoLine::Tags(tags) => write!(f, "Tags: {}", tags.join(", ")),
+            InfoLine::Accesses(count) => write!(f, "Accesses: {}", count),
+            InfoLine::LastAccess(time) => {
+                write!(f, "Last access: {}", time.format("%Y-%m-%d %H:%M"))
+            }
+            InfoLine::Frecency(score) => write!(f, "Frecency: {:.1}", score),
+            InfoLine::FirstCommit(date) => write!(f, "First commit: {}", date.format("%Y-%m-%d")),
+            InfoLine::IgnoredCount(count) => write!(f, "Ignored files: {}", count),
+            InfoLine::StashCount(0) => write!(f, "Stash: clean"),
+            InfoLine::StashCount(count) => write!(f, "Stash: {} entries", count),
+            InfoLine::Priority(priority) => write!(f, "Priority: {:?}", priority),
+            InfoLine::CiStatus(status) => write!(f, "CI: {}", status),
+            InfoLine::ProjectUrl(url) => write!(f, "Project: {}", url),
+        }
+    }
+}
+
+/// Collects and prints the information lines about a single repo
+pub struct RepoInfoPrinter {
+    lines: Vec<InfoLine>,
+}
+
+impl RepoInfoPrinter {
+    /// Starts with the information kept in storage
+    pub fn new(path: &str, repo_access: &RepoAccess, weights: &FrecencyConfig) -> Self {
+        let mut lines = vec![InfoLine::Path(path.to_string())];
+        if !repo_access.description.is_empty() {
+            lines.push(InfoLine::Description(repo_access.description.clone()));
+        }
+        lines.push(InfoLine::Tags(repo_access.tags.clone()));
+        lines.push(InfoLine::Accesses(repo_access.access_times.len()));
+        if let Some(last_access) = repo_access.access_times.iter().max() {
+            lines.push(InfoLine::LastAccess(*last_access));
+        }
+        lines.push(InfoLine::Frecency(repo_access.calculate_frecency(weights)));
+        if let Some(priority) = repo_access.priority {
+            lines.push(InfoLine::Priority(priority));
+        }
+        if let Some(url) = &repo_access.project_url {
+            lines.push(InfoLine::ProjectUrl(url.clone()));
+        }
+        if let Some(first_commit) = repo_access.first_commit_date {
+            lines.push(InfoLine::FirstCommit(first_commit));
+        }
+
+        RepoInfoPrinter { lines }
+    }
+
+    /// Appends an extra line, e.g. live data from git
+    pub fn push(&mut self, line: InfoLine) {
+        self.lines.push(line);
+    }
+
+    /// Collects the lines into one structured value
+    pub fn to_output(&self) -> RepoInfoOutput {
+        let mut output = RepoInfoOutput::default();
+        for line in &self.lines {
+            match line.clone() {
+                InfoLine::Path(path) => output.path = path,
+                InfoLine::Description(description) => output.description = Some(description),
+                InfoLine::Tags(tags) => output.tags = tags,
+                InfoLine::Accesses(count) => output.accesses = count,
+                InfoLine::LastAccess(time) => output.last_access = Some(time),
+                InfoLine::Frecency(score) => output.frecency = score,
+                InfoLine::FirstCommit(date) => output.first_commit = Some(date),
+                InfoLine::IgnoredCount(count) => output.ignored_files = Some(count),
+                InfoLine::StashCount(count) => output.stash_entries = Some(count),
+                InfoLine::Priority(priority) => output.priority = Some(priority),
+                InfoLine::CiStatus(status) => output.ci_status = Some(status),
+                InfoLine::ProjectUrl(url) => output.project_url = Some(url),
+            }
+        }
+        output
+    }
+
+    /// Renders the info in the given format
+    pub fn render(&self, format: InfoFormat) -> Result<String> {
+        Ok(match format {
+            InfoFormat::Text => self
+                .lines
+                .iter()
+                .map(|line| format!("{}\n", line))
+                .collect(),
+            InfoFormat::Json => serde_json::to_string_pretty(&self.to_output())? + "\n",
+            InfoFormat::Yaml => to_yaml(&serde_json::to_value(self.to_output())?),
+        })
+    }
+
+    pub fn print_info(&self, format: InfoFormat) -> Result<()> {
+        print!("{}", self.render(format)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_info {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_info_lines_from_storage() {
+        let mut repo_access = RepoAccess::new(vec!["rust".to_string(), "cli".to_string()]);
+        repo_access.first_commit_date = Some(Utc.with_ymd_and_hms(2020, 5, 17, 8, 0, 0).unwrap());
+
+        let mut printer =
+            RepoInfoPrinter::new("/src/mangit", &repo_access, &FrecencyConfig::default());
+        printer.push(InfoLine::IgnoredCount(42));
+
+        let lines: Vec<String> = printer.lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines[0], "Path: /src/mangit");
+        assert_eq!(lines[1], "Tags: rust, cli");
+        assert_eq!(lines[2], "Accesses: 1");
+        assert!(lines.contains(&"First commit: 2020-05-17".to_string()));
+        assert_eq!(lines.last().unwrap(), "Ignored files: 42");
+        // No description line when the description is empty
+        assert!(!lines.iter().any(|line| line.starts_with("Description")));
+    }
+
+    #[test]
+    fn test_structured_formats() {
+        let mut repo_access = RepoAccess::new(vec!["rust".to_string()]);
+        repo_access.priority = Some(Priority::High);
+        let mut printer =
+            RepoInfoPrinter::new("/src/mangit", &repo_access, &FrecencyConfig::default());
+        printer.push(InfoLine::StashCount(2));
+        printer.push(InfoLine::CiStatus(CiStatus::Passed));
+
+        let output = printer.to_output();
+        assert_eq!(output.path, "/src/mangit");
+        assert_eq!(output.tags, vec!["rust"]);
+        assert_eq!(output.stash_entries, Some(2));
+        assert_eq!(output.ignored_files, None);
+
+        let json = printer.render(InfoFormat::Json).unwrap();
+        let parsed: RepoInfoOutput = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, output);
+
+        let yaml = printer.render(InfoFormat::Yaml).unwrap();
+        assert!(yaml.contains("path: \"/src/mangit\"\n"));
+        assert!(yaml.contains("tags:\n  - \"rust\"\n"));
+        assert!(yaml.contains("priority: \"high\"\n"));
+        assert!(yaml.contains("ci_status: \"passed\"\n"));
+        assert!(yaml.contains("ignored_files: null\n"));
+        // One top-level line per field, plus the tag item
+        assert_eq!(yaml.lines().count(), 13);
+
+        assert!(
+            printer
+                .render(InfoFormat::Text)
+                .unwrap()
+                .starts_with("Path: /src/mangit\n")
+        );
+    }
+
+    #[test]
+    fn test_stash_count_line() {
+        assert_eq!(InfoLine::StashCount(0).to_string(), "Stash: clean");
+        assert_eq!(InfoLine::StashCount(3).to_string(), "Stash: 3 entries");
+    }
+}
@@ -0,0 +1,113 @@
+use serde::Serialize;
+
+use crate::repository::Repository;
+
+/// A repo as written by `--output json`: all repo fields plus its score
+#[derive(Serialize)]
+struct JsonRepo<'a> {
+    #[serde(flatten)]
+    repo: &'a Repository,
+    frecency_score: f64,
+}
+
+/// A tag as written by `tags --output json`
+#[derive(Serialize)]
+struct JsonTag<'a> {
+    tag: &'a str,
+    count: usize,
+}
+
+/// Renders repos and tags as JSON arrays for scripts
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+    /// Renders an array with one object per repo, in the given order
+    pub fn render(repos: &[(Repository, f64)]) -> String {
+        let repos: Vec<JsonRepo> = repos
+            .iter()
+            .map(|(repo, score)| JsonRepo {
+                repo,
+                frecency_score: *score,
+            })
+            .collect();
+        to_json(&repos)
+    }
+
+    /// Renders an array of the repos' fields without scores, for `export`
+    pub fn render_repos(repos: &[Repository]) -> String {
+        to_json(&repos)
+    }
+
+    /// Renders an array of `{"tag": ..., "count": ...}` objects, in the given order
+    pub fn render_tags(tags: &[(String, usize)]) -> String {
+        let tags: Vec<JsonTag> = tags
+            .iter()
+            .map(|(tag, count)| JsonTag { tag, count: *count })
+            .collect();
+        to_json(&tags)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    // Plain structs with string keys always serialize
+    serde_json::to_string_pretty(value).expect("serializable value") + "\n"
+}
+
+#[cfg(test)]
+mod tests_json {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_render_repos() {
+        let mut repo = Repository::new(
+            "mangit".to_string(),
+            "/src/mangit".to_string(),
+            vec!["rust".to_string(), "cli".to_string()],
+            "Tag your repos".to_string(),
+        );
+        repo.language = Some("Rust".to_string());
+
+        let output = JsonRenderer::render(&[(repo, 12.5)]);
+        let parsed: Value = serde_json::from_str(&output).unwrap();
+        let repos = parsed.as_array().unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0]["path"], "/src/mangit");
+        assert_eq!(repos[0]["name"], "mangit");
+        assert_eq!(repos[0]["tags"], serde_json::json!(["rust", "cli"]));
+        assert_eq!(repos[0]["language"], "Rust");
+        assert_eq!(repos[0]["frecency_score"], 12.5);
+
+        assert_eq!(JsonRenderer::render(&[]).trim(), "[]");
+    }
+
+    #[test]
+    fn test_render_repos_round_trip() {
+        let mut repo = Repository::new(
+            "mangit".to_string(),
+            "/src/mangit".to_string(),
+            vec!["rust".to_string()],
+            "Tag \"your\" repos".to_string(),
+        );
+        repo.language = Some("Rust".to_string());
+
+        let output = JsonRenderer::render_repos(std::slice::from_ref(&repo));
+        let parsed: Vec<Repository> = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].path, repo.path);
+        assert_eq!(parsed[0].tags, repo.tags);
+        assert_eq!(parsed[0].description, repo.description);
+        assert_eq!(parsed[0].language, repo.language);
+        assert_eq!(parsed[0].last_modified, repo.last_modified);
+    }
+
+    #[test]
+    fn test_render_tags() {
+        let tags = vec![("rust".to_string(), 5), ("cli".to_string(), 2)];
+        let parsed: Value = serde_json::from_str(&JsonRenderer::render_tags(&tags)).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([{"tag": "rust", "count": 5}, {"tag": "cli", "count": 2}])
+        );
+    }
+}
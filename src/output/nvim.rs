@@ -0,0 +1,148 @@
+use crate::repository::Repository;
+
+/// Renders repos as a Lua table of Telescope entries, for `dofile()` in a picker
+pub struct NvimTelescopeRenderer;
+
+impl NvimTelescopeRenderer {
+    /// Each entry has `display = "name [tags]"` and `value = "/abs/path"`
+    pub fn render(repos: &[Repository]) -> String {
+        let entries = repos
+            .iter()
+            .map(|repo| {
+                let display = if repo.tags.is_empty() {
+                    repo.name.clone()
+                } else {
+                    format!("{} [{}]", repo.name, repo.tags.join(", "))
+                };
+                format!(
+                    "  {{ display = {}, value = {} }},\n",
+                    lua_string(&display),
+                    lua_string(&repo.path)
+                )
+            })
+            .collect();
+        lua_table(entries)
+    }
+}
+
+/// Renders repos as a Lua table of quickfix items for `vim.fn.setqflist()`
+pub struct NvimQuickfixRenderer;
+
+impl NvimQuickfixRenderer {
+    /// Each entry has `filename = "/abs/path"` and the tags as `text`
+    pub fn render(repos: &[Repository]) -> String {
+        let entries = repos
+            .iter()
+            .map(|repo| {
+                format!(
+                    "  {{ filename = {}, text = {} }},\n",
+                    lua_string(&repo.path),
+                    lua_string(&repo.tags.join(", "))
+                )
+            })
+            .collect();
+        lua_table(entries)
+    }
+}
+
+/// Wraps the entries in a `return { ... }` chunk so the file can be `dofile()`d
+fn lua_table(entries: String) -> String {
+    format!("return {{\n{}}}\n", entries)
+}
+
+/// Quotes a value as a Lua string literal
+fn lua_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\{:03}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests_nvim {
+    use super::*;
+
+    fn repo(name: &str, path: &str, tags: &[&str]) -> Repository {
+        Repository::new(
+            name.to_string(),
+            path.to_string(),
+            tags.iter().map(|tag| tag.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    /// Checks that braces balance outside of string literals and every string is closed
+    fn assert_valid_lua_table(lua: &str) {
+        let mut depth = 0i32;
+        let mut chars = lua.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    assert!(depth >= 0, "unbalanced braces in {}", lua);
+                }
+                '"' => loop {
+                    match chars.next() {
+                        Some('\\') => {
+                            chars.next();
+                        }
+                        Some('"') => break,
+                        Some('\n') | None => panic!("unterminated string in {}", lua),
+                        Some(_) => {}
+                    }
+                },
+                _ => {}
+            }
+        }
+        assert_eq!(depth, 0, "unbalanced braces in {}", lua);
+        assert!(lua.starts_with("return {"));
+    }
+
+    #[test]
+    fn test_telescope_entries() {
+        let repos = vec![
+            repo("mangit", "/src/mangit", &["rust", "cli"]),
+            repo("notes", "/src/notes", &[]),
+        ];
+        let lua = NvimTelescopeRenderer::render(&repos);
+        assert_eq!(
+            lua,
+            "return {\n  { display = \"mangit [rust, cli]\", value = \"/src/mangit\" },\n  { display = \"notes\", value = \"/src/notes\" },\n}\n"
+        );
+        assert_valid_lua_table(&lua);
+    }
+
+    #[test]
+    fn test_quickfix_entries() {
+        let lua = NvimQuickfixRenderer::render(&[repo("mangit", "/src/mangit", &["rust"])]);
+        assert_eq!(
+            lua,
+            "return {\n  { filename = \"/src/mangit\", text = \"rust\" },\n}\n"
+        );
+        assert_valid_lua_table(&lua);
+        assert_eq!(NvimQuickfixRenderer::render(&[]), "return {\n}\n");
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        let repos = vec![repo("we\"ird}", "/src/a\\b\nc", &["{tag}"])];
+        let lua = NvimTelescopeRenderer::render(&repos);
+        assert!(lua.contains(r#""we\"ird} [{tag}]""#));
+        assert!(lua.contains(r#""/src/a\\b\nc""#));
+        assert_valid_lua_table(&lua);
+        assert_valid_lua_table(&NvimQuickfixRenderer::render(&repos));
+        assert_eq!(lua_string("a\u{1}b"), "\"a\\001b\"");
+    }
+}
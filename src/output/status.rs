@@ -0,0 +1,86 @@
+use crate::util::git::GitStatus;
+
+const HEADER: [&str; 4] = ["NAME", "BRANCH", "CHANGED", "UNTRACKED"];
+
+/// Renders the git status of repos as an aligned table for `mangit status`
+pub struct StatusTable;
+
+impl StatusTable {
+    /// One row per repo, in the given order. Clean repos show a ✓ instead of counts
+    pub fn render(rows: &[(String, GitStatus)]) -> String {
+        let cells: Vec<[String; 4]> = rows
+            .iter()
+            .map(|(name, status)| {
+                let branch = status.branch.as_deref().unwrap_or("(detached)").to_string();
+                if status.is_clean() {
+                    [name.clone(), branch, "✓".to_string(), String::new()]
+                } else {
+                    [
+                        name.clone(),
+                        branch,
+                        status.changed_count.to_string(),
+                        status.untracked_count.to_string(),
+                    ]
+                }
+            })
+            .collect();
+
+        let mut widths = HEADER.map(|title| title.chars().count());
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let header = HEADER.map(str::to_string);
+        std::iter::once(&header)
+            .chain(&cells)
+            .map(|row| {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect();
+                line.join("  ").trim_end().to_string() + "\n"
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_status {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let rows = vec![
+            (
+                "mangit".to_string(),
+                GitStatus {
+                    branch: Some("main".to_string()),
+                    changed_count: 3,
+                    untracked_count: 1,
+                },
+            ),
+            (
+                "notes".to_string(),
+                GitStatus {
+                    branch: None,
+                    changed_count: 0,
+                    untracked_count: 0,
+                },
+            ),
+        ];
+
+        assert_eq!(
+            StatusTable::render(&rows),
+            "NAME    BRANCH      CHANGED  UNTRACKED\n\
+             mangit  main        3        1\n\
+             notes   (detached)  ✓\n"
+        );
+        assert_eq!(
+            StatusTable::render(&[]),
+            "NAME  BRANCH  CHANGED  UNTRACKED\n"
+        );
+    }
+}
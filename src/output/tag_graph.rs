@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Builds a spanning forest over tag pairs that co-occur at least `threshold` times.
+/// Stronger pairs are considered first, so each tag is linked to the tags it appears
+/// with most often (a minimum spanning tree when weights are inverted counts)
+pub fn build_tag_graph(
+    cooccurrence: &HashMap<(String, String), usize>,
+    threshold: usize,
+) -> Vec<(String, String)> {
+    let mut pairs: Vec<(&(String, String), &usize)> = cooccurrence
+        .iter()
+        .filter(|(_, count)| **count >= threshold)
+        .collect();
+
+    // Strongest pairs first, names as tie-breaker for stable output
+    pairs.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    // Union-find over tag names (Kruskal)
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    fn find<'a>(parent: &mut HashMap<&'a str, &'a str>, tag: &'a str) -> &'a str {
+        let mut root = tag;
+        while let Some(&p) = parent.get(root) {
+            if p == root {
+                break;
+            }
+            root = p;
+        }
+        parent.insert(tag, root);
+        root
+    }
+
+    let mut edges = Vec::new();
+    for ((a, b), _) in pairs {
+        parent.entry(a).or_insert(a);
+        parent.entry(b).or_insert(b);
+
+        let root_a = find(&mut parent, a);
+        let root_b = find(&mut parent, b);
+        if root_a != root_b {
+            parent.insert(root_a, root_b);
+            edges.push((a.clone(), b.clone()));
+        }
+    }
+
+    edges
+}
+
+/// Renders graph edges as ASCII rows. Each chain of linked tags is printed on one
+/// row, branches continue on the following rows below the tag they split from
+pub fn render_tag_graph(edges: &[(String, String)]) -> String {
+    let mut adjacency: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (a, b) in edges {
+        adjacency.entry(a).or_default().push(b);
+        adjacency.entry(b).or_default().push(a);
+    }
+    for neighbours in adjacency.values_mut() {
+        neighbours.sort();
+    }
+
+    let mut visited = HashSet::new();
+    let mut lines = Vec::new();
+
+    // Start each tree from a leaf so the first row is as long as possible
+    for (tag, neighbours) in &adjacency {
+        if neighbours.len() == 1 && !visited.contains(tag) {
+            lines.push(String::new());
+            render_node(tag, 0, &adjacency, &mut visited, &mut lines);
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn render_node<'a>(
+    tag: &'a str,
+    column: usize,
+    adjacency: &BTreeMap<&'a str, Vec<&'a str>>,
+    visited: &mut HashSet<&'a str>,
+    lines: &mut Vec<String>,
+) {
+    visited.insert(tag);
+    if let Some(line) = lines.last_mut() {
+        line.push_str(tag);
+    }
+
+    let children: Vec<&str> = adjacency[tag]
+        .iter()
+        .copied()
+        .filter(|child| !visited.contains(child))
+        .collect();
+
+    for (i, child) in children.into_iter().enumerate() {
+        if i == 0 {
+            if let Some(line) = lines.last_mut() {
+                line.push_str(" --- ");
+            }
+            render_node(
+                child,
+                column + tag.chars().count() + 5,
+                adjacency,
+                visited,
+                lines,
+            );
+        } else {
+            lines.push(format!("{}`--- ", " ".repeat(column)));
+            render_node(child, column + 5, adjacency, visited, lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_tag_graph {
+    use super::*;
+
+    fn pair(a: &str, b: &str) -> (String, String) {
+        (a.to_string(), b.to_string())
+    }
+
+    #[test]
+    fn test_build_tag_graph_threshold() {
+        let mut cooccurrence = HashMap::new();
+        cooccurrence.insert(pair("cli", "rust"), 3);
+        cooccurrence.insert(pair("cli", "terminal"), 2);
+        cooccurrence.insert(pair("python", "web"), 1);
+
+        let edges = build_tag_graph(&cooccurrence, 2);
+        assert_eq!(edges, vec![pair("cli", "rust"), pair("cli", "terminal")]);
+
+        let edges = build_tag_graph(&cooccurrence, 1);
+        assert_eq!(edges.len(), 3);
+    }
+
+    #[test]
+    fn test_build_tag_graph_skips_cycles() {
+        let mut cooccurrence = HashMap::new();
+        cooccurrence.insert(pair("cli", "rust"), 5);
+        cooccurrence.insert(pair("rust", "terminal"), 4);
+        cooccurrence.insert(pair("cli", "terminal"), 1);
+
+        let edges = build_tag_graph(&cooccurrence, 1);
+        assert_eq!(edges, vec![pair("cli", "rust"), pair("rust", "terminal")]);
+    }
+
+    #[test]
+    fn test_render_tag_graph_chain() {
+        let edges = vec![pair("cli", "rust"), pair("cli", "terminal")];
+        assert_eq!(render_tag_graph(&edges), "rust --- cli --- terminal");
+    }
+
+    #[test]
+    fn test_render_tag_graph_branch() {
+        let edges = vec![
+            pair("cli", "rust"),
+            pair("cli", "terminal"),
+            pair("cli", "tui"),
+        ];
+        assert_eq!(
+            render_tag_graph(&edges),
+            "rust --- cli --- terminal\n         `--- tui"
+        );
+    }
+
+    #[test]
+    fn test_render_tag_graph_separate_trees() {
+        let edges = vec![pair("cli", "rust"), pair("python", "web")];
+        assert_eq!(render_tag_graph(&edges), "cli --- rust\npython --- web");
+    }
+
+    #[test]
+    fn test_render_tag_graph_empty() {
+        assert_eq!(render_tag_graph(&[]), "");
+    }
+}
@@ -0,0 +1,89 @@
+use crate::repository::Repository;
+
+/// Renders repos as a TOML document with one `[[repos]]` table per repo
+pub struct TomlRenderer;
+
+impl TomlRenderer {
+    /// Repos without a language leave the key out, TOML has no null
+    pub fn render(repos: &[Repository]) -> String {
+        let mut output = String::new();
+        for repo in repos {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output += "[[repos]]\n";
+            output += &format!("path = {}\n", quote(&repo.path));
+            output += &format!("name = {}\n", quote(&repo.name));
+            let tags: Vec<String> = repo.tags.iter().map(|tag| quote(tag)).collect();
+            output += &format!("tags = [{}]\n", tags.join(", "));
+            output += &format!("description = {}\n", quote(&repo.description));
+            if let Some(language) = &repo.language {
+                output += &format!("language = {}\n", quote(language));
+            }
+            output += &format!("last_modified = {}\n", repo.last_modified.to_rfc3339());
+        }
+        output
+    }
+}
+
+/// Writes a TOML basic string
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests_toml {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_render() {
+        let mut repo = Repository::new(
+            "mangit".to_string(),
+            "/src/mangit".to_string(),
+            vec!["rust".to_string(), "cli".to_string()],
+            "Say \"hi\"\\n".to_string(),
+        );
+        repo.language = Some("Rust".to_string());
+        repo.last_modified = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let mut plain = repo.clone();
+        plain.path = "/src/notes".to_string();
+        plain.name = "notes".to_string();
+        plain.tags = Vec::new();
+        plain.description = "\u{1}".to_string();
+        plain.language = None;
+
+        assert_eq!(
+            TomlRenderer::render(&[repo, plain]),
+            "[[repos]]\n\
+             path = \"/src/mangit\"\n\
+             name = \"mangit\"\n\
+             tags = [\"rust\", \"cli\"]\n\
+             description = \"Say \\\"hi\\\"\\\\n\"\n\
+             language = \"Rust\"\n\
+             last_modified = 2024-05-01T12:00:00+00:00\n\
+             \n\
+             [[repos]]\n\
+             path = \"/src/notes\"\n\
+             name = \"notes\"\n\
+             tags = []\n\
+             description = \"\\u0001\"\n\
+             last_modified = 2024-05-01T12:00:00+00:00\n"
+        );
+        assert_eq!(TomlRenderer::render(&[]), "");
+    }
+}
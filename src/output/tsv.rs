@@ -0,0 +1,93 @@
+use crate::repository::Repository;
+
+const HEADER: [&str; 5] = ["NAME", "LANGUAGE", "TAGS", "PATH", "SCORE"];
+
+/// Renders repos as tab-separated values for spreadsheet tools
+pub struct TsvRenderer;
+
+impl TsvRenderer {
+    /// Renders a header row and one row per repo. Tags are joined with `;`
+    pub fn render(repos: &[(Repository, f64)]) -> String {
+        let mut output = HEADER.join("\t");
+        output.push('\n');
+
+        for (repo, score) in repos {
+            let fields = [
+                escape_field(&repo.name),
+                escape_field(repo.language.as_deref().unwrap_or("")),
+                escape_field(&repo.tags.join(";")),
+                escape_field(&repo.path),
+                format!("{:.1}", score),
+            ];
+            output.push_str(&fields.join("\t"));
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// Escapes characters that would break the row or column structure
+fn escape_field(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests_tsv {
+    use super::*;
+
+    fn repo(name: &str, path: &str, tags: &[&str], language: Option<&str>) -> Repository {
+        let mut repo = Repository::new(
+            name.to_string(),
+            path.to_string(),
+            tags.iter().map(|t| t.to_string()).collect(),
+            String::new(),
+        );
+        repo.language = language.map(str::to_string);
+        repo
+    }
+
+    #[test]
+    fn test_render_header_and_rows() {
+        let repos = vec![
+            (
+                repo("mangit", "/src/mangit", &["rust", "cli"], Some("Rust")),
+                180.0,
+            ),
+            (repo("notes", "/src/notes", &[], None), 10.25),
+        ];
+
+        let output = TsvRenderer::render(&repos);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "NAME\tLANGUAGE\tTAGS\tPATH\tSCORE");
+        assert_eq!(lines[1], "mangit\tRust\trust;cli\t/src/mangit\t180.0");
+        assert_eq!(lines[2], "notes\t\t\t/src/notes\t10.2");
+        for line in lines {
+            assert_eq!(line.matches('\t').count(), 4);
+        }
+    }
+
+    #[test]
+    fn test_render_escapes_special_characters() {
+        let repos = vec![(repo("odd\tname", "/src/odd\nrepo", &["a"], None), 1.0)];
+
+        let output = TsvRenderer::render(&repos);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "odd\\tname\t\ta\t/src/odd\\nrepo\t1.0");
+        assert_eq!(lines[1].matches('\t').count(), 4);
+    }
+}
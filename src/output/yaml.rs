@@ -0,0 +1,118 @@
+use serde_json::Value;
+
+/// Renders a JSON value as a YAML document, keys in the map's (sorted) order.
+/// Strings are written as double quoted scalars, whose escapes are a superset of
+/// JSON's, so any value round-trips
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_map(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => write_list(&mut out, items, 0),
+        scalar => {
+            out += &scalar_to_yaml(scalar);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn write_map(out: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        out.push_str(&" ".repeat(indent));
+        out.push_str(&key_to_yaml(key));
+        out.push(':');
+        write_nested(out, value, indent);
+    }
+}
+
+fn write_list(out: &mut String, items: &[Value], indent: usize) {
+    for item in items {
+        out.push_str(&" ".repeat(indent));
+        out.push('-');
+        write_nested(out, item, indent);
+    }
+}
+
+/// Writes the value after a `key:` or `-`, on the same line if it is a scalar
+fn write_nested(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_map(out, map, indent + 2);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_list(out, items, indent + 2);
+        }
+        scalar => {
+            out.push(' ');
+            out.push_str(&scalar_to_yaml(scalar));
+            out.push('\n');
+        }
+    }
+}
+
+fn scalar_to_yaml(value: &Value) -> String {
+    match value {
+        Value::Object(_) => "{}".to_string(),
+        Value::Array(_) => "[]".to_string(),
+        // Null, booleans, numbers and quoted strings are written the same in both
+        other => other.to_string(),
+    }
+}
+
+/// Keys made of letters, digits and `_` stay bare, anything else is quoted
+fn key_to_yaml(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests_yaml {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_values() {
+        let value = json!({
+            "path": "/src/mangit",
+            "tags": ["rust", "cli"],
+            "frecency": 12.5,
+            "priority": null,
+            "archived": false,
+            "empty": [],
+            "stats": {"files": 3, "weird key": "a: b"},
+            "groups": [{"name": "x"}, [1, 2]]
+        });
+        assert_eq!(
+            to_yaml(&value),
+            "archived: false\n\
+             empty: []\n\
+             frecency: 12.5\n\
+             groups:\n  -\n    name: \"x\"\n  -\n    - 1\n    - 2\n\
+             path: \"/src/mangit\"\n\
+             priority: null\n\
+             stats:\n  files: 3\n  \"weird key\": \"a: b\"\n\
+             tags:\n  - \"rust\"\n  - \"cli\"\n"
+        );
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        let value = json!({"description": "line one\n\"quoted\" # not a comment"});
+        assert_eq!(
+            to_yaml(&value),
+            "description: \"line one\\n\\\"quoted\\\" # not a comment\"\n"
+        );
+    }
+
+    #[test]
+    fn test_top_level_scalars() {
+        assert_eq!(to_yaml(&json!("x")), "\"x\"\n");
+        assert_eq!(to_yaml(&json!({})), "{}\n");
+        assert_eq!(to_yaml(&json!([1])), "- 1\n");
+    }
+}
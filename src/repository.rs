@@ -1,6 +1,108 @@
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use std::path::Path;
+use std::process::Command;
+
+use crate::config::is_git_repo;
+use crate::storage::RepoAccess;
+use crate::util::git;
+
+// README files checked for a description, in priority order
+const README_FILES: [&str; 3] = ["README.md", "README.rst", "README.txt"];
+const MAX_DESCRIPTION_LEN: usize = 200;
+// Build files that give away a project's language, the first one found wins.
+// Ruby, PHP and Elixir come before package.json, which their web projects often
+// have for frontend assets
+const LANGUAGE_FILES: [(&str, &str); 20] = [
+    ("Cargo.toml", "Rust"),
+    ("Gemfile", "Ruby"),
+    ("composer.json", "PHP"),
+    ("mix.exs", "Elixir"),
+    ("package.json", "JavaScript/TypeScript"),
+    ("go.mod", "Go"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Kotlin"),
+    ("build.sbt", "Scala"),
+    ("requirements.txt", "Python"),
+    ("setup.py", "Python"),
+    ("CMakeLists.txt", "C/C++"),
+    ("Package.swift", "Swift"),
+    ("build.zig", "Zig"),
+    ("pubspec.yaml", "Dart"),
+    ("Makefile.PL", "Perl"),
+    ("*.pm", "Perl"),
+    ("*.csproj", "C#"),
+    ("*.sln", "C#"),
+];
+// Source file extensions counted when there is no build file
+const LANGUAGE_EXTENSIONS: [(&str, &str); 23] = [
+    ("rs", "Rust"),
+    ("js", "JavaScript/TypeScript"),
+    ("ts", "JavaScript/TypeScript"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("py", "Python"),
+    ("c", "C/C++"),
+    ("h", "C/C++"),
+    ("cc", "C/C++"),
+    ("cpp", "C/C++"),
+    ("hpp", "C/C++"),
+    ("rb", "Ruby"),
+    ("swift", "Swift"),
+    ("kt", "Kotlin"),
+    ("scala", "Scala"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("zig", "Zig"),
+    ("pl", "Perl"),
+    ("pm", "Perl"),
+    ("dart", "Dart"),
+    ("cs", "C#"),
+    ("php", "PHP"),
+];
+// File in a repo's root listing its tags
+pub const TAG_FILE: &str = ".mangit-tags";
+
+/// How a repo's tags have to match the tags searched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagRequirement {
+    /// Repos must have all of the tags
+    #[default]
+    All,
+    /// Repos must have at least one of the tags
+    Any,
+    /// Repos must have exactly the tags, no more and no less
+    Exact,
+}
+
+impl TagRequirement {
+    /// Checks a repo's tags against the wanted tags. Tags are compared ignoring
+    /// case, the way `search` has always matched them, so `Rust` meets `rust`
+    /// and `Exact` treats `[CLI, rust]` and `[cli, Rust]` as the same set
+    pub fn is_met(&self, wanted: &[String], tags: &[String]) -> bool {
+        let contains = |list: &[String], tag: &String| {
+            let tag = tag.to_lowercase();
+            list.iter().any(|t| t.to_lowercase() == tag)
+        };
+
+        match self {
+            TagRequirement::All => wanted.iter().all(|tag| contains(tags, tag)),
+            TagRequirement::Any => wanted.iter().any(|tag| contains(tags, tag)),
+            TagRequirement::Exact => {
+                wanted.iter().all(|tag| contains(tags, tag))
+                    && tags.iter().all(|tag| contains(wanted, tag))
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Repository {
@@ -10,6 +112,10 @@ pub struct Repository {
     pub description: String,
     pub last_modified: DateTime<Utc>,
     pub language: Option<String>,
+    #[serde(default)]
+    pub language_version: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
 }
 
 impl Repository {
@@ -21,38 +127,35 @@ impl Repository {
             description,
             last_modified: Utc::now(),
             language: None,
+            language_version: None,
+            archived: false,
         }
     }
 
     pub fn detect_language(&mut self) {
-        let path = Path::new(&self.path);
-
-        // Check for common project files to determine language
-        if path.join("Cargo.toml").exists() {
-            self.language = Some("Rust".to_string());
-        } else if path.join("package.json").exists() {
-            self.language = Some("JavaScript/TypeScript".to_string());
-        } else if path.join("go.mod").exists() {
-            self.language = Some("Go".to_string());
-        } else if path.join("pom.xml").exists() || path.join("build.gradle").exists() {
-            self.language = Some("Java".to_string());
-        } else if path.join("requirements.txt").exists() || path.join("setup.py").exists() {
-            self.language = Some("Python".to_string());
-        } else if path.join("CMakeLists.txt").exists() {
-            self.language = Some("C/C++".to_string());
+        if let Some(language) = detect_language(Path::new(&self.path)) {
+            self.language = Some(language);
         }
-        // More language detection can be added here
     }
 
+    /// Checks whether any token of the query matches the repo. A `path:` token is
+    /// matched against the full path as a glob, or as a prefix if it has no `*`
     pub fn matches_query(&self, query: &str) -> bool {
         if query.is_empty() {
             return true;
         }
 
-        let query_lower = query.to_lowercase();
-        let tokens: Vec<&str> = query_lower.split_whitespace().collect();
+        for token in query.split_whitespace() {
+            if let Some(pattern) = token.strip_prefix("path:") {
+                if matches_path_pattern(&self.path, pattern) {
+                    return true;
+                }
+                continue;
+            }
+
+            let token = token.to_lowercase();
+            let token = token.as_str();
 
-        for token in tokens {
             if self.name.to_lowercase().contains(token) {
                 return true;
             }
@@ -69,31 +172,359 @@ impl Repository {
                 return true;
             }
 
-            if let Some(lang) = &self.language {
-                if lang.to_lowercase().contains(token) {
-                    return true;
-                }
+            if let Some(lang) = &self.language
+                && lang.to_lowercase().contains(token)
+            {
+                return true;
             }
         }
 
         false
     }
+}
 
-    pub fn matches_tags(&self, tags: &[String]) -> bool {
-        if tags.is_empty() {
-            return true;
+/// Builds a repository from a storage entry. The name is the chosen display name,
+/// the language is the cached one if any (call `detect_language` to fill it in)
+impl From<(&str, &RepoAccess)> for Repository {
+    fn from((path, repo_access): (&str, &RepoAccess)) -> Self {
+        let mut repo = Repository::new(
+            repo_access.display_name(path),
+            path.to_string(),
+            repo_access.tags.clone(),
+            repo_access.description.clone(),
+        );
+        if let Some(last_access) = repo_access.access_times.iter().max() {
+            repo.last_modified = *last_access;
+        }
+        repo.language = repo_access.language.clone();
+        repo.archived = repo_access.archived;
+        repo
+    }
+}
+
+/// How much each field of a repo contributed to its match against a query,
+/// see `score_breakdown`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScoreBreakdown {
+    pub name_score: i64,
+    pub tag_score: i64,
+    pub desc_score: i64,
+    pub lang_score: i64,
+    pub total: i64,
+}
+
+/// Shows the breakdown as `[name:45 tags:2 desc:0 lang:1 = 48]`
+impl fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[name:{} tags:{} desc:{} lang:{} = {}]",
+            self.name_score, self.tag_score, self.desc_score, self.lang_score, self.total
+        )
+    }
+}
+
+/// Scores a repo against a query: fuzzy match scores for the name and description,
+/// one point per query term that is one of the repo's tags and one point if the
+/// language contains the query. Terms are separated by commas or whitespace
+pub fn score_breakdown(repo: &Repository, query: &str, matcher: &SkimMatcherV2) -> ScoreBreakdown {
+    let terms: Vec<String> = query
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let fuzzy = |text: &str| {
+        terms
+            .iter()
+            .filter_map(|term| matcher.fuzzy_match(text, term))
+            .sum::<i64>()
+    };
+
+    let name_score = fuzzy(&repo.name);
+    let desc_score = fuzzy(&repo.description);
+    let tag_score = terms
+        .iter()
+        .filter(|term| repo.tags.iter().any(|tag| tag.to_lowercase() == **term))
+        .count() as i64;
+    let lang_score = repo.language.as_ref().map_or(0, |language| {
+        let language = language.to_lowercase();
+        i64::from(terms.iter().any(|term| language.contains(term.as_str())))
+    });
+
+    ScoreBreakdown {
+        name_score,
+        tag_score,
+        desc_score,
+        lang_score,
+        total: name_score + tag_score + desc_score + lang_score,
+    }
+}
+
+/// Detects a project's language from the build files in its root directory,
+/// falling back to the most common source file extension there
+pub fn detect_language(path: &Path) -> Option<String> {
+    LANGUAGE_FILES
+        .iter()
+        .find(|(pattern, _)| has_file(path, pattern))
+        .map(|(_, language)| language.to_string())
+        .or_else(|| detect_language_from_extension(path))
+}
+
+/// Picks the language with the most source files directly in `path`, ties go to
+/// the alphabetically first language
+pub fn detect_language_from_extension(path: &Path) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for entry in fs::read_dir(path).ok()?.flatten() {
+        let file_name = entry.file_name();
+        let Some(extension) = Path::new(&file_name).extension() else {
+            continue;
+        };
+        if let Some((_, language)) = LANGUAGE_EXTENSIONS
+            .iter()
+            .find(|(known, _)| extension.eq_ignore_ascii_case(known))
+        {
+            *counts.entry(language).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(language, _)| language.to_string())
+}
+
+/// Checks for a file in the root of `path`. A `*.ext` pattern matches any file
+/// with that extension
+fn has_file(path: &Path, pattern: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(extension) => fs::read_dir(path).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                Path::new(&entry.file_name())
+                    .extension()
+                    .is_some_and(|ext| ext == extension)
+            })
+        }),
+        None => path.join(pattern).exists(),
+    }
+}
+
+/// Detects the CI services a project is set up for from their config files and
+/// returns a `ci:<service>` tag for each
+pub fn detect_ci(path: &Path) -> Vec<String> {
+    let services = [
+        (&[".github/workflows"][..], "github-actions"),
+        (&[".gitlab-ci.yml"][..], "gitlab"),
+        (&["Jenkinsfile"][..], "jenkins"),
+        (&[".travis.yml"][..], "travis"),
+        (&["circle.yml", ".circleci"][..], "circleci"),
+    ];
+
+    services
+        .iter()
+        .filter(|(files, _)| files.iter().any(|file| path.join(file).exists()))
+        .map(|(_, service)| format!("ci:{}", service))
+        .collect()
+}
+
+/// Detects the toolchain version a project asks for, e.g. the Rust edition or the
+/// Node.js engine from package.json
+pub fn detect_language_version(path: &Path, language: &str) -> Option<String> {
+    match language {
+        "Rust" => {
+            let output = Command::new("cargo")
+                .args(["metadata", "--no-deps", "--format-version", "1"])
+                .current_dir(path)
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            parse_cargo_metadata_version(&String::from_utf8_lossy(&output.stdout))
+        }
+        "JavaScript/TypeScript" => {
+            parse_node_engine(&fs::read_to_string(path.join("package.json")).ok()?)
+        }
+        "Python" => parse_python_requires(&fs::read_to_string(path.join("setup.py")).ok()?),
+        _ => None,
+    }
+}
+
+/// Reads the `rust-version` of the first package in `cargo metadata` output,
+/// falling back to its edition
+fn parse_cargo_metadata_version(metadata: &str) -> Option<String> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata).ok()?;
+    let package = metadata.get("packages")?.get(0)?;
+    if let Some(rust_version) = package.get("rust_version").and_then(|v| v.as_str()) {
+        return Some(rust_version.to_string());
+    }
+    let edition = package.get("edition")?.as_str()?;
+    Some(format!("edition {}", edition))
+}
+
+/// Reads `engines.node` from a package.json
+fn parse_node_engine(package_json: &str) -> Option<String> {
+    let package: serde_json::Value = serde_json::from_str(package_json).ok()?;
+    let node = package.get("engines")?.get("node")?.as_str()?;
+    Some(node.to_string())
+}
+
+/// Reads the `python_requires` argument from a setup.py
+fn parse_python_requires(setup_py: &str) -> Option<String> {
+    let start = setup_py.find("python_requires")?;
+    let value = setup_py[start + "python_requires".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    let end = value.find(quote)?;
+    Some(value[..end].to_string())
+}
+
+/// Matches a path against a pattern where `*` stands for any run of characters.
+/// Patterns without a `*` match any path they are a prefix of
+pub fn matches_path_pattern(path: &str, pattern: &str) -> bool {
+    if !pattern.contains('*') {
+        return path.starts_with(pattern);
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, rest) = (parts[0], &parts[1..]);
+    let Some(mut remaining) = path.strip_prefix(first) else {
+        return false;
+    };
+
+    // The last part has to sit at the end of the path, the ones in between
+    // are matched as early as possible
+    let (last, middle) = rest.split_last().expect("pattern contains a '*'");
+    for part in middle {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
         }
+    }
+
+    remaining.ends_with(last)
+}
+
+/// Returns the last component of a repo path, used as the repo's display name
+pub fn repo_name_from_path(path: &str) -> String {
+    Path::new(path)
+        .components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .next_back()
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Extracts a short description from the first non-empty, non-heading line of the
+/// repo's README. Returns None if there is no README or it has no such line
+pub fn extract_readme_description(path: &Path) -> Result<Option<String>> {
+    let Some(readme_path) = README_FILES
+        .iter()
+        .map(|name| path.join(name))
+        .find(|readme_path| readme_path.is_file())
+    else {
+        return Ok(None);
+    };
+
+    let content = fs::read_to_string(&readme_path)
+        .with_context(|| format!("Failed to read {}", readme_path.display()))?;
+
+    // A line followed by an underline is a reStructuredText heading
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    let line = lines.iter().enumerate().find_map(|(i, line)| {
+        let is_heading = line.starts_with('#')
+            || is_heading_underline(line)
+            || lines
+                .get(i + 1)
+                .is_some_and(|next| is_heading_underline(next));
+        (!line.is_empty() && !is_heading).then_some(*line)
+    });
 
-        for tag in tags {
-            if !self.tags.iter().any(|t| t == tag) {
-                return false;
+    Ok(line
+        .map(strip_markdown)
+        .filter(|description| !description.is_empty())
+        .map(|description| description.chars().take(MAX_DESCRIPTION_LEN).collect()))
+}
+
+/// Makes up a description for a repo from, in order: its README, the subject of
+/// its latest commit, or its name with `-` and `_` turned into spaces
+pub fn auto_generate_description(path: &Path, repo: &Repository) -> Option<String> {
+    if let Ok(Some(description)) = extract_readme_description(path) {
+        return Some(description);
+    }
+
+    if is_git_repo(path)
+        && let Some(subject) = git::last_commit_subject(&path.to_string_lossy())
+    {
+        return Some(subject.chars().take(MAX_DESCRIPTION_LEN).collect());
+    }
+
+    let words = repo.name.replace(['-', '_'], " ");
+    let words = words.trim();
+    let mut chars = words.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// Reads the tags listed in the repo's `.mangit-tags` file, separated by commas or
+/// newlines. `#` starts a comment. Returns None if the repo has no tag file
+pub fn read_tag_file(path: &Path) -> Result<Option<Vec<String>>> {
+    let tag_file = path.join(TAG_FILE);
+    if !tag_file.is_file() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&tag_file)
+        .with_context(|| format!("Failed to read {}", tag_file.display()))?;
+
+    let mut tags: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for tag in line.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+            if !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
             }
         }
+    }
+    Ok(Some(tags))
+}
 
-        true
+/// Checks for reStructuredText heading underlines like `=====` or `-----`
+fn is_heading_underline(line: &str) -> bool {
+    let mut chars = line.chars();
+    match chars.next() {
+        Some(first) if "=-~^*+#".contains(first) => chars.all(|c| c == first),
+        _ => false,
     }
 }
 
+/// Removes bold, italic and code markers from a line of Markdown
+fn strip_markdown(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut result = String::with_capacity(line.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '*' | '`' => continue,
+            // Only underscores at word boundaries are emphasis, keep snake_case intact
+            '_' => {
+                let prev_is_word = i > 0 && chars[i - 1].is_alphanumeric();
+                let next_is_word = chars.get(i + 1).is_some_and(|c| c.is_alphanumeric());
+                if prev_is_word && next_is_word {
+                    result.push(c);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result.trim().to_string()
+}
+
 #[cfg(test)]
 mod tests_repository {
     use super::*;
@@ -154,6 +585,104 @@ mod tests_repository {
         assert_eq!(repo.language, Some("Rust".to_string()));
     }
 
+    #[test]
+    fn test_detect_language_build_files() {
+        let cases = [
+            ("Gemfile", "Ruby"),
+            ("Package.swift", "Swift"),
+            ("build.gradle.kts", "Kotlin"),
+            ("build.sbt", "Scala"),
+            ("mix.exs", "Elixir"),
+            ("build.zig", "Zig"),
+            ("Makefile.PL", "Perl"),
+            ("Util.pm", "Perl"),
+            ("pubspec.yaml", "Dart"),
+            ("App.csproj", "C#"),
+            ("App.sln", "C#"),
+            ("composer.json", "PHP"),
+            ("go.mod", "Go"),
+            ("build.gradle", "Java"),
+            ("setup.py", "Python"),
+            ("CMakeLists.txt", "C/C++"),
+        ];
+        for (file, language) in cases {
+            let temp_dir = tempdir().unwrap();
+            fs::write(temp_dir.path().join(file), "").unwrap();
+            assert_eq!(
+                detect_language(temp_dir.path()).as_deref(),
+                Some(language),
+                "{}",
+                file
+            );
+        }
+
+        // Web projects of other languages often have a package.json too
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("Gemfile"), "").unwrap();
+        assert_eq!(detect_language(temp_dir.path()).as_deref(), Some("Ruby"));
+    }
+
+    #[test]
+    fn test_detect_language_from_extension() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path();
+        assert_eq!(detect_language(path), None);
+
+        fs::write(path.join("notes.txt"), "").unwrap();
+        assert_eq!(detect_language_from_extension(path), None);
+
+        fs::write(path.join("main.py"), "").unwrap();
+        fs::write(path.join("util.rb"), "").unwrap();
+        // Tied, alphabetically first wins
+        assert_eq!(detect_language(path).as_deref(), Some("Python"));
+
+        for file in ["a.rb", "b.RB"] {
+            fs::write(path.join(file), "").unwrap();
+        }
+        // Files in subdirectories aren't counted
+        fs::create_dir(path.join("scripts")).unwrap();
+        for file in ["a.py", "b.py", "c.py"] {
+            fs::write(path.join("scripts").join(file), "").unwrap();
+        }
+        assert_eq!(detect_language(path).as_deref(), Some("Ruby"));
+
+        // A build file beats the extension count
+        fs::write(path.join("build.zig"), "").unwrap();
+        assert_eq!(detect_language(path).as_deref(), Some("Zig"));
+    }
+
+    #[test]
+    fn test_detect_ci() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path();
+        assert!(detect_ci(path).is_empty());
+
+        fs::create_dir_all(path.join(".github/workflows")).unwrap();
+        fs::write(path.join("Jenkinsfile"), "pipeline {}\n").unwrap();
+        assert_eq!(detect_ci(path), vec!["ci:github-actions", "ci:jenkins"]);
+
+        fs::write(path.join(".gitlab-ci.yml"), "").unwrap();
+        fs::write(path.join(".travis.yml"), "").unwrap();
+        fs::create_dir(path.join(".circleci")).unwrap();
+        assert_eq!(
+            detect_ci(path),
+            vec![
+                "ci:github-actions",
+                "ci:gitlab",
+                "ci:jenkins",
+                "ci:travis",
+                "ci:circleci"
+            ]
+        );
+
+        // The older CircleCI config file counts too, an empty .github does not
+        let other = tempdir().unwrap();
+        fs::write(other.path().join("circle.yml"), "").unwrap();
+        fs::create_dir(other.path().join(".github")).unwrap();
+        assert_eq!(detect_ci(other.path()), vec!["ci:circleci"]);
+    }
+
     #[test]
     fn test_detect_language_javascript() {
         let temp_dir = tempdir().unwrap();
@@ -179,6 +708,156 @@ mod tests_repository {
         assert_eq!(repo.language, Some("JavaScript/TypeScript".to_string()));
     }
 
+    #[test]
+    fn test_from_repo_access() {
+        let last_access = Utc::now();
+        let mut repo_access = RepoAccess::new(vec!["rust".to_string()]);
+        repo_access.access_times = vec![last_access - chrono::Duration::hours(2), last_access];
+        repo_access.description = "A tool".to_string();
+
+        let repo = Repository::from(("/home/user/projects/mangit", &repo_access));
+
+        assert_eq!(repo.name, "mangit");
+        assert_eq!(repo.path, "/home/user/projects/mangit");
+        assert_eq!(repo.tags, vec!["rust".to_string()]);
+        assert_eq!(repo.description, "A tool");
+        assert_eq!(repo.last_modified, last_access);
+        assert!(repo.language.is_none());
+    }
+
+    #[test]
+    fn test_repo_name_from_path() {
+        assert_eq!(repo_name_from_path("/home/user/projects/mangit"), "mangit");
+        assert_eq!(repo_name_from_path("/home/user/projects/mangit/"), "mangit");
+        assert_eq!(
+            repo_name_from_path("/home/user/projects/mangit/."),
+            "mangit"
+        );
+        assert_eq!(repo_name_from_path("/"), "/");
+    }
+
+    #[test]
+    fn test_extract_readme_description_markdown() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("README.md"),
+            "# my-tool\n\n**Fast** tool for _tagging_ `git` repos with snake_case names\n\nMore text",
+        )
+        .unwrap();
+
+        let description = extract_readme_description(temp_dir.path()).unwrap();
+        assert_eq!(
+            description,
+            Some("Fast tool for tagging git repos with snake_case names".to_string())
+        );
+    }
+
+    #[test]
+    fn test_auto_generate_description() {
+        let repo =
+            |name: &str| Repository::new(name.to_string(), String::new(), vec![], String::new());
+
+        let with_readme = tempdir().unwrap();
+        fs::write(
+            with_readme.path().join("README.md"),
+            "# tool\n\nTags git repos.\n",
+        )
+        .unwrap();
+        assert_eq!(
+            auto_generate_description(with_readme.path(), &repo("my-tool")),
+            Some("Tags git repos.".to_string())
+        );
+
+        let with_commit = tempdir().unwrap();
+        let path = with_commit.path().to_str().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args([
+                    "-C",
+                    path,
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                ])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet"]);
+        git(&[
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "Add parser for tag files",
+        ]);
+        assert_eq!(
+            auto_generate_description(with_commit.path(), &repo("parser")),
+            Some("Add parser for tag files".to_string())
+        );
+
+        let empty = tempdir().unwrap();
+        assert_eq!(
+            auto_generate_description(empty.path(), &repo("my-cool_tool")),
+            Some("My cool tool".to_string())
+        );
+        assert_eq!(auto_generate_description(empty.path(), &repo("-")), None);
+    }
+
+    #[test]
+    fn test_extract_readme_description_priority_and_rst() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("README.rst"),
+            "my-tool\n=======\n\nFrom the rst file",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("README.txt"), "From the txt file").unwrap();
+
+        let description = extract_readme_description(temp_dir.path()).unwrap();
+        assert_eq!(description, Some("From the rst file".to_string()));
+    }
+
+    #[test]
+    fn test_extract_readme_description_truncates() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("README.txt"), "a".repeat(300)).unwrap();
+
+        let description = extract_readme_description(temp_dir.path()).unwrap();
+        assert_eq!(description.unwrap().len(), 200);
+    }
+
+    #[test]
+    fn test_extract_readme_description_missing() {
+        let temp_dir = tempdir().unwrap();
+        assert_eq!(extract_readme_description(temp_dir.path()).unwrap(), None);
+
+        fs::write(temp_dir.path().join("README.md"), "# Only a heading\n\n").unwrap();
+        assert_eq!(extract_readme_description(temp_dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_tag_file() {
+        let temp_dir = tempdir().unwrap();
+        assert_eq!(read_tag_file(temp_dir.path()).unwrap(), None);
+
+        fs::write(
+            temp_dir.path().join(TAG_FILE),
+            "# tags for this repo\nrust, cli\n\ntool # the binary\ncli\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_tag_file(temp_dir.path()).unwrap(),
+            Some(vec![
+                "rust".to_string(),
+                "cli".to_string(),
+                "tool".to_string()
+            ])
+        );
+    }
+
     #[test]
     fn test_matches_query_empty() {
         // Arrange
@@ -209,6 +888,24 @@ mod tests_repository {
         assert!(repo.matches_query("TEST")); // Case insensitive
     }
 
+    #[test]
+    fn test_matches_query_path() {
+        let repo = Repository::new(
+            "repo".to_string(),
+            "/old/projects/repo".to_string(),
+            vec![],
+            String::new(),
+        );
+
+        assert!(repo.matches_query("path:/old/projects/*"));
+        assert!(repo.matches_query("path:/old/"));
+        assert!(repo.matches_query("path:*/repo"));
+        assert!(repo.matches_query("path:/old/*/re*"));
+        assert!(!repo.matches_query("path:/new/*"));
+        assert!(!repo.matches_query("path:*/other"));
+        assert!(!repo.matches_query("path:/OLD/"));
+    }
+
     #[test]
     fn test_matches_query_description() {
         // Arrange
@@ -254,60 +951,136 @@ mod tests_repository {
     }
 
     #[test]
-    fn test_matches_tags_empty() {
+    fn test_tag_requirement_empty() {
         // Arrange
-        let repo = Repository::new(
-            "test-repo".to_string(),
-            "/path/to/repo".to_string(),
-            vec!["rust".to_string(), "cli".to_string()],
-            "A test repository".to_string(),
-        );
+        let tags = vec!["rust".to_string(), "cli".to_string()];
 
         // Act & Assert
-        assert!(repo.matches_tags(&[]));
+        assert!(TagRequirement::All.is_met(&[], &tags));
+        assert!(!TagRequirement::Any.is_met(&[], &tags));
+        assert!(!TagRequirement::Exact.is_met(&[], &tags));
+        assert!(TagRequirement::Exact.is_met(&[], &[]));
     }
 
     #[test]
-    fn test_matches_tags_single() {
+    fn test_tag_requirement_single() {
         // Arrange
-        let repo = Repository::new(
-            "test-repo".to_string(),
-            "/path/to/repo".to_string(),
-            vec!["rust".to_string(), "cli".to_string()],
-            "A test repository".to_string(),
-        );
+        let tags = vec!["rust".to_string(), "cli".to_string()];
 
         // Act & Assert
-        assert!(repo.matches_tags(&["rust".to_string()]));
-        assert!(repo.matches_tags(&["cli".to_string()]));
+        assert!(TagRequirement::All.is_met(&["rust".to_string()], &tags));
+        assert!(TagRequirement::All.is_met(&["cli".to_string()], &tags));
     }
 
     #[test]
-    fn test_matches_tags_multiple() {
+    fn test_tag_requirement_multiple() {
         // Arrange
-        let repo = Repository::new(
-            "test-repo".to_string(),
-            "/path/to/repo".to_string(),
-            vec!["rust".to_string(), "cli".to_string()],
-            "A test repository".to_string(),
-        );
+        let tags = vec!["rust".to_string(), "cli".to_string()];
 
         // Act & Assert
-        assert!(repo.matches_tags(&["rust".to_string(), "cli".to_string()]));
+        assert!(TagRequirement::All.is_met(&["rust".to_string(), "cli".to_string()], &tags));
     }
 
     #[test]
-    fn test_matches_tags_not_found() {
+    fn test_tag_requirement_not_found() {
         // Arrange
-        let repo = Repository::new(
-            "test-repo".to_string(),
-            "/path/to/repo".to_string(),
-            vec!["rust".to_string(), "cli".to_string()],
-            "A test repository".to_string(),
-        );
+        let tags = vec!["rust".to_string(), "cli".to_string()];
 
         // Act & Assert
-        assert!(!repo.matches_tags(&["web".to_string()]));
-        assert!(!repo.matches_tags(&["rust".to_string(), "web".to_string()]));
+        assert!(!TagRequirement::All.is_met(&["web".to_string()], &tags));
+        assert!(!TagRequirement::All.is_met(&["rust".to_string(), "web".to_string()], &tags));
+    }
+
+    #[test]
+    fn test_tag_requirements() {
+        let tags =
+            |tags: &[&str]| -> Vec<String> { tags.iter().map(|tag| tag.to_string()).collect() };
+        let wanted = tags(&["rust", "cli"]);
+        let superset = tags(&["rust", "cli", "web"]);
+        let subset = tags(&["rust"]);
+        let exact = tags(&["CLI", "rust"]);
+
+        assert!(TagRequirement::All.is_met(&wanted, &superset));
+        assert!(!TagRequirement::All.is_met(&wanted, &subset));
+        assert!(TagRequirement::All.is_met(&wanted, &exact));
+
+        assert!(TagRequirement::Any.is_met(&wanted, &superset));
+        assert!(TagRequirement::Any.is_met(&wanted, &subset));
+        assert!(!TagRequirement::Any.is_met(&wanted, &tags(&["web"])));
+
+        assert!(!TagRequirement::Exact.is_met(&wanted, &superset));
+        assert!(!TagRequirement::Exact.is_met(&wanted, &subset));
+        assert!(TagRequirement::Exact.is_met(&wanted, &exact));
+    }
+
+    #[test]
+    fn test_tag_requirement_ignores_case() {
+        let wanted = vec!["Rust".to_string(), "cli".to_string()];
+        let tags = vec!["rust".to_string(), "CLI".to_string()];
+
+        assert!(TagRequirement::All.is_met(&wanted, &tags));
+        assert!(TagRequirement::Any.is_met(&["RUST".to_string()], &tags));
+        assert!(TagRequirement::Exact.is_met(&wanted, &tags));
+        assert!(!TagRequirement::Exact.is_met(&wanted[..1], &tags));
+    }
+
+    #[test]
+    fn test_parse_language_versions() {
+        let metadata = r#"{"packages":[{"name":"mangit","edition":"2024","rust_version":null}]}"#;
+        assert_eq!(
+            parse_cargo_metadata_version(metadata).as_deref(),
+            Some("edition 2024")
+        );
+        let metadata = r#"{"packages":[{"name":"mangit","edition":"2021","rust_version":"1.70"}]}"#;
+        assert_eq!(
+            parse_cargo_metadata_version(metadata).as_deref(),
+            Some("1.70")
+        );
+        assert_eq!(parse_cargo_metadata_version(r#"{"packages":[]}"#), None);
+
+        let package_json = r#"{"name": "app", "engines": {"node": ">=18"}}"#;
+        assert_eq!(parse_node_engine(package_json).as_deref(), Some(">=18"));
+        assert_eq!(parse_node_engine(r#"{"name": "app"}"#), None);
+
+        let setup_py = "setup(\n    name=\"app\",\n    python_requires = '>=3.8',\n)\n";
+        assert_eq!(parse_python_requires(setup_py).as_deref(), Some(">=3.8"));
+        assert_eq!(parse_python_requires("setup(name=\"app\")"), None);
+    }
+
+    #[test]
+    fn test_score_breakdown() {
+        let matcher = SkimMatcherV2::default();
+        let mut repo = Repository::new(
+            "rust-analyzer".to_string(),
+            "/src/rust-analyzer".to_string(),
+            vec!["rust".to_string(), "lsp".to_string()],
+            "A Rust compiler front-end for IDEs".to_string(),
+        );
+        repo.language = Some("Rust".to_string());
+
+        let breakdown = score_breakdown(&repo, "rust,lsp", &matcher);
+        assert!(breakdown.name_score > 0);
+        assert!(breakdown.desc_score > 0);
+        assert_eq!(breakdown.tag_score, 2);
+        assert_eq!(breakdown.lang_score, 1);
+        assert_eq!(
+            breakdown.total,
+            breakdown.name_score
+                + breakdown.tag_score
+                + breakdown.desc_score
+                + breakdown.lang_score
+        );
+
+        let breakdown = score_breakdown(&repo, "python", &matcher);
+        assert_eq!(breakdown, ScoreBreakdown::default());
+
+        let breakdown = ScoreBreakdown {
+            name_score: 45,
+            tag_score: 2,
+            desc_score: 0,
+            lang_score: 1,
+            total: 48,
+        };
+        assert_eq!(breakdown.to_string(), "[name:45 tags:2 desc:0 lang:1 = 48]");
     }
 }
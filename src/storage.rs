@@ -1,45 +1,219 @@
+pub mod saved_searches;
+pub mod vocab;
+
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration as StdDuration;
+
+use clap::ValueEnum;
+
+use crate::config::{Config, DEFAULT_MAX_ACCESS_HISTORY, FrecencyConfig, is_git_repo};
+use crate::repository::{TagRequirement, detect_language, read_tag_file, repo_name_from_path};
+use crate::util::ci::CiStatus;
+use crate::util::expr::{SortExpr, SortVars};
+use crate::util::git;
+use crate::util::{is_within_depth, normalize_path, scan_git_repos};
+use uuid::Uuid;
+use vocab::TagVocabulary;
+
+/// Default window of the "active projects" view
+pub const DEFAULT_ACTIVE_DAYS: u64 = 30;
+// Storage is defragmented after a delete leaves it below this share of its capacity.
+// A map that was never shrunk sits well above it, so only mass deletes trigger it
+const DEFRAGMENT_FILL_RATIO: f64 = 0.25;
+// Smaller maps aren't worth the work of defragmenting after a delete
+const DEFRAGMENT_MIN_CAPACITY: usize = 64;
+// Minutes a fetched CI status is shown before it is fetched again
+const CI_STATUS_TTL_MINUTES: i64 = 5;
+// A temporary repos file this old belongs to a save that was interrupted
+const STALE_TMP_FILE_MINUTES: u64 = 10;
+
+/// How important a repo is, set with `add --priority`
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    /// Factor applied to frecency scores when `frecency_weight_by_priority` is on
+    pub fn frecency_weight(&self) -> f64 {
+        match self {
+            Priority::High => 2.0,
+            Priority::Medium => 1.0,
+            Priority::Low => 0.5,
+        }
+    }
 
-use crate::config::Config;
+    /// Marker shown in list output
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            Priority::High => "!!!",
+            Priority::Medium => "!!",
+            Priority::Low => "!",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RepoAccess {
     pub tags: Vec<String>,
     pub access_times: Vec<DateTime<Utc>>,
+    // Date of the first commit, fetched on demand with `update --fetch-first-commit`
+    #[serde(default)]
+    pub first_commit_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub description: String,
+    // When git was last asked for the latest commit, and the answer
+    #[serde(default)]
+    pub last_commit_check: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_commit_date: Option<DateTime<Utc>>,
+    // Cached language, refreshed on access when `auto_update_language` is on
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub last_language_check: Option<DateTime<Utc>>,
+    // Short stable identifier, assigned when the repo is added
+    #[serde(default)]
+    pub repo_id: String,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    // Display name chosen by the user, the directory name is used when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    // URL of the `origin` remote, recorded when the repo is added
+    #[serde(default)]
+    pub remote_url: Option<String>,
+    // Checked out branch, recorded by `update --refresh-all`
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    // Ticket or issue the repo belongs to, set with `add --link-to-project-management`
+    #[serde(default)]
+    pub project_url: Option<String>,
+    // Latest CI result from `info --show-ci-status`, and when it was fetched
+    #[serde(default)]
+    pub ci_status: Option<CiStatus>,
+    #[serde(default)]
+    pub last_ci_check: Option<DateTime<Utc>>,
+    // Archived repos are left out of searches unless `--include-archived` is given
+    #[serde(default)]
+    pub archived: bool,
+    // Free-text note kept apart from the description, see `mangit note`
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 impl RepoAccess {
-    fn new(tags: Vec<String>) -> Self {
+    pub fn new(tags: Vec<String>) -> Self {
         RepoAccess {
             tags,
             access_times: vec![Utc::now()],
+            first_commit_date: None,
+            description: String::new(),
+            last_commit_check: None,
+            last_commit_date: None,
+            language: None,
+            last_language_check: None,
+            repo_id: String::new(),
+            priority: None,
+            name: None,
+            remote_url: None,
+            git_branch: None,
+            project_url: None,
+            ci_status: None,
+            last_ci_check: None,
+            archived: false,
+            note: None,
         }
     }
 
-    fn record_access(&mut self) {
+    /// Adds an access now, keeping at most `max_history` of the latest access times
+    fn record_access(&mut self, max_history: usize) {
         self.access_times.push(Utc::now());
-        // Keep only the last 10 access times to avoid unbounded growth
-        if self.access_times.len() > 10 {
-            self.access_times = self.access_times.split_off(self.access_times.len() - 10);
+        // Keep only the last access times to avoid unbounded growth
+        if self.access_times.len() > max_history {
+            self.access_times = self
+                .access_times
+                .split_off(self.access_times.len() - max_history);
         }
     }
 
-    fn update_tags(&mut self, tags: Vec<String>) {
+    fn update_tags(&mut self, tags: Vec<String>, max_history: usize) {
         self.tags = tags;
-        self.record_access();
+        self.record_access(max_history);
     }
 
     fn reset_frequency(&mut self) {
         self.access_times = vec![Utc::now()];
     }
 
-    fn calculate_frecency(&self) -> f64 {
+    /// Re-detects the language of the repo at `path` unless it was checked within
+    /// `interval`. Returns true if the detection ran
+    fn refresh_language(&mut self, path: &Path, interval: Duration) -> bool {
+        let now = Utc::now();
+        if self
+            .last_language_check
+            .is_some_and(|checked| now.signed_duration_since(checked) < interval)
+        {
+            return false;
+        }
+
+        self.language = detect_language(path);
+        self.last_language_check = Some(now);
+        true
+    }
+
+    /// Re-reads everything mangit caches about the repo at `path`: language,
+    /// remote URL, branch, last commit date and the tags in its `.mangit-tags` file.
+    /// A known language is kept when detection finds none
+    pub fn full_refresh(&mut self, path: &str) -> Result<()> {
+        if !is_git_repo(Path::new(path)) {
+            return Err(anyhow!("Not a git repo: {}", path));
+        }
+
+        let now = Utc::now();
+        // git log fails in a repo without commits
+        self.last_commit_date = git::last_commit_date(path).ok().flatten();
+        self.last_commit_check = Some(now);
+        self.remote_url = git::remote_origin_url(path);
+        self.git_branch = git::current_branch(path);
+        if let Some(language) = detect_language(Path::new(path)) {
+            self.language = Some(language);
+        }
+        self.last_language_check = Some(now);
+
+        if let Some(file_tags) = read_tag_file(Path::new(path))? {
+            for tag in file_tags {
+                if !self.tags.contains(&tag) {
+                    self.tags.push(tag);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored CI status, unless it is too old to be trusted
+    pub fn cached_ci_status(&self) -> Option<CiStatus> {
+        let checked = self.last_ci_check?;
+        if Utc::now().signed_duration_since(checked) >= Duration::minutes(CI_STATUS_TTL_MINUTES) {
+            return None;
+        }
+        self.ci_status
+    }
+
+    pub fn calculate_frecency(&self, weights: &FrecencyConfig) -> f64 {
         let now = Utc::now();
         let mut score = 0.0;
 
@@ -48,17 +222,17 @@ impl RepoAccess {
 
             // Weight based on recency
             let weight = if age < Duration::minutes(1) {
-                100.0 // Within last minute
+                weights.weight_minute
             } else if age < Duration::minutes(30) {
-                80.0 // Within last 30 minutes
+                weights.weight_half_hour
             } else if age < Duration::hours(1) {
-                60.0 // Within last hour
+                weights.weight_hour
             } else if age < Duration::hours(24) {
-                40.0 // Within last day
+                weights.weight_day
             } else if age < Duration::hours(24 * 7) {
-                20.0 // Within last week
+                weights.weight_week
             } else {
-                10.0 // Older than a week
+                weights.weight_older
             };
 
             score += weight;
@@ -66,56 +240,615 @@ impl RepoAccess {
 
         score
     }
+
+    /// Folds another entry for the same repo into this one, keeping the tags and
+    /// the latest `max_history` access times of both. Every other field keeps this
+    /// entry's value when it is set and takes the other one's otherwise
+    fn merge(&mut self, other: RepoAccess, max_history: usize) {
+        // Destructured so a new field can't be forgotten here
+        let RepoAccess {
+            tags,
+            access_times,
+            first_commit_date,
+            description,
+            last_commit_check,
+            last_commit_date,
+            language,
+            last_language_check,
+            repo_id,
+            priority,
+            name,
+            remote_url,
+            git_branch,
+            project_url,
+            ci_status,
+            last_ci_check,
+            archived,
+            note,
+        } = other;
+
+        for tag in tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+
+        self.access_times.extend(access_times);
+        self.access_times.sort();
+        if self.access_times.len() > max_history {
+            self.access_times
+                .drain(..self.access_times.len() - max_history);
+        }
+
+        if self.description.is_empty() {
+            self.description = description;
+        }
+        if self.repo_id.is_empty() {
+            self.repo_id = repo_id;
+        }
+        self.archived |= archived;
+        self.first_commit_date = self.first_commit_date.or(first_commit_date);
+        self.last_commit_check = self.last_commit_check.or(last_commit_check);
+        self.last_commit_date = self.last_commit_date.or(last_commit_date);
+        self.language = self.language.take().or(language);
+        self.last_language_check = self.last_language_check.or(last_language_check);
+        self.priority = self.priority.or(priority);
+        self.name = self.name.take().or(name);
+        self.remote_url = self.remote_url.take().or(remote_url);
+        self.git_branch = self.git_branch.take().or(git_branch);
+        self.project_url = self.project_url.take().or(project_url);
+        self.ci_status = self.ci_status.take().or(ci_status);
+        self.last_ci_check = self.last_ci_check.or(last_ci_check);
+        self.note = self.note.take().or(note);
+    }
+
+    /// The repo's display name: the chosen name, or else the last component of its path
+    pub fn display_name(&self, path: &str) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| repo_name_from_path(path))
+    }
+
+    /// Frecency multiplied by the repo's priority weight, unset counts as medium
+    pub fn priority_weighted_frecency(&self, weights: &FrecencyConfig) -> f64 {
+        self.calculate_frecency(weights) * self.priority.map_or(1.0, |p| p.frecency_weight())
+    }
+}
+
+/// Whether repos need a known language to be included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LanguageFilter {
+    #[default]
+    Any,
+    /// Only repos with a detected language
+    Known,
+    /// Only repos without a detected language
+    Unknown,
+}
+
+impl LanguageFilter {
+    /// Checks a repo against the filter, detecting its language if none is cached
+    pub fn matches(&self, path: &str, repo_access: &RepoAccess) -> bool {
+        if *self == LanguageFilter::Any {
+            return true;
+        }
+
+        let has_language =
+            repo_access.language.is_some() || detect_language(Path::new(path)).is_some();
+        has_language == (*self == LanguageFilter::Known)
+    }
+}
+
+/// Whether archived repos are included in a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArchiveFilter {
+    #[default]
+    Exclude,
+    Include,
+    /// Only archived repos
+    Only,
+}
+
+impl ArchiveFilter {
+    pub fn matches(&self, archived: bool) -> bool {
+        match self {
+            ArchiveFilter::Exclude => !archived,
+            ArchiveFilter::Include => true,
+            ArchiveFilter::Only => archived,
+        }
+    }
+}
+
+/// Which repos to keep by their `origin` remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteUrlFilter {
+    /// Only repos with a remote URL
+    Present,
+    /// Only local-only repos
+    Missing,
+    /// Only repos whose remote URL contains the text, ignoring case
+    Matching(String),
+}
+
+impl RemoteUrlFilter {
+    pub fn matches(&self, remote_url: Option<&str>) -> bool {
+        match (self, remote_url) {
+            (RemoteUrlFilter::Present, url) => url.is_some(),
+            (RemoteUrlFilter::Missing, url) => url.is_none(),
+            (RemoteUrlFilter::Matching(text), Some(url)) => {
+                url.to_lowercase().contains(&text.to_lowercase())
+            }
+            (RemoteUrlFilter::Matching(_), None) => false,
+        }
+    }
+}
+
+/// Bounds on how long ago a repo was last accessed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AccessWindow {
+    // Accessed at most this long ago
+    pub since: Option<Duration>,
+    // Not accessed for at least this long, never accessed repos included
+    pub before: Option<Duration>,
+}
+
+impl AccessWindow {
+    pub fn contains(&self, repo_access: &RepoAccess, now: DateTime<Utc>) -> bool {
+        let last_access = repo_access.access_times.iter().max();
+        self.since
+            .is_none_or(|since| last_access.is_some_and(|time| now - *time <= since))
+            && self
+                .before
+                .is_none_or(|before| last_access.is_none_or(|time| now - *time >= before))
+    }
+}
+
+/// A tag search: repos matching `include` (combined with `requirement`)
+/// that have none of the `exclude` tags, optionally limited to a frecency range
+#[derive(Debug, Clone, Default)]
+pub struct SearchExpr {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub requirement: TagRequirement,
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub language: LanguageFilter,
+    // Only repos at most this many levels below the directory
+    pub within: Option<(PathBuf, usize)>,
+    pub archived: ArchiveFilter,
+    pub accessed: AccessWindow,
+    // Only repos with these paths, e.g. the siblings of a repo
+    pub paths: Option<HashSet<String>>,
+}
+
+impl SearchExpr {
+    /// Checks a repo's tags against the expression, ignoring case
+    pub fn matches(&self, tags: &[String]) -> bool {
+        let has_tag = |search_tag: &String| {
+            let search_tag = search_tag.to_lowercase();
+            tags.iter().any(|t| t.to_lowercase() == search_tag)
+        };
+
+        self.requirement.is_met(&self.include, tags) && !self.exclude.iter().any(has_tag)
+    }
+
+    /// Checks a repo path against the expression's directory limit and path set
+    pub fn path_in_range(&self, path: &str) -> bool {
+        self.within
+            .as_ref()
+            .is_none_or(|(base, depth)| is_within_depth(base, Path::new(path), *depth))
+            && self.paths.as_ref().is_none_or(|paths| paths.contains(path))
+    }
+
+    /// Checks a frecency score against the expression's score range
+    pub fn score_in_range(&self, score: f64) -> bool {
+        self.min_score.is_none_or(|min| score >= min)
+            && self.max_score.is_none_or(|max| score <= max)
+    }
+}
+
+/// How recently a repo has been used, see `classify_activity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ActivityTier {
+    /// Accessed within the past day
+    Daily,
+    /// Accessed within the past week
+    Weekly,
+    /// Accessed within the past month
+    Monthly,
+    /// Accessed less recently
+    Rare,
+    /// Never accessed since it was added
+    Never,
+}
+
+impl ActivityTier {
+    pub const ALL: [ActivityTier; 5] = [
+        ActivityTier::Daily,
+        ActivityTier::Weekly,
+        ActivityTier::Monthly,
+        ActivityTier::Rare,
+        ActivityTier::Never,
+    ];
+}
+
+/// Sorts a repo into an activity tier by its latest access
+pub fn classify_activity(access_times: &[DateTime<Utc>]) -> ActivityTier {
+    classify_activity_at(access_times, Utc::now())
+}
+
+fn classify_activity_at(access_times: &[DateTime<Utc>], now: DateTime<Utc>) -> ActivityTier {
+    // The only access is the one recorded when the repo was added
+    if access_times.len() <= 1 {
+        return ActivityTier::Never;
+    }
+
+    let Some(last_access) = access_times.iter().max() else {
+        return ActivityTier::Never;
+    };
+    let age = now.signed_duration_since(*last_access);
+
+    if age <= Duration::days(1) {
+        ActivityTier::Daily
+    } else if age <= Duration::weeks(1) {
+        ActivityTier::Weekly
+    } else if age <= Duration::days(30) {
+        ActivityTier::Monthly
+    } else {
+        ActivityTier::Rare
+    }
+}
+
+/// Repos tracked under different paths that share a display name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameConflict {
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+/// Outcome of `Storage::canonicalize_paths`
+#[derive(Debug, Default)]
+pub struct PathNormalizeResult {
+    // Old and new path of every repo whose path changed
+    pub changed: Vec<(String, String)>,
+    pub unchanged: usize,
+    // Repos whose path could not be resolved
+    pub failed: Vec<String>,
+}
+
+/// Outcome of `Storage::add_repo`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddResult {
+    pub name: String,
+    pub path: String,
+    pub id: String,
+    // False when the repo was already tracked and only its tags were updated
+    pub is_new: bool,
+}
+
+/// Outcome of `Storage::apply_tag_files`
+#[derive(Debug, Default)]
+pub struct TagFileReport {
+    pub updated: usize,
+    // Repos without a tag file
+    pub skipped: usize,
+    pub errors: Vec<(String, anyhow::Error)>,
+}
+
+/// Outcome of `Storage::scan_repos`
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    pub added: Vec<String>,
+    pub already_tracked: Vec<String>,
+    // Directories looked at that aren't git repos
+    pub not_git: usize,
+}
+
+/// Problems `Storage::validate` found, each list sorted
+#[derive(Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    // Repos whose path no longer exists
+    pub missing: Vec<String>,
+    // Existing paths without a `.git` directory
+    pub not_git: Vec<String>,
+    // Pairs of stored paths that differ only by trailing slashes
+    pub duplicates: Vec<(String, String)>,
+}
+
+impl ValidationReport {
+    pub fn problem_count(&self) -> usize {
+        self.missing.len() + self.not_git.len() + self.duplicates.len()
+    }
+}
+
+/// Outcome of `Storage::delete_tag`
+#[derive(Debug, Default, PartialEq)]
+pub struct TagDeleteReport {
+    // Repos the tag was removed from
+    pub updated: usize,
+    // Repos left without any tags, sorted. Removed from storage with `remove_empty`
+    pub untagged: Vec<String>,
+}
+
+/// What `Storage::defragment` cleaned up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DeFragResult {
+    // Approximate heap memory released
+    pub bytes_freed: usize,
+    pub access_times_trimmed: usize,
+}
+
+/// Handle to the background cleanup thread started by `Storage::start_cleanup_task`
+pub struct CleanupTask {
+    shutdown: mpsc::Sender<()>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl CleanupTask {
+    /// Signals the task to stop and waits for it to finish
+    pub fn stop(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.handle.join();
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Storage {
     // Map of absolute repo paths to their access information
     pub repos: HashMap<String, RepoAccess>,
+    // How often to re-detect languages on access, None when `auto_update_language` is off
+    #[serde(skip)]
+    language_check_interval: Option<Duration>,
+    #[serde(skip)]
+    weight_by_priority: bool,
+    #[serde(skip)]
+    frecency_weights: FrecencyConfig,
+    // Access times kept per repo, from `max_access_history`
+    #[serde(skip, default = "default_max_access_history")]
+    max_access_history: usize,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage {
+            repos: HashMap::new(),
+            language_check_interval: None,
+            weight_by_priority: false,
+            frecency_weights: FrecencyConfig::default(),
+            max_access_history: DEFAULT_MAX_ACCESS_HISTORY,
+        }
+    }
+}
+
+fn default_max_access_history() -> usize {
+    DEFAULT_MAX_ACCESS_HISTORY
+}
+
+/// 32-bit FNV-1a, a hash that stays the same across builds unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Removes temporary repos files left behind by saves that were interrupted
+/// before the rename. Recent ones may belong to a save that is still running in
+/// another process, so only stale files are removed
+fn remove_stale_tmp_files(config: &Config) {
+    let Ok(entries) = fs::read_dir(config.mangit_dir_path()) else {
+        return;
+    };
+    let max_age = StdDuration::from_secs(STALE_TMP_FILE_MINUTES * 60);
+    for entry in entries.flatten() {
+        if !Config::is_repos_tmp_file(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= max_age);
+        if stale {
+            // Another process may be removing it at the same time
+            let _ = fs::remove_file(entry.path());
+        }
+    }
 }
 
 impl Storage {
     /// Creates a new Storage instance, loading data from disk if available
     pub fn new(config: &Config) -> Result<Self> {
+        let mut storage = Self::load_all(config)?;
+        // Return a cleaned up storage (removing non-existent paths)
+        storage.cleanup();
+        Ok(storage)
+    }
+
+    /// Loads storage from disk, keeping repos whose paths no longer exist
+    /// (e.g. on a new machine before they are cloned)
+    pub fn load_all(config: &Config) -> Result<Self> {
         config.ensure_mangit_dir()?;
 
+        remove_stale_tmp_files(config);
+
         let repos_path = config.repos_path();
-        if repos_path.exists() {
+        let mut storage = if repos_path.exists() {
             let data = fs::read_to_string(&repos_path).context("Failed to read repos file")?;
-            let storage: Storage =
-                serde_json::from_str(&data).context("Failed to parse repos file")?;
-
-            // Return a cleaned up storage (removing non-existent paths)
-            let mut storage = storage;
-            storage.cleanup();
-            Ok(storage)
+            serde_json::from_str(&data).context("Failed to parse repos file")?
         } else {
-            Ok(Storage::default())
+            Storage::default()
+        };
+
+        storage.apply_config(config);
+        // Loading never writes, the IDs come from the paths so they stay the same
+        // until the next save stores them
+        storage.assign_missing_ids();
+        Ok(storage)
+    }
+
+    fn apply_config(&mut self, config: &Config) {
+        self.language_check_interval = config
+            .auto_update_language
+            .then(|| Duration::hours(config.language_check_interval_hours as i64));
+        self.weight_by_priority = config.frecency_weight_by_priority;
+        self.frecency_weights = config.frecency.clone();
+        // A history of 0 would forget every access right away
+        self.max_access_history = config.max_access_history.max(1);
+    }
+
+    /// Frecency score of a repo, weighted by priority if the config asks for it
+    pub fn frecency(&self, repo_access: &RepoAccess) -> f64 {
+        frecency_score(repo_access, &self.frecency_weights, self.weight_by_priority)
+    }
+
+    /// Frecency weights from the config
+    pub fn frecency_weights(&self) -> &FrecencyConfig {
+        &self.frecency_weights
+    }
+
+    /// Gives repos tracked before IDs existed an ID derived from their path.
+    /// Returns true if any were missing
+    fn assign_missing_ids(&mut self) -> bool {
+        let mut missing: Vec<String> = self
+            .repos
+            .iter()
+            .filter(|(_, repo_access)| repo_access.repo_id.is_empty())
+            .map(|(path, _)| path.clone())
+            .collect();
+        missing.sort();
+
+        for path in &missing {
+            let repo_id = self.path_repo_id(path);
+            if let Some(repo_access) = self.repos.get_mut(path) {
+                repo_access.repo_id = repo_id;
+            }
+        }
+        !missing.is_empty()
+    }
+
+    /// An ID that no tracked repo uses yet, the same for `path` on every load
+    fn path_repo_id(&self, path: &str) -> String {
+        (0u32..)
+            .map(|attempt| format!("{:08x}", fnv1a(format!("{}:{}", path, attempt).as_bytes())))
+            .find(|repo_id| self.find_by_id(repo_id).is_none())
+            .expect("an unused repo ID")
+    }
+
+    /// Generates a repo ID that no tracked repo uses yet
+    fn new_repo_id(&self) -> String {
+        loop {
+            let repo_id = Uuid::new_v4().simple().to_string()[..8].to_string();
+            if self.find_by_id(&repo_id).is_none() {
+                return repo_id;
+            }
+        }
+    }
+
+    /// Finds a tracked repo by its ID
+    pub fn find_by_id(&self, id: &str) -> Option<(&str, &RepoAccess)> {
+        self.repos
+            .iter()
+            .find(|(_, repo_access)| repo_access.repo_id == id)
+            .map(|(path, repo_access)| (path.as_str(), repo_access))
+    }
+
+    /// Resolves a repo path or ID to the absolute path the repo is stored under.
+    /// Anything that isn't a tracked path or a known ID is treated as a path
+    pub fn resolve_path(&self, path_or_id: &str) -> Result<String> {
+        let abs_path = Self::to_absolute_path(path_or_id)?;
+        if self.repos.contains_key(&abs_path) {
+            return Ok(abs_path);
+        }
+
+        Ok(self
+            .find_by_id(path_or_id)
+            .map(|(path, _)| path.to_string())
+            .unwrap_or(abs_path))
+    }
+
+    /// Resolves a tracked repo by path, ID or name. Fails when nothing matches or
+    /// several repos have the name
+    pub fn resolve_repo(&self, query: &str) -> Result<String> {
+        let path = self.resolve_path(query)?;
+        if self.repos.contains_key(&path) {
+            return Ok(path);
+        }
+
+        let mut named: Vec<&String> = self
+            .repos
+            .iter()
+            .filter(|(path, repo_access)| repo_access.display_name(path) == query)
+            .map(|(path, _)| path)
+            .collect();
+        named.sort();
+        match named.as_slice() {
+            [] => Err(anyhow!("Repo not found: {}", query)),
+            [path] => Ok(path.to_string()),
+            paths => Err(anyhow!(
+                "Several repos are named {}: {}",
+                query,
+                paths
+                    .iter()
+                    .map(|path| path.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
         }
     }
 
-    /// Saves the current storage state to disk
+    /// Saves the current storage state to disk. The data is written to a
+    /// temporary file that then replaces the repos file, so a crash mid-write
+    /// can't leave a truncated repos file behind
     pub fn save(&self, config: &Config) -> Result<()> {
-        let repos_path = config.repos_path();
         let json = serde_json::to_string_pretty(self).context("Failed to serialize storage")?;
-        fs::write(&repos_path, json).context("Failed to write repos file")?;
+        let tmp_path = config.repos_tmp_path();
+        let mut file = fs::File::create_new(&tmp_path).context("Failed to create repos file")?;
+        let written = file
+            .write_all(json.as_bytes())
+            .and_then(|()| file.sync_all())
+            .context("Failed to write repos file")
+            .and_then(|()| {
+                fs::rename(&tmp_path, config.repos_path()).context("Failed to replace repos file")
+            });
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        written?;
+
+        // The rename itself only survives a crash once the directory is synced
+        #[cfg(unix)]
+        fs::File::open(config.mangit_dir_path())
+            .and_then(|dir| dir.sync_all())
+            .context("Failed to sync the mangit directory")?;
         Ok(())
     }
 
-    /// Converts a path to an absolute path
-    fn to_absolute_path(path: &str) -> Result<String> {
+    /// Creates a fresh storage tracking the given repos, for when the repos file
+    /// is lost. Every repo starts out untagged with a single access
+    pub fn rebuild_from_paths(paths: &[&str]) -> Result<Self> {
+        let mut storage = Storage::default();
+        for path in paths {
+            storage.add_repo(path, Vec::new())?;
+        }
+        Ok(storage)
+    }
+
+    /// Converts a path to an absolute path without `.` and `..` components, so
+    /// `scan .` and `add api` agree on the path of a repo
+    pub fn to_absolute_path(path: &str) -> Result<String> {
         let path_buf = PathBuf::from(path);
-        if path_buf.is_absolute() {
-            Ok(path_buf.to_string_lossy().to_string())
+        let abs_path = if path_buf.is_absolute() {
+            path_buf
         } else {
             let current_dir = env::current_dir().context("Failed to get current directory")?;
-            let abs_path = current_dir.join(path_buf);
-            Ok(abs_path.to_string_lossy().to_string())
-        }
+            current_dir.join(path_buf)
+        };
+        Ok(normalize_path(&abs_path).to_string_lossy().to_string())
     }
 
-    /// Adds a repo with tags. Returns true if it's a new repo, false if updated
-    pub fn add_repo(&mut self, path: &str, tags: Vec<String>) -> Result<bool> {
+    /// Adds a repo with tags, or replaces the tags of an already tracked repo
+    pub fn add_repo(&mut self, path: &str, tags: Vec<String>) -> Result<AddResult> {
         let abs_path = Self::to_absolute_path(path)?;
 
         // Check if path exists
@@ -125,28 +858,148 @@ impl Storage {
 
         let is_new = !self.repos.contains_key(&abs_path);
         if is_new {
-            self.repos.insert(abs_path, RepoAccess::new(tags));
+            let mut repo_access = RepoAccess::new(tags);
+            repo_access.repo_id = self.new_repo_id();
+            self.repos.insert(abs_path.clone(), repo_access);
         } else {
             if let Some(repo_access) = self.repos.get_mut(&abs_path) {
-                repo_access.update_tags(tags);
+                repo_access.update_tags(tags, self.max_access_history);
             }
         }
 
-        Ok(is_new)
+        let repo_access = &self.repos[&abs_path];
+        Ok(AddResult {
+            name: repo_access.display_name(&abs_path),
+            id: repo_access.repo_id.clone(),
+            path: abs_path,
+            is_new,
+        })
+    }
+
+    /// Fails if a repo other than `abs_path` is already shown as `name`
+    pub fn check_duplicate_name(&self, name: &str, abs_path: &str) -> Result<()> {
+        let existing = self
+            .repos
+            .iter()
+            .filter(|(path, repo_access)| {
+                path.as_str() != abs_path && repo_access.display_name(path) == name
+            })
+            .map(|(path, _)| path)
+            .min();
+
+        match existing {
+            Some(existing_path) => Err(anyhow!(
+                "A repo named '{}' already exists at {}, not adding {}",
+                name,
+                existing_path,
+                abs_path
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the access information of a tracked repo
+    pub fn get_repo(&self, path: &str) -> Result<Option<&RepoAccess>> {
+        let abs_path = self.resolve_path(path)?;
+        Ok(self.repos.get(&abs_path))
+    }
+
+    /// Returns the access information of a tracked repo for modification
+    pub fn get_repo_mut(&mut self, path: &str) -> Result<Option<&mut RepoAccess>> {
+        let abs_path = self.resolve_path(path)?;
+        Ok(self.repos.get_mut(&abs_path))
+    }
+
+    /// Sets or, with None, clears the note of a tracked repo
+    pub fn set_note(&mut self, path: &str, note: Option<String>) -> Result<()> {
+        let repo_access = self
+            .get_repo_mut(path)?
+            .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+        repo_access.note = note;
+        Ok(())
     }
 
     /// Deletes a repo from storage. Returns true if found and deleted
     pub fn delete_repo(&mut self, path: &str) -> Result<bool> {
-        let abs_path = Self::to_absolute_path(path)?;
-        Ok(self.repos.remove(&abs_path).is_some())
+        let abs_path = self.resolve_path(path)?;
+        let deleted = self.repos.remove(&abs_path).is_some();
+        if deleted {
+            self.defragment_if_sparse();
+        }
+        Ok(deleted)
+    }
+
+    /// Archives every repo whose most recent access is more than `max_age` ago.
+    /// Repos that are already archived or were never accessed are left alone.
+    /// Returns the newly archived paths, sorted
+    pub fn archive_by_age(&mut self, max_age: Duration) -> Result<Vec<String>> {
+        let cutoff = Utc::now()
+            .checked_sub_signed(max_age)
+            .ok_or_else(|| anyhow!("Duration is too long"))?;
+
+        let mut archived = Vec::new();
+        for (path, repo_access) in self.repos.iter_mut() {
+            let is_stale = repo_access
+                .access_times
+                .iter()
+                .max()
+                .is_some_and(|last_access| *last_access < cutoff);
+            if is_stale && !repo_access.archived {
+                repo_access.archived = true;
+                archived.push(path.clone());
+            }
+        }
+
+        archived.sort();
+        Ok(archived)
+    }
+
+    /// Returns the other repos at most `depth` levels below the directory `depth`
+    /// levels above `path`. With depth 1 these are the repos in the same parent
+    /// directory. Sorted
+    pub fn find_siblings(&self, path: &str, depth: usize) -> Result<Vec<String>> {
+        let abs_path = self.resolve_path(path)?;
+        let Some(base) = Path::new(&abs_path).ancestors().nth(depth) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(self.find_matching(|path, _| {
+            let path = Path::new(path);
+            path != Path::new(&abs_path) && path != base && is_within_depth(base, path, depth)
+        }))
+    }
+
+    /// Returns the paths of all repos the predicate accepts, sorted
+    pub fn find_matching(&self, predicate: impl Fn(&str, &RepoAccess) -> bool) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .repos
+            .iter()
+            .filter(|(path, repo_access)| predicate(path, repo_access))
+            .map(|(path, _)| path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Removes all repos the predicate accepts and returns their paths
+    pub fn remove_matching(
+        &mut self,
+        predicate: impl Fn(&str, &RepoAccess) -> bool,
+    ) -> Result<Vec<String>> {
+        let removed = self.find_matching(predicate);
+        for path in &removed {
+            self.repos.remove(path);
+        }
+        self.defragment_if_sparse();
+        Ok(removed)
     }
 
     /// Updates a repo's tags. Returns true if found and updated
     pub fn update_repo(&mut self, path: &str, tags: Vec<String>) -> Result<bool> {
-        let abs_path = Self::to_absolute_path(path)?;
+        let abs_path = self.resolve_path(path)?;
 
         if let Some(repo_access) = self.repos.get_mut(&abs_path) {
-            repo_access.update_tags(tags);
+            repo_access.update_tags(tags, self.max_access_history);
             Ok(true)
         } else {
             Ok(false)
@@ -155,10 +1008,13 @@ impl Storage {
 
     /// Records an access to a repo. Returns true if found
     pub fn record_access(&mut self, path: &str) -> Result<bool> {
-        let abs_path = Self::to_absolute_path(path)?;
+        let abs_path = self.resolve_path(path)?;
 
         if let Some(repo_access) = self.repos.get_mut(&abs_path) {
-            repo_access.record_access();
+            repo_access.record_access(self.max_access_history);
+            if let Some(interval) = self.language_check_interval {
+                repo_access.refresh_language(Path::new(&abs_path), interval);
+            }
             Ok(true)
         } else {
             Ok(false)
@@ -169,7 +1025,7 @@ impl Storage {
     pub fn reset_frequency(&mut self, path: Option<&str>) -> Result<usize> {
         match path {
             Some(path) => {
-                let abs_path = Self::to_absolute_path(path)?;
+                let abs_path = self.resolve_path(path)?;
                 if let Some(repo_access) = self.repos.get_mut(&abs_path) {
                     repo_access.reset_frequency();
                     Ok(1)
@@ -183,83 +1039,739 @@ impl Storage {
                 for (_, repo_access) in self.repos.iter_mut() {
                     repo_access.reset_frequency();
                 }
+                self.defragment();
                 Ok(count)
             }
         }
     }
 
-    /// Searches for repos by tags, returns paths sorted by frecency
-    pub fn search_by_tags(&mut self, tags: &[String]) -> Vec<String> {
-        if tags.is_empty() {
-            return Vec::new();
+    /// Clears the tags of a specific repo or all repos if path is None, leaving
+    /// their access history alone
+    pub fn reset_tags(&mut self, path: Option<&str>) -> Result<usize> {
+        self.replace_tags(path, Vec::new())
+    }
+
+    /// Replaces the tags of a specific repo or all repos if path is None, without
+    /// counting it as an access
+    pub fn replace_tags(&mut self, path: Option<&str>, tags: Vec<String>) -> Result<usize> {
+        match path {
+            Some(path) => {
+                let abs_path = self.resolve_path(path)?;
+                match self.repos.get_mut(&abs_path) {
+                    Some(repo_access) => {
+                        repo_access.tags = tags;
+                        Ok(1)
+                    }
+                    None => Ok(0),
+                }
+            }
+            None => {
+                for repo_access in self.repos.values_mut() {
+                    repo_access.tags = tags.clone();
+                }
+                Ok(self.repos.len())
+            }
+        }
+    }
+
+    /// Releases excess map capacity and tidies every repo's access history:
+    /// access times are sorted oldest first and capped at `max_access_history`
+    pub fn defragment(&mut self) -> DeFragResult {
+        let entry_size = std::mem::size_of::<(String, RepoAccess)>();
+        let time_size = std::mem::size_of::<DateTime<Utc>>();
+        let mut result = DeFragResult::default();
+
+        let capacity = self.repos.capacity();
+        self.repos.shrink_to_fit();
+        result.bytes_freed += (capacity - self.repos.capacity()) * entry_size;
+
+        for repo_access in self.repos.values_mut() {
+            let times = &mut repo_access.access_times;
+            times.sort();
+            if times.len() > self.max_access_history {
+                let trimmed = times.len() - self.max_access_history;
+                times.drain(..trimmed);
+                result.access_times_trimmed += trimmed;
+            }
+
+            let capacity = times.capacity();
+            times.shrink_to_fit();
+            result.bytes_freed += (capacity - times.capacity()) * time_size;
+        }
+
+        result
+    }
+
+    /// Defragments once deletes have left most of a large map's capacity unused
+    fn defragment_if_sparse(&mut self) -> bool {
+        let capacity = self.repos.capacity();
+        let sparse = capacity >= DEFRAGMENT_MIN_CAPACITY
+            && (self.repos.len() as f64) < capacity as f64 * DEFRAGMENT_FILL_RATIO;
+        if sparse {
+            self.defragment();
         }
+        sparse
+    }
 
-        // Convert tags to lowercase for case-insensitive matching
-        let tags_lower: Vec<String> = tags.iter().map(|tag| tag.to_lowercase()).collect();
+    /// Searches for repos by tags, returns paths sorted by frecency.
+    /// Repos scoring below `min_score` are left out
+    #[cfg(test)]
+    pub fn search_by_tags(&mut self, tags: &[String], min_score: Option<f64>) -> Vec<String> {
+        self.search(&SearchExpr {
+            include: tags.to_vec(),
+            min_score,
+            ..Default::default()
+        })
+    }
+
+    /// Searches for repos matching a search expression, returns paths sorted by frecency
+    #[cfg(test)]
+    pub fn search(&mut self, expr: &SearchExpr) -> Vec<String> {
+        self.search_with_scores(expr)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Searches for repos matching a search expression, returns paths and their
+    /// frecency scores sorted by frecency. Every match counts as accessed
+    #[cfg(test)]
+    pub fn search_with_scores(&mut self, expr: &SearchExpr) -> Vec<(String, f64)> {
+        let matches = self.score_search(expr);
+        self.record_search_accesses(&matches);
+        matches
+    }
+
+    /// Scores the repos matching a search expression, sorted by frecency and
+    /// including the access the search adds, but without recording it. Lets a
+    /// caller that only shows some matches record just those with
+    /// `record_search_accesses`
+    pub fn score_search(&self, expr: &SearchExpr) -> Vec<(String, f64)> {
+        if expr.include.is_empty() {
+            return Vec::new();
+        }
 
         // Collect matching repos and their frecency scores
+        let now = Utc::now();
         let mut matches: Vec<(String, f64)> = self
             .repos
-            .iter_mut()
-            .filter(|(_, repo_access)| {
-                tags_lower.iter().all(|search_tag| {
-                    repo_access
-                        .tags
-                        .iter()
-                        .any(|t| t.to_lowercase() == *search_tag)
-                })
+            .iter()
+            // Scores are checked before this search adds its own access
+            .filter(|(path, repo_access)| {
+                expr.matches(&repo_access.tags)
+                    && expr.score_in_range(self.frecency(repo_access))
+                    && expr.language.matches(path, repo_access)
+                    && expr.path_in_range(path)
+                    && expr.archived.matches(repo_access.archived)
+                    && expr.accessed.contains(repo_access, now)
             })
             .map(|(path, repo_access)| {
-                // Record access for each viewed repo
-                repo_access.record_access();
-                (path.clone(), repo_access.calculate_frecency())
+                let mut viewed = repo_access.clone();
+                viewed.record_access(self.max_access_history);
+                (path.clone(), self.frecency(&viewed))
             })
             .collect();
 
         // Sort by frecency score (descending)
         matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
-        // Return just the paths
-        matches.into_iter().map(|(path, _)| path).collect()
+        matches
     }
 
-    /// Searches for repos by a single tag (for backward compatibility)
-    pub fn search_by_tag(&mut self, tag: &str) -> Vec<String> {
-        self.search_by_tags(&[tag.to_string()])
+    /// Records an access on each of the given search results
+    pub fn record_search_accesses(&mut self, matches: &[(String, f64)]) {
+        for (path, _) in matches {
+            if let Some(repo_access) = self.repos.get_mut(path) {
+                repo_access.record_access(self.max_access_history);
+            }
+        }
     }
 
-    /// Removes repos with non-existent paths
-    pub fn cleanup(&mut self) {
-        self.repos.retain(|path, _| Path::new(path).exists());
-    }
+    /// Returns all repo paths sorted by frecency, without recording an access
+    pub fn get_repos_by_frecency(&self) -> Vec<String> {
+        let mut repos: Vec<(String, f64)> = self
+            .repos
+            .iter()
+            .map(|(path, repo_access)| (path.clone(), self.frecency(repo_access)))
+            .collect();
 
-    /// Returns a map of all tags and their usage counts
-    pub fn get_all_tags(&self) -> HashMap<String, usize> {
-        let mut tag_counts = HashMap::new();
+        repos.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
 
-        for repo_access in self.repos.values() {
-            for tag in &repo_access.tags {
-                *tag_counts.entry(tag.clone()).or_insert(0) += 1;
-            }
-        }
+        repos.into_iter().map(|(path, _)| path).collect()
+    }
 
-        tag_counts
+    /// Returns repos accessed within the last `days` (30 by default) that have all
+    /// of `tags`, most frecent first. Answers "what am I actively working on?"
+    pub fn active_repos(&self, tags: &[String], days: Option<u64>) -> Vec<String> {
+        let days = days.unwrap_or(DEFAULT_ACTIVE_DAYS);
+        let cutoff = Utc::now() - Duration::days(days as i64);
+        let expr = SearchExpr {
+            include: tags.to_vec(),
+            ..Default::default()
+        };
+
+        self.get_repos_by_frecency()
+            .into_iter()
+            .filter(|path| {
+                let repo_access = &self.repos[path];
+                let is_recent = repo_access
+                    .access_times
+                    .iter()
+                    .max()
+                    .is_some_and(|last_access| *last_access >= cutoff);
+                is_recent && (tags.is_empty() || expr.matches(&repo_access.tags))
+            })
+            .collect()
     }
-}
+
+    /// Returns the paths of repos last accessed within `window`
+    pub fn accessed_within(&self, window: Duration) -> HashSet<String> {
+        let cutoff = Utc::now() - window;
+        self.repos
+            .iter()
+            .filter(|(_, repo_access)| {
+                repo_access
+                    .access_times
+                    .iter()
+                    .max()
+                    .is_some_and(|last_access| *last_access >= cutoff)
+            })
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Returns all repo paths sorted by first commit date, oldest first.
+    /// Repos without a known first commit date are listed last
+    pub fn get_repos_by_age(&self) -> Vec<String> {
+        let mut repos: Vec<(&String, &RepoAccess)> = self.repos.iter().collect();
+
+        repos.sort_by(
+            |a, b| match (a.1.first_commit_date, b.1.first_commit_date) {
+                (Some(date_a), Some(date_b)) => date_a.cmp(&date_b).then_with(|| a.0.cmp(b.0)),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.0.cmp(b.0),
+            },
+        );
+
+        repos.into_iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Returns all repo paths sorted by priority, high first and unset last.
+    /// Repos with the same priority are ordered by frecency
+    pub fn get_repos_by_priority(&self) -> Vec<String> {
+        let mut repos: Vec<(&String, Option<Priority>, f64)> = self
+            .repos
+            .iter()
+            .map(|(path, repo_access)| (path, repo_access.priority, self.frecency(repo_access)))
+            .collect();
+
+        repos.sort_by(|a, b| {
+            (a.1.is_none(), a.1)
+                .cmp(&(b.1.is_none(), b.1))
+                .then_with(|| b.2.total_cmp(&a.2))
+                .then_with(|| a.0.cmp(b.0))
+        });
+
+        repos.into_iter().map(|(path, _, _)| path.clone()).collect()
+    }
+
+    /// Replaces every stored path with its canonical form, resolving symlinks and
+    /// `.`/`..` components. Repos that turn out to be the same directory are merged
+    pub fn canonicalize_paths(&mut self) -> Result<PathNormalizeResult> {
+        let mut result = PathNormalizeResult::default();
+
+        let mut paths: Vec<String> = self.repos.keys().cloned().collect();
+        paths.sort();
+
+        for path in paths {
+            let canonical = match fs::canonicalize(&path) {
+                Ok(canonical) => canonical.to_string_lossy().to_string(),
+                Err(_) => {
+                    result.failed.push(path);
+                    continue;
+                }
+            };
+
+            if canonical == path {
+                result.unchanged += 1;
+                continue;
+            }
+
+            let repo_access = self.repos.remove(&path).expect("path from repos");
+            match self.repos.get_mut(&canonical) {
+                Some(existing) => existing.merge(repo_access, self.max_access_history),
+                None => {
+                    self.repos.insert(canonical.clone(), repo_access);
+                }
+            }
+            result.changed.push((path, canonical));
+        }
+
+        Ok(result)
+    }
+
+    /// Tracks every git repo at most `depth` levels below `root` with `tags` and
+    /// its detected language. Repos that are already tracked are left as they are
+    pub fn scan_repos(&mut self, root: &str, tags: &[String], depth: usize) -> Result<ScanReport> {
+        let root = Self::to_absolute_path(root)?;
+        if !Path::new(&root).is_dir() {
+            return Err(anyhow!("Not a directory: {}", root));
+        }
+
+        let scan = scan_git_repos(Path::new(&root), depth);
+        let mut report = ScanReport {
+            not_git: scan.other_dirs,
+            ..Default::default()
+        };
+        for repo in scan.repos {
+            let path = repo.to_string_lossy().to_string();
+            if self.repos.contains_key(&path) {
+                report.already_tracked.push(path);
+                continue;
+            }
+
+            self.add_repo(&path, tags.to_vec())?;
+            let repo_access = self.repos.get_mut(&path).expect("repo was just added");
+            repo_access.language = detect_language(&repo);
+            repo_access.last_language_check = Some(Utc::now());
+            report.added.push(path);
+        }
+        Ok(report)
+    }
+
+    /// Re-reads the `.mangit-tags` file of every repo. The file's tags are added to
+    /// the existing ones, or replace them if `replace` is set
+    pub fn apply_tag_files(&mut self, replace: bool) -> TagFileReport {
+        let mut report = TagFileReport::default();
+
+        let mut paths: Vec<&String> = self.repos.keys().collect();
+        paths.sort();
+        let results: Vec<(String, Result<Option<Vec<String>>>)> = paths
+            .into_iter()
+            .map(|path| (path.clone(), read_tag_file(Path::new(path))))
+            .collect();
+
+        for (path, result) in results {
+            match result {
+                Ok(Some(file_tags)) => {
+                    let repo_access = self.repos.get_mut(&path).expect("path from repos");
+                    if replace {
+                        repo_access.tags = file_tags;
+                    } else {
+                        for tag in file_tags {
+                            if !repo_access.tags.contains(&tag) {
+                                repo_access.tags.push(tag);
+                            }
+                        }
+                    }
+                    report.updated += 1;
+                }
+                Ok(None) => report.skipped += 1,
+                Err(e) => report.errors.push((path, e)),
+            }
+        }
+
+        report
+    }
+
+    /// Returns the display names used by more than one repo, sorted by name
+    pub fn find_name_conflicts(&self) -> Vec<NameConflict> {
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for (path, repo_access) in &self.repos {
+            by_name
+                .entry(repo_access.display_name(path))
+                .or_default()
+                .push(path.clone());
+        }
+
+        let mut conflicts: Vec<NameConflict> = by_name
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(name, mut paths)| {
+                paths.sort();
+                NameConflict { name, paths }
+            })
+            .collect();
+        conflicts.sort_by(|a, b| a.name.cmp(&b.name));
+        conflicts
+    }
+
+    /// Gives a repo a display name. Fails if another repo already goes by that name
+    pub fn set_repo_name(&mut self, path: &str, name: &str) -> Result<()> {
+        let abs_path = self.resolve_path(path)?;
+        if let Some((other, _)) = self.repos.iter().find(|(other, repo_access)| {
+            **other != abs_path && repo_access.display_name(other) == name
+        }) {
+            return Err(anyhow!("Name '{}' is already used by {}", name, other));
+        }
+
+        let repo_access = self
+            .repos
+            .get_mut(&abs_path)
+            .ok_or_else(|| anyhow!("Repo not found: {}", path))?;
+        repo_access.name = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Renames the repo whose name, path or ID is `old`. Returns false if there is
+    /// no such repo, fails if `old` is ambiguous or `new` is taken by another repo
+    pub fn rename_repo(&mut self, old: &str, new: &str) -> Result<bool> {
+        let by_path = self.repos.contains_key(&self.resolve_path(old)?);
+        let by_name = self
+            .repos
+            .iter()
+            .any(|(path, repo_access)| repo_access.display_name(path) == old);
+        if !by_path && !by_name {
+            return Ok(false);
+        }
+
+        let path = self.resolve_repo(old)?;
+        self.set_repo_name(&path, new)?;
+        Ok(true)
+    }
+
+    /// Returns all repo paths sorted by number of tags, most tagged first
+    pub fn get_repos_by_tag_count(&self) -> Vec<String> {
+        let mut repos: Vec<(&String, &RepoAccess)> = self.repos.iter().collect();
+        repos.sort_by(|a, b| {
+            b.1.tags
+                .len()
+                .cmp(&a.1.tags.len())
+                .then_with(|| a.0.cmp(b.0))
+        });
+        repos.into_iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Returns all repo paths sorted by a sort expression, highest value first.
+    /// `age_days` counts from the first commit, or the first access if that is unknown
+    pub fn get_repos_by_expr(&self, expr: &SortExpr) -> Vec<String> {
+        let now = Utc::now();
+        let mut repos: Vec<(String, f64)> = self
+            .repos
+            .iter()
+            .map(|(path, repo_access)| {
+                let born = repo_access
+                    .first_commit_date
+                    .or_else(|| repo_access.access_times.iter().min().copied());
+                let vars = SortVars {
+                    frecency: self.frecency(repo_access),
+                    tag_count: repo_access.tags.len() as f64,
+                    age_days: born.map_or(0.0, |born| (now - born).num_days() as f64),
+                    access_count: repo_access.access_times.len() as f64,
+                };
+                (path.clone(), expr.eval(&vars))
+            })
+            .collect();
+
+        repos.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        repos.into_iter().map(|(path, _)| path).collect()
+    }
+
+    /// Returns all repo paths sorted by their latest access, most recent first
+    pub fn get_repos_by_last_access(&self) -> Vec<String> {
+        let mut repos: Vec<(&String, Option<&DateTime<Utc>>)> = self
+            .repos
+            .iter()
+            .map(|(path, repo_access)| (path, repo_access.access_times.iter().max()))
+            .collect();
+        repos.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        repos.into_iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    /// Returns the paths of all tracked repos, sorted
+    pub fn all_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.repos.keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Returns the `n` most recently accessed repos, most recent first. Repos that
+    /// were never accessed are left out
+    pub fn most_recently_accessed(&self, n: usize) -> Vec<String> {
+        let mut paths = self.get_repos_by_last_access();
+        paths.retain(|path| !self.repos[path].access_times.is_empty());
+        paths.truncate(n);
+        paths
+    }
+
+    /// Like `most_recently_accessed`, but with each repo's last access time
+    pub fn recent_repos(&self, n: usize) -> Vec<(String, DateTime<Utc>)> {
+        self.most_recently_accessed(n)
+            .into_iter()
+            .map(|path| {
+                let last_access = self.repos[&path].access_times.iter().max().copied();
+                (
+                    path,
+                    last_access.expect("never accessed repos are left out"),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the `n` most frecent repos with their scores, highest first
+    pub fn top_repos(&self, n: usize) -> Vec<(String, f64)> {
+        self.get_repos_by_frecency()
+            .into_iter()
+            .take(n)
+            .map(|path| {
+                let score = self.frecency(&self.repos[&path]);
+                (path, score)
+            })
+            .collect()
+    }
+
+    /// Searches for repos by a single tag (for backward compatibility)
+    #[cfg(test)]
+    pub fn search_by_tag(&mut self, tag: &str) -> Vec<String> {
+        self.search_by_tags(&[tag.to_string()], None)
+    }
+
+    /// Checks that every repo's path exists and is a git repo, and that no repo is
+    /// stored twice under slightly different paths
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        let mut paths: Vec<&String> = self.repos.keys().collect();
+        paths.sort();
+
+        let mut seen: HashMap<PathBuf, &String> = HashMap::new();
+        for path in paths {
+            if !Path::new(path).exists() {
+                report.missing.push(path.clone());
+            } else if !is_git_repo(Path::new(path)) {
+                report.not_git.push(path.clone());
+            }
+
+            // `/code/api/`, `/code/./api` and `/code/x/../api` are all `/code/api`
+            if let Some(first) = seen.insert(normalize_path(Path::new(path)), path) {
+                report.duplicates.push((first.clone(), path.clone()));
+            }
+        }
+        report
+    }
+
+    /// Removes repos with non-existent paths. Returns true if any were removed
+    pub fn cleanup(&mut self) -> bool {
+        let count = self.repos.len();
+        self.repos.retain(|path, _| Path::new(path).exists());
+        self.repos.len() != count
+    }
+
+    /// Spawns a thread that loads and cleans up the storage every `interval` until
+    /// the returned task is stopped, saving only when a repo was removed
+    pub fn start_cleanup_task(config: Arc<Config>, interval: StdDuration) -> CleanupTask {
+        let (shutdown, shutdown_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = shutdown_rx.recv_timeout(interval) {
+                let result = Storage::load_all(&config).and_then(|mut storage| {
+                    if storage.cleanup() {
+                        storage.save(&config)?;
+                    }
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    eprintln!("Cleanup failed: {}", e);
+                }
+            }
+        });
+
+        CleanupTask { shutdown, handle }
+    }
+
+    /// Returns a map of all tags and their usage counts
+    pub fn get_all_tags(&self) -> HashMap<String, usize> {
+        let mut tag_counts = HashMap::new();
+
+        for repo_access in self.repos.values() {
+            for tag in &repo_access.tags {
+                *tag_counts.entry(tag.clone()).or_insert(0) += 1;
+            }
+        }
+
+        tag_counts
+    }
+
+    /// Renames a tag on every repo, matching `old` regardless of case. A repo that
+    /// already has `new` just loses `old`, so no duplicate is created. Returns how
+    /// many repos changed
+    pub fn rename_tag(&mut self, old: &str, new: &str) -> usize {
+        let old = old.to_lowercase();
+        let mut updated = 0;
+
+        for repo_access in self.repos.values_mut() {
+            let mut renamed: Vec<String> = Vec::with_capacity(repo_access.tags.len());
+            for tag in &repo_access.tags {
+                let tag = if tag.to_lowercase() == old { new } else { tag };
+                if !renamed.iter().any(|t| t == tag) {
+                    renamed.push(tag.to_string());
+                }
+            }
+
+            if renamed != repo_access.tags {
+                repo_access.tags = renamed;
+                updated += 1;
+            }
+        }
+
+        updated
+    }
+
+    /// Removes a tag from every repo, matching it regardless of case. Repos left
+    /// without tags are kept, or stop being tracked if `remove_empty` is set
+    pub fn delete_tag(&mut self, tag: &str, remove_empty: bool) -> TagDeleteReport {
+        let tag = tag.to_lowercase();
+        let mut report = TagDeleteReport::default();
+
+        for (path, repo_access) in self.repos.iter_mut() {
+            let count = repo_access.tags.len();
+            repo_access.tags.retain(|t| t.to_lowercase() != tag);
+            if repo_access.tags.len() == count {
+                continue;
+            }
+
+            report.updated += 1;
+            if repo_access.tags.is_empty() {
+                report.untagged.push(path.clone());
+            }
+        }
+        report.untagged.sort();
+
+        if remove_empty && !report.untagged.is_empty() {
+            for path in &report.untagged {
+                self.repos.remove(path);
+            }
+            self.defragment_if_sparse();
+        }
+        report
+    }
+
+    /// Returns the vocabulary tags no repo uses anymore, sorted
+    pub fn get_orphaned_tag_defs(&self, vocab: &TagVocabulary) -> Vec<String> {
+        let in_use = self.get_all_tags();
+        let mut orphaned: Vec<String> = vocab
+            .tags
+            .keys()
+            .filter(|tag| !in_use.contains_key(*tag))
+            .cloned()
+            .collect();
+        orphaned.sort();
+        orphaned
+    }
+
+    /// Returns the tags used on repos that the vocabulary doesn't define, sorted.
+    /// Always empty without a vocabulary
+    pub fn validate_tag_consistency(&self, vocab: &TagVocabulary) -> Vec<String> {
+        if vocab.tags.is_empty() {
+            return Vec::new();
+        }
+
+        let mut undefined: Vec<String> = self
+            .get_all_tags()
+            .into_keys()
+            .filter(|tag| vocab.describe(tag).is_none())
+            .collect();
+        undefined.sort();
+        undefined
+    }
+
+    /// Returns how often each pair of tags appears on the same repo.
+    /// Pairs are keyed in alphabetical order so (a, b) and (b, a) are merged
+    pub fn get_tag_cooccurrence(&self) -> HashMap<(String, String), usize> {
+        let mut pair_counts = HashMap::new();
+
+        for repo_access in self.repos.values() {
+            let mut tags: Vec<&String> = repo_access.tags.iter().collect();
+            tags.sort();
+            tags.dedup();
+
+            for (i, a) in tags.iter().enumerate() {
+                for b in &tags[i + 1..] {
+                    *pair_counts.entry(((*a).clone(), (*b).clone())).or_insert(0) += 1;
+                }
+            }
+        }
+
+        pair_counts
+    }
+}
+
+fn frecency_score(
+    repo_access: &RepoAccess,
+    weights: &FrecencyConfig,
+    weight_by_priority: bool,
+) -> f64 {
+    if weight_by_priority {
+        repo_access.priority_weighted_frecency(weights)
+    } else {
+        repo_access.calculate_frecency(weights)
+    }
+}
+
+/// Multiplies the scores of the `boosted` repos by `factor` and sorts the
+/// results by score again
+pub fn boost_scores(
+    scored: Vec<(String, f64)>,
+    boosted: &HashSet<String>,
+    factor: f64,
+) -> Vec<(String, f64)> {
+    let mut scored: Vec<(String, f64)> = scored
+        .into_iter()
+        .map(|(path, score)| {
+            let score = if boosted.contains(&path) {
+                score * factor
+            } else {
+                score
+            };
+            (path, score)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored
+}
+
+/// Returns the average frecency of the repos carrying each tag
+pub fn compute_tag_frecency_scores(
+    repos: &HashMap<String, RepoAccess>,
+    weights: &FrecencyConfig,
+) -> HashMap<String, f64> {
+    let mut totals: HashMap<String, (f64, usize)> = HashMap::new();
+
+    for repo_access in repos.values() {
+        let score = repo_access.calculate_frecency(weights);
+        for tag in &repo_access.tags {
+            let entry = totals.entry(tag.clone()).or_insert((0.0, 0));
+            entry.0 += score;
+            entry.1 += 1;
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|(tag, (total, count))| (tag, total / count as f64))
+        .collect()
+}
 
 #[cfg(test)]
 mod tests_storage {
     use super::*;
-    use std::fs::File;
-    use std::io::Write;
+    use chrono::TimeZone;
     use std::thread::sleep;
-    use std::time::Duration as StdDuration;
     use tempfile::tempdir;
 
     fn create_test_config() -> (Config, tempfile::TempDir) {
         let temp_dir = tempdir().unwrap();
         let config = Config {
             mangit_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Config::default()
         };
         config.ensure_mangit_dir().unwrap();
         (config, temp_dir)
@@ -285,22 +1797,27 @@ mod tests_storage {
         let repo_path = create_fake_repo(temp_dir.path());
 
         let mut storage = Storage::new(&config).unwrap();
-        let is_new = storage
+        let result = storage
             .add_repo(
                 repo_path.to_str().unwrap(),
                 vec!["test".to_string(), "rust".to_string()],
             )
             .unwrap();
 
-        assert!(is_new);
+        assert!(result.is_new);
+        assert_eq!(result.name, "fake_repo");
+        assert_eq!(result.path, repo_path.to_str().unwrap());
+        assert_eq!(result.id, storage.repos[&result.path].repo_id);
+        assert_eq!(result.id.len(), 8);
         assert_eq!(storage.repos.len(), 1);
 
         // Test adding the same repo again
-        let is_new = storage
+        let second = storage
             .add_repo(repo_path.to_str().unwrap(), vec!["updated".to_string()])
             .unwrap();
 
-        assert!(!is_new);
+        assert!(!second.is_new);
+        assert_eq!(second.id, result.id);
         assert_eq!(storage.repos.len(), 1);
 
         // Verify tags were updated
@@ -361,6 +1878,62 @@ mod tests_storage {
         assert!(!updated);
     }
 
+    #[test]
+    fn test_get_repos_by_last_access() {
+        let mut storage = Storage::default();
+        let now = Utc::now();
+        for (path, days_ago) in [("/repos/old", 30), ("/repos/new", 1), ("/repos/mid", 7)] {
+            let mut repo_access = RepoAccess::new(vec![]);
+            repo_access.access_times = vec![now - Duration::days(days_ago)];
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+        let mut never = RepoAccess::new(vec![]);
+        never.access_times.clear();
+        storage.repos.insert("/repos/never".to_string(), never);
+
+        assert_eq!(
+            storage.get_repos_by_last_access(),
+            vec!["/repos/new", "/repos/mid", "/repos/old", "/repos/never"]
+        );
+        assert_eq!(
+            storage.most_recently_accessed(2),
+            vec!["/repos/new", "/repos/mid"]
+        );
+        assert_eq!(
+            storage.most_recently_accessed(10),
+            vec!["/repos/new", "/repos/mid", "/repos/old"]
+        );
+        assert!(storage.most_recently_accessed(0).is_empty());
+    }
+
+    #[test]
+    fn test_reset_tags() {
+        let mut storage = storage_with_priorities();
+        let access_counts: Vec<usize> = storage
+            .repos
+            .values()
+            .map(|repo_access| repo_access.access_times.len())
+            .collect();
+
+        assert_eq!(storage.reset_tags(None).unwrap(), storage.repos.len());
+        assert!(storage.repos.values().all(|r| r.tags.is_empty()));
+        // Frecency data is left alone
+        let after: Vec<usize> = storage
+            .repos
+            .values()
+            .map(|repo_access| repo_access.access_times.len())
+            .collect();
+        assert_eq!(access_counts, after);
+
+        let count = storage
+            .replace_tags(None, vec!["inbox".to_string()])
+            .unwrap();
+        assert_eq!(count, storage.repos.len());
+        assert!(storage.repos.values().all(|r| r.tags == ["inbox"]));
+
+        assert_eq!(storage.reset_tags(Some("/not/tracked")).unwrap(), 0);
+    }
+
     #[test]
     fn test_reset_frequency() {
         let (config, temp_dir) = create_test_config();
@@ -497,20 +2070,123 @@ mod tests_storage {
             .unwrap();
 
         // Test searching by multiple tags
-        let rust_cli_repos = storage.search_by_tags(&["rust".to_string(), "cli".to_string()]);
+        let rust_cli_repos = storage.search_by_tags(&["rust".to_string(), "cli".to_string()], None);
         assert_eq!(rust_cli_repos.len(), 1);
         assert_eq!(rust_cli_repos[0], repo1.to_str().unwrap().to_string());
 
         // Test searching by tags where no repo has all tags
-        let no_match_repos = storage.search_by_tags(&["rust".to_string(), "python".to_string()]);
+        let no_match_repos =
+            storage.search_by_tags(&["rust".to_string(), "python".to_string()], None);
         assert_eq!(no_match_repos.len(), 0);
 
         // Test case insensitivity
-        let case_insensitive = storage.search_by_tags(&["RUST".to_string(), "cli".to_string()]);
+        let case_insensitive =
+            storage.search_by_tags(&["RUST".to_string(), "cli".to_string()], None);
         assert_eq!(case_insensitive.len(), 1);
         assert_eq!(case_insensitive[0], repo1.to_str().unwrap().to_string());
     }
 
+    #[test]
+    fn test_search_any_tag() {
+        let mut storage = Storage::default();
+        let now = Utc::now();
+        for (path, tags, accesses) in [
+            ("/repos/rust-cli", &["rust", "cli"][..], 1),
+            ("/repos/rust-web", &["rust", "web"][..], 3),
+            ("/repos/py-cli", &["python", "cli"][..], 5),
+            ("/repos/go", &["go"][..], 9),
+        ] {
+            let mut repo_access = RepoAccess::new(tags.iter().map(|t| t.to_string()).collect());
+            repo_access.access_times = vec![now; accesses];
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+
+        let any = |tags: &[&str]| SearchExpr {
+            include: tags.iter().map(|t| t.to_string()).collect(),
+            requirement: TagRequirement::Any,
+            ..Default::default()
+        };
+        assert_eq!(
+            storage.search(&any(&["RUST", "cli"])),
+            vec!["/repos/py-cli", "/repos/rust-web", "/repos/rust-cli"]
+        );
+        assert_eq!(
+            storage.search_by_tags(&["rust".to_string(), "cli".to_string()], None),
+            vec!["/repos/rust-cli"]
+        );
+
+        // One tag means the same thing in both modes
+        assert_eq!(
+            storage.search(&any(&["rust"])),
+            storage.search_by_tags(&["rust".to_string()], None)
+        );
+        assert!(storage.search(&any(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_search_expr() {
+        let (config, temp_dir) = create_test_config();
+        let repo1 = create_fake_repo(&temp_dir.path().join("repo1"));
+        let repo2 = create_fake_repo(&temp_dir.path().join("repo2"));
+        let repo3 = create_fake_repo(&temp_dir.path().join("repo3"));
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(
+                repo1.to_str().unwrap(),
+                vec!["rust".to_string(), "cli".to_string()],
+            )
+            .unwrap();
+        storage
+            .add_repo(
+                repo2.to_str().unwrap(),
+                vec!["rust".to_string(), "web".to_string()],
+            )
+            .unwrap();
+        storage
+            .add_repo(
+                repo3.to_str().unwrap(),
+                vec!["python".to_string(), "cli".to_string()],
+            )
+            .unwrap();
+
+        // (rust OR python) AND NOT web
+        let expr = SearchExpr {
+            include: vec!["rust".to_string(), "python".to_string()],
+            exclude: vec!["WEB".to_string()],
+            requirement: TagRequirement::Any,
+            ..Default::default()
+        };
+        let results = storage.search(&expr);
+        assert_eq!(results.len(), 2);
+        assert!(results.contains(&repo1.to_str().unwrap().to_string()));
+        assert!(results.contains(&repo3.to_str().unwrap().to_string()));
+
+        // rust AND NOT cli
+        let expr = SearchExpr {
+            include: vec!["rust".to_string()],
+            exclude: vec!["cli".to_string()],
+            requirement: TagRequirement::All,
+            ..Default::default()
+        };
+        assert_eq!(storage.search(&expr), vec![repo2.to_str().unwrap()]);
+
+        // Exactly rust and cli
+        let expr = SearchExpr {
+            include: vec!["cli".to_string(), "rust".to_string()],
+            requirement: TagRequirement::Exact,
+            ..Default::default()
+        };
+        assert_eq!(storage.search(&expr), vec![repo1.to_str().unwrap()]);
+
+        // Excluding without including matches nothing
+        let expr = SearchExpr {
+            exclude: vec!["web".to_string()],
+            ..Default::default()
+        };
+        assert!(storage.search(&expr).is_empty());
+    }
+
     #[test]
     fn test_frecency_sorting() {
         let (config, temp_dir) = create_test_config();
@@ -542,31 +2218,623 @@ mod tests_storage {
     }
 
     #[test]
-    fn test_cleanup() {
+    fn test_get_repos_by_frecency() {
         let (config, temp_dir) = create_test_config();
-        let repo_path = create_fake_repo(temp_dir.path());
+        let repo1 = create_fake_repo(&temp_dir.path().join("repo1"));
+        let repo2 = create_fake_repo(&temp_dir.path().join("repo2"));
 
         let mut storage = Storage::new(&config).unwrap();
         storage
-            .add_repo(repo_path.to_str().unwrap(), vec!["test".to_string()])
+            .add_repo(repo1.to_str().unwrap(), vec!["common".to_string()])
             .unwrap();
+        storage
+            .add_repo(repo2.to_str().unwrap(), vec!["other".to_string()])
+            .unwrap();
+        storage.record_access(repo2.to_str().unwrap()).unwrap();
 
-        // Add a non-existent path directly to the HashMap
-        let non_existent = "/path/does/not/exist";
-        storage.repos.insert(
-            non_existent.to_string(),
-            RepoAccess::new(vec!["fake".to_string()]),
+        let listed = storage.get_repos_by_frecency();
+        assert_eq!(
+            listed,
+            vec![repo2.to_str().unwrap(), repo1.to_str().unwrap()]
         );
 
-        assert_eq!(storage.repos.len(), 2);
+        // Listing must not count as an access
+        let repo_access = storage.repos.get(repo1.to_str().unwrap()).unwrap();
+        assert_eq!(repo_access.access_times.len(), 1);
+    }
 
-        // Run cleanup
-        storage.cleanup();
+    #[test]
+    fn test_resolve_repo() {
+        let mut storage = Storage::default();
+        for path in ["/work/api", "/old/api", "/work/web"] {
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(vec![]));
+        }
+        let mut renamed = RepoAccess::new(vec![]);
+        renamed.name = Some("site".to_string());
+        storage.repos.insert("/work/blog".to_string(), renamed);
+
+        assert_eq!(storage.resolve_repo("/work/web").unwrap(), "/work/web");
+        assert_eq!(storage.resolve_repo("web").unwrap(), "/work/web");
+        assert_eq!(storage.resolve_repo("site").unwrap(), "/work/blog");
+        let err = storage.resolve_repo("api").unwrap_err().to_string();
+        assert!(err.contains("/old/api, /work/api"), "{}", err);
+        assert!(storage.resolve_repo("missing").is_err());
+    }
+
+    #[test]
+    fn test_find_siblings() {
+        let mut storage = Storage::default();
+        for path in [
+            "/work/api",
+            "/work/web",
+            "/work/tools/cli",
+            "/work/tools/lib",
+            "/personal/blog",
+            "/work",
+        ] {
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(vec![]));
+        }
+
+        assert_eq!(
+            storage.find_siblings("/work/api", 1).unwrap(),
+            vec!["/work/web"]
+        );
+        assert_eq!(
+            storage.find_siblings("/work/tools/cli", 1).unwrap(),
+            vec!["/work/tools/lib"]
+        );
+        // Two levels up includes the repos in the parent's sibling directories
+        assert_eq!(
+            storage.find_siblings("/work/tools/cli", 2).unwrap(),
+            vec!["/work/api", "/work/tools/lib", "/work/web"]
+        );
+        // Untracked paths have siblings too
+        assert_eq!(
+            storage.find_siblings("/personal/notes", 1).unwrap(),
+            vec!["/personal/blog"]
+        );
+        assert!(storage.find_siblings("/work/api", 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remote_url_filter() {
+        let github = Some("git@GitHub.com:tizee/mangit.git");
+        let gitlab = Some("https://gitlab.com/group/project.git");
+
+        assert!(RemoteUrlFilter::Present.matches(github));
+        assert!(!RemoteUrlFilter::Present.matches(None));
+        assert!(RemoteUrlFilter::Missing.matches(None));
+        assert!(!RemoteUrlFilter::Missing.matches(gitlab));
+
+        let matching = RemoteUrlFilter::Matching("github.COM".to_string());
+        assert!(matching.matches(github));
+        assert!(!matching.matches(gitlab));
+        assert!(!matching.matches(None));
+    }
+
+    #[test]
+    fn test_archive_by_age() {
+        let now = Utc::now();
+        let mut storage = Storage::default();
+        for (path, days_ago) in [
+            ("/repos/old", Some(400)),
+            ("/repos/recent", Some(10)),
+            ("/repos/never", None),
+            ("/repos/archived", Some(500)),
+        ] {
+            let mut repo_access = RepoAccess::new(vec![]);
+            repo_access.access_times = days_ago
+                .map(|days| vec![now - Duration::days(days), now - Duration::days(days + 1)])
+                .unwrap_or_default();
+            repo_access.archived = path == "/repos/archived";
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+        // Accessed long ago but also recently
+        storage
+            .repos
+            .get_mut("/repos/recent")
+            .unwrap()
+            .access_times
+            .push(now - Duration::days(800));
+
+        assert_eq!(
+            storage.archive_by_age(Duration::days(365)).unwrap(),
+            vec!["/repos/old"]
+        );
+        assert!(storage.repos["/repos/old"].archived);
+        assert!(!storage.repos["/repos/recent"].archived);
+        assert!(!storage.repos["/repos/never"].archived);
+        // Nothing new to archive the second time
+        assert!(
+            storage
+                .archive_by_age(Duration::days(365))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            storage.archive_by_age(Duration::days(5)).unwrap(),
+            vec!["/repos/recent"]
+        );
+    }
+
+    #[test]
+    fn test_search_archived() {
+        let (config, temp_dir) = create_test_config();
+        let active = create_fake_repo(&temp_dir.path().join("active"));
+        let archived = create_fake_repo(&temp_dir.path().join("archived"));
+        let (active, archived) = (active.to_str().unwrap(), archived.to_str().unwrap());
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(active, vec!["rust".to_string()]).unwrap();
+        storage
+            .add_repo(archived, vec!["rust".to_string()])
+            .unwrap();
+        storage.repos.get_mut(archived).unwrap().archived = true;
+
+        let mut expr = SearchExpr {
+            include: vec!["rust".to_string()],
+            ..Default::default()
+        };
+        let paths = |storage: &mut Storage, expr: &SearchExpr| {
+            let mut paths: Vec<String> = storage
+                .search_with_scores(expr)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths(&mut storage, &expr), vec![active]);
+        expr.archived = ArchiveFilter::Include;
+        assert_eq!(paths(&mut storage, &expr), vec![active, archived]);
+        expr.archived = ArchiveFilter::Only;
+        assert_eq!(paths(&mut storage, &expr), vec![archived]);
+    }
+
+    #[test]
+    fn test_access_window() {
+        let now = Utc::now();
+        let accessed = |days_ago: &[i64]| {
+            let mut repo_access = RepoAccess::new(vec!["rust".to_string()]);
+            repo_access.access_times = days_ago
+                .iter()
+                .map(|days| now - Duration::days(*days))
+                .collect();
+            repo_access
+        };
+        let this_week = accessed(&[40, 2]);
+        let last_month = accessed(&[20, 30]);
+        let never = accessed(&[]);
+
+        let since = AccessWindow {
+            since: Some(Duration::weeks(1)),
+            before: None,
+        };
+        assert!(since.contains(&this_week, now));
+        assert!(!since.contains(&last_month, now));
+        assert!(!since.contains(&never, now));
+
+        let before = AccessWindow {
+            since: None,
+            before: Some(Duration::days(14)),
+        };
+        assert!(!before.contains(&this_week, now));
+        assert!(before.contains(&last_month, now));
+        assert!(before.contains(&never, now));
+
+        let between = AccessWindow {
+            since: Some(Duration::days(25)),
+            before: Some(Duration::days(14)),
+        };
+        assert!(between.contains(&last_month, now));
+        assert!(!between.contains(&this_week, now));
+        assert!(AccessWindow::default().contains(&never, now));
+
+        // Searches filter on the last access before their own
+        let mut storage = Storage::default();
+        storage.repos.insert("/repos/week".to_string(), this_week);
+        storage.repos.insert("/repos/month".to_string(), last_month);
+        let expr = SearchExpr {
+            include: vec!["rust".to_string()],
+            accessed: before,
+            ..Default::default()
+        };
+        let found: Vec<String> = storage
+            .search_with_scores(&expr)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(found, vec!["/repos/month"]);
+    }
+
+    #[test]
+    fn test_check_duplicate_name() {
+        let (config, temp_dir) = create_test_config();
+        let first = create_fake_repo(&temp_dir.path().join("work"));
+        let second = create_fake_repo(&temp_dir.path().join("personal"));
+        let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(first, vec![]).unwrap();
+
+        let err = storage
+            .check_duplicate_name("fake_repo", second)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(first));
+        assert!(err.contains(second));
+
+        // Re-adding the same repo is not a duplicate
+        assert!(storage.check_duplicate_name("fake_repo", first).is_ok());
+        assert!(storage.check_duplicate_name("other", second).is_ok());
+
+        // Custom names count instead of the directory name
+        storage.repos.get_mut(first).unwrap().name = Some("renamed".to_string());
+        assert!(storage.check_duplicate_name("fake_repo", second).is_ok());
+        assert!(storage.check_duplicate_name("renamed", second).is_err());
+    }
+
+    #[test]
+    fn test_get_repos_by_expr() {
+        let (config, temp_dir) = create_test_config();
+        let mut storage = Storage::new(&config).unwrap();
+        for (name, tags, accesses) in [("a", 1, 2), ("b", 3, 0), ("c", 2, 4)] {
+            let repo = create_fake_repo(&temp_dir.path().join(name));
+            let tags = (0..tags).map(|i| format!("tag{}", i)).collect();
+            storage.add_repo(repo.to_str().unwrap(), tags).unwrap();
+            for _ in 0..accesses {
+                storage.record_access(repo.to_str().unwrap()).unwrap();
+            }
+        }
+
+        let by_frecency = SortExpr::parse("frecency * 1.0").unwrap();
+        assert_eq!(
+            storage.get_repos_by_expr(&by_frecency),
+            storage.get_repos_by_frecency()
+        );
+        let by_tags = SortExpr::parse("tag_count").unwrap();
+        assert_eq!(
+            storage.get_repos_by_expr(&by_tags),
+            storage.get_repos_by_tag_count()
+        );
+        let dirs: Vec<String> = storage
+            .get_repos_by_expr(&SortExpr::parse("-access_count").unwrap())
+            .iter()
+            .map(|path| repo_name_from_path(Path::new(path).parent().unwrap().to_str().unwrap()))
+            .collect();
+        assert_eq!(dirs, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_active_repos() {
+        let (config, temp_dir) = create_test_config();
+        let recent = create_fake_repo(&temp_dir.path().join("recent"));
+        let stale = create_fake_repo(&temp_dir.path().join("stale"));
+        let older = create_fake_repo(&temp_dir.path().join("older"));
+
+        let mut storage = Storage::new(&config).unwrap();
+        for repo in [&recent, &stale, &older] {
+            storage
+                .add_repo(repo.to_str().unwrap(), vec!["work".to_string()])
+                .unwrap();
+        }
+        storage
+            .add_repo(
+                temp_dir.path().to_str().unwrap(),
+                vec!["personal".to_string()],
+            )
+            .unwrap();
+
+        // Last accessed 45 and 20 days ago
+        storage
+            .repos
+            .get_mut(stale.to_str().unwrap())
+            .unwrap()
+            .access_times = vec![Utc::now() - Duration::days(45)];
+        storage
+            .repos
+            .get_mut(older.to_str().unwrap())
+            .unwrap()
+            .access_times = vec![Utc::now() - Duration::days(20)];
+
+        // Default window of 30 days
+        let active = storage.active_repos(&["work".to_string()], None);
+        assert_eq!(
+            active,
+            vec![recent.to_str().unwrap(), older.to_str().unwrap()]
+        );
+
+        let active = storage.active_repos(&["work".to_string()], Some(7));
+        assert_eq!(active, vec![recent.to_str().unwrap()]);
+
+        // Without tags every recently accessed repo is active
+        let active = storage.active_repos(&[], None);
+        assert_eq!(active.len(), 3);
+    }
+
+    #[test]
+    fn test_get_repos_by_age() {
+        let (config, temp_dir) = create_test_config();
+        let repo1 = create_fake_repo(&temp_dir.path().join("repo1"));
+        let repo2 = create_fake_repo(&temp_dir.path().join("repo2"));
+        let repo3 = create_fake_repo(&temp_dir.path().join("repo3"));
+
+        let mut storage = Storage::new(&config).unwrap();
+        for repo in [&repo1, &repo2, &repo3] {
+            storage
+                .add_repo(repo.to_str().unwrap(), vec!["test".to_string()])
+                .unwrap();
+        }
+
+        let old = Utc::now() - Duration::days(365);
+        let newer = Utc::now() - Duration::days(30);
+        storage
+            .get_repo_mut(repo1.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .first_commit_date = Some(newer);
+        storage
+            .get_repo_mut(repo3.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .first_commit_date = Some(old);
+
+        let listed = storage.get_repos_by_age();
+        assert_eq!(
+            listed,
+            vec![
+                repo3.to_str().unwrap(),
+                repo1.to_str().unwrap(),
+                repo2.to_str().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cleanup() {
+        let (config, temp_dir) = create_test_config();
+        let repo_path = create_fake_repo(temp_dir.path());
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(repo_path.to_str().unwrap(), vec!["test".to_string()])
+            .unwrap();
+
+        // Add a non-existent path directly to the HashMap
+        let non_existent = "/path/does/not/exist";
+        storage.repos.insert(
+            non_existent.to_string(),
+            RepoAccess::new(vec!["fake".to_string()]),
+        );
+
+        assert_eq!(storage.repos.len(), 2);
+
+        // Run cleanup
+        assert!(storage.cleanup());
 
         // Only the real repo should remain
         assert_eq!(storage.repos.len(), 1);
         assert!(storage.repos.contains_key(repo_path.to_str().unwrap()));
         assert!(!storage.repos.contains_key(non_existent));
+
+        // Nothing left to remove
+        assert!(!storage.cleanup());
+    }
+
+    #[test]
+    fn test_validate() {
+        let (_config, temp_dir) = create_test_config();
+        let repo = create_fake_repo(temp_dir.path())
+            .to_string_lossy()
+            .to_string();
+        let plain = temp_dir.path().join("plain");
+        fs::create_dir_all(&plain).unwrap();
+        let plain = plain.to_string_lossy().to_string();
+        let missing = temp_dir.path().join("gone").to_string_lossy().to_string();
+
+        let slashed = format!("{}/", repo);
+        let dotted = format!("{}/./", repo);
+        let parent = format!("{}/../plain/../{}", repo, repo_name_from_path(&repo));
+
+        let mut storage = Storage::default();
+        for path in [&repo, &plain, &missing, &slashed, &dotted, &parent] {
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(vec![]));
+        }
+
+        let report = storage.validate();
+        assert_eq!(report.missing, vec![missing]);
+        assert_eq!(report.not_git, vec![plain.clone()]);
+        let mut duplicates: Vec<&String> = report.duplicates.iter().map(|(_, dup)| dup).collect();
+        duplicates.sort();
+        assert_eq!(duplicates, vec![&slashed, &parent, &dotted]);
+        assert_eq!(report.problem_count(), 5);
+
+        storage.cleanup();
+        for path in [&slashed, &dotted, &parent, &plain] {
+            storage.repos.remove(path);
+        }
+        assert_eq!(storage.validate(), ValidationReport::default());
+    }
+
+    #[test]
+    fn test_cleanup_task() {
+        let (config, temp_dir) = create_test_config();
+        let repo_path = create_fake_repo(temp_dir.path());
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(repo_path.to_str().unwrap(), vec!["test".to_string()])
+            .unwrap();
+        storage.save(&config).unwrap();
+
+        // Remove the repo from disk, the stale entry is still in the saved file
+        fs::remove_dir_all(&repo_path).unwrap();
+        let data = fs::read_to_string(config.repos_path()).unwrap();
+        assert!(data.contains(repo_path.to_str().unwrap()));
+
+        let task =
+            Storage::start_cleanup_task(Arc::new(config.clone()), StdDuration::from_millis(20));
+        sleep(StdDuration::from_millis(200));
+        task.stop();
+
+        let data = fs::read_to_string(config.repos_path()).unwrap();
+        assert!(!data.contains(repo_path.to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_scan_repos() {
+        let (config, temp_dir) = create_test_config();
+        let root = temp_dir.path().join("code");
+        for repo in ["api", "tools/cli", "tools/cli/vendor/dep", ".hidden/secret"] {
+            fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+        }
+        fs::write(root.join("api/Cargo.toml"), "[package]\n").unwrap();
+        fs::create_dir_all(root.join("notes/drafts")).unwrap();
+
+        let mut storage = Storage::new(&config).unwrap();
+        let cli = root.join("tools/cli").to_string_lossy().to_string();
+        storage.add_repo(&cli, vec!["old".to_string()]).unwrap();
+
+        let report = storage
+            .scan_repos(root.to_str().unwrap(), &["work".to_string()], 3)
+            .unwrap();
+        let api = root.join("api").to_string_lossy().to_string();
+        assert_eq!(report.added, vec![api.clone()]);
+        assert_eq!(report.already_tracked, vec![cli.clone()]);
+        // tools, notes and notes/drafts
+        assert_eq!(report.not_git, 3);
+
+        assert_eq!(storage.repos[&api].tags, vec!["work".to_string()]);
+        assert_eq!(storage.repos[&api].language.as_deref(), Some("Rust"));
+        assert_eq!(storage.repos[&cli].tags, vec!["old".to_string()]);
+        assert_eq!(storage.repos.len(), 2);
+
+        // Scanning again finds nothing new
+        let report = storage.scan_repos(root.to_str().unwrap(), &[], 3).unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(report.already_tracked.len(), 2);
+
+        // So does a root spelled with `.` and `..`
+        let dotted_root = format!("{}/./tools/..", root.display());
+        let report = storage.scan_repos(&dotted_root, &[], 3).unwrap();
+        assert!(report.added.is_empty());
+        assert_eq!(report.already_tracked.len(), 2);
+
+        assert!(
+            storage
+                .scan_repos(root.join("api/Cargo.toml").to_str().unwrap(), &[], 3)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_max_access_history() {
+        let (mut config, temp_dir) = create_test_config();
+        config.max_access_history = 3;
+        let repo_path = create_fake_repo(temp_dir.path());
+        let path = repo_path.to_str().unwrap();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(path, vec![]).unwrap();
+        let old = Utc::now() - Duration::days(10);
+        storage.repos.get_mut(path).unwrap().access_times = vec![old];
+        for _ in 0..5 {
+            storage.record_access(path).unwrap();
+        }
+
+        let times = &storage.repos[path].access_times;
+        assert_eq!(times.len(), 3);
+        assert!(times.is_sorted());
+        assert!(!times.contains(&old));
+
+        // Updating the tags counts as an access too
+        storage.update_repo(path, vec!["rust".to_string()]).unwrap();
+        assert_eq!(storage.repos[path].access_times.len(), 3);
+    }
+
+    #[test]
+    fn test_interrupted_save() {
+        let (config, temp_dir) = create_test_config();
+        let repo_path = create_fake_repo(temp_dir.path());
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(repo_path.to_str().unwrap(), vec!["rust".to_string()])
+            .unwrap();
+        storage.save(&config).unwrap();
+        assert_eq!(tmp_files(&config).len(), 0);
+
+        // A save that crashed halfway only leaves a truncated temporary file
+        let stale_path = config.repos_tmp_path();
+        fs::write(&stale_path, r#"{"repos": {"/trunc"#).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&stale_path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - StdDuration::from_secs(3600))
+            .unwrap();
+        // A recent one may belong to a save still running in another process
+        let running_path = config.repos_tmp_path();
+        assert_ne!(stale_path, running_path);
+        fs::write(&running_path, "{").unwrap();
+
+        let loaded = Storage::new(&config).unwrap();
+        assert_eq!(
+            loaded.repos[repo_path.to_str().unwrap()].tags,
+            vec!["rust".to_string()]
+        );
+        assert_eq!(tmp_files(&config), vec![running_path]);
+    }
+
+    fn tmp_files(config: &Config) -> Vec<PathBuf> {
+        fs::read_dir(config.mangit_dir_path())
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| Config::is_repos_tmp_file(&entry.file_name().to_string_lossy()))
+            .map(|entry| entry.path())
+            .collect()
+    }
+
+    #[test]
+    fn test_note() {
+        let (config, temp_dir) = create_test_config();
+        let repo = create_fake_repo(temp_dir.path());
+        let repo = repo.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&repo, vec![]).unwrap();
+        assert_eq!(storage.repos[&repo].note, None);
+
+        storage
+            .set_note(&repo, Some("Reference for the parser".to_string()))
+            .unwrap();
+        storage.save(&config).unwrap();
+        let mut storage = Storage::new(&config).unwrap();
+        assert_eq!(
+            storage.repos[&repo].note.as_deref(),
+            Some("Reference for the parser")
+        );
+
+        storage.set_note(&repo, None).unwrap();
+        storage.save(&config).unwrap();
+        let storage_file = fs::read_to_string(config.repos_path()).unwrap();
+        assert!(storage_file.contains(r#""note": null"#));
+        assert_eq!(Storage::new(&config).unwrap().repos[&repo].note, None);
+
+        let missing = temp_dir.path().join("missing");
+        assert!(storage.set_note(&missing.to_string_lossy(), None).is_err());
+
+        // Entries written before notes existed have no `note` key
+        let repo_access: RepoAccess =
+            serde_json::from_str(r#"{"tags": ["rust"], "access_times": []}"#).unwrap();
+        assert_eq!(repo_access.note, None);
     }
 
     #[test]
@@ -645,4 +2913,981 @@ mod tests_storage {
         assert_eq!(all_tags.get("web"), Some(&1));
         assert_eq!(all_tags.get("python"), Some(&1));
     }
+
+    #[test]
+    fn test_rename_tag() {
+        let mut storage = Storage::default();
+        for (path, tags) in [
+            ("/repos/old", &["web", "js"][..]),
+            ("/repos/new", &["javascript"][..]),
+            ("/repos/both", &["js", "cli", "javascript"][..]),
+            ("/repos/upper", &["JS"][..]),
+            ("/repos/none", &["rust"][..]),
+        ] {
+            let tags = tags.iter().map(|tag| tag.to_string()).collect();
+            storage
+                .repos
+                .insert(path.to_string(), RepoAccess::new(tags));
+        }
+        let tags = |storage: &Storage, path: &str| storage.repos[path].tags.clone();
+
+        assert_eq!(storage.rename_tag("Js", "javascript"), 3);
+        assert_eq!(tags(&storage, "/repos/old"), vec!["web", "javascript"]);
+        assert_eq!(tags(&storage, "/repos/new"), vec!["javascript"]);
+        assert_eq!(tags(&storage, "/repos/both"), vec!["javascript", "cli"]);
+        assert_eq!(tags(&storage, "/repos/upper"), vec!["javascript"]);
+        assert_eq!(tags(&storage, "/repos/none"), vec!["rust"]);
+
+        assert_eq!(storage.rename_tag("go", "golang"), 0);
+        // Fixing the case of a tag counts as a change
+        assert_eq!(storage.rename_tag("rust", "Rust"), 1);
+        assert_eq!(tags(&storage, "/repos/none"), vec!["Rust"]);
+        assert_eq!(storage.rename_tag("rust", "Rust"), 0);
+    }
+
+    #[test]
+    fn test_delete_tag() {
+        let storage_with_tags = || {
+            let mut storage = Storage::default();
+            for (path, tags) in [
+                ("/repos/only", &["WIP"][..]),
+                ("/repos/more", &["wip", "rust"][..]),
+                ("/repos/other", &["rust"][..]),
+                ("/repos/untagged", &[][..]),
+            ] {
+                let tags = tags.iter().map(|tag| tag.to_string()).collect();
+                storage
+                    .repos
+                    .insert(path.to_string(), RepoAccess::new(tags));
+            }
+            storage
+        };
+
+        let mut storage = storage_with_tags();
+        let report = storage.delete_tag("wip", false);
+        assert_eq!(report.updated, 2);
+        assert_eq!(report.untagged, vec!["/repos/only"]);
+        assert_eq!(storage.repos.len(), 4);
+        assert!(storage.repos["/repos/only"].tags.is_empty());
+        assert_eq!(storage.repos["/repos/more"].tags, vec!["rust"]);
+        assert_eq!(storage.delete_tag("wip", false), TagDeleteReport::default());
+
+        let mut storage = storage_with_tags();
+        let report = storage.delete_tag("Wip", true);
+        assert_eq!(report.updated, 2);
+        assert_eq!(report.untagged, vec!["/repos/only"]);
+        // Repos that had no tags to begin with stay
+        let mut paths: Vec<&String> = storage.repos.keys().collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["/repos/more", "/repos/other", "/repos/untagged"]
+        );
+    }
+
+    #[test]
+    fn test_get_tag_cooccurrence() {
+        let (config, temp_dir) = create_test_config();
+        let repo1 = create_fake_repo(&temp_dir.path().join("repo1"));
+        let repo2 = create_fake_repo(&temp_dir.path().join("repo2"));
+
+        let mut storage = Storage::new(&config).unwrap();
+
+        storage
+            .add_repo(
+                repo1.to_str().unwrap(),
+                vec!["rust".to_string(), "cli".to_string()],
+            )
+            .unwrap();
+
+        storage
+            .add_repo(
+                repo2.to_str().unwrap(),
+                vec!["rust".to_string(), "cli".to_string(), "web".to_string()],
+            )
+            .unwrap();
+
+        let pairs = storage.get_tag_cooccurrence();
+
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(
+            pairs.get(&("cli".to_string(), "rust".to_string())),
+            Some(&2)
+        );
+        assert_eq!(pairs.get(&("cli".to_string(), "web".to_string())), Some(&1));
+        assert_eq!(
+            pairs.get(&("rust".to_string(), "web".to_string())),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn test_compute_tag_frecency_scores() {
+        let mut repos = HashMap::new();
+
+        // Two "old" repos, last touched weeks ago
+        for path in ["/repos/a", "/repos/b"] {
+            let mut access = RepoAccess::new(vec!["old".to_string()]);
+            access.access_times = vec![Utc::now() - Duration::days(30)];
+            repos.insert(path.to_string(), access);
+        }
+
+        // One "hot" repo, accessed several times just now
+        let mut access = RepoAccess::new(vec!["hot".to_string()]);
+        access.access_times = vec![Utc::now(); 5];
+        repos.insert("/repos/c".to_string(), access);
+
+        let scores = compute_tag_frecency_scores(&repos, &FrecencyConfig::default());
+
+        assert_eq!(scores.get("old"), Some(&10.0));
+        assert_eq!(scores.get("hot"), Some(&500.0));
+
+        let mut tags: Vec<(&String, &f64)> = scores.iter().collect();
+        tags.sort_by(|a, b| b.1.total_cmp(a.1));
+        assert_eq!(tags[0].0, "hot");
+    }
+
+    #[test]
+    fn test_remove_matching() {
+        let (config, temp_dir) = create_test_config();
+        let old1 = create_fake_repo(&temp_dir.path().join("old/one"));
+        let old2 = create_fake_repo(&temp_dir.path().join("old/two"));
+        let new = create_fake_repo(&temp_dir.path().join("new"));
+
+        let mut storage = Storage::new(&config).unwrap();
+        for repo in [&old1, &old2, &new] {
+            storage
+                .add_repo(repo.to_str().unwrap(), vec!["test".to_string()])
+                .unwrap();
+        }
+
+        let old_prefix = temp_dir.path().join("old").to_string_lossy().to_string();
+        let removed = storage
+            .remove_matching(|path, _| path.starts_with(&old_prefix))
+            .unwrap();
+
+        assert_eq!(
+            removed,
+            vec![
+                old1.to_string_lossy().to_string(),
+                old2.to_string_lossy().to_string()
+            ]
+        );
+        assert_eq!(storage.repos.len(), 1);
+        assert!(storage.repos.contains_key(new.to_str().unwrap()));
+
+        // Nothing left to match
+        let removed = storage
+            .remove_matching(|path, _| path.starts_with(&old_prefix))
+            .unwrap();
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_language_is_gated_by_interval() {
+        let temp_dir = tempdir().unwrap();
+        let repo_path = create_fake_repo(temp_dir.path());
+        fs::write(repo_path.join("Cargo.toml"), "").unwrap();
+
+        let mut access = RepoAccess::new(vec![]);
+        assert!(access.refresh_language(&repo_path, Duration::hours(24)));
+        assert_eq!(access.language.as_deref(), Some("Rust"));
+
+        // Checked just now, so a change is not picked up yet
+        fs::remove_file(repo_path.join("Cargo.toml")).unwrap();
+        fs::write(repo_path.join("go.mod"), "").unwrap();
+        assert!(!access.refresh_language(&repo_path, Duration::hours(24)));
+        assert_eq!(access.language.as_deref(), Some("Rust"));
+
+        // Once the interval has passed the language is detected again
+        access.last_language_check = Some(Utc::now() - Duration::hours(25));
+        assert!(access.refresh_language(&repo_path, Duration::hours(24)));
+        assert_eq!(access.language.as_deref(), Some("Go"));
+    }
+
+    #[test]
+    fn test_record_access_updates_language() {
+        let (mut config, temp_dir) = create_test_config();
+        let repo_path = create_fake_repo(temp_dir.path());
+        fs::write(repo_path.join("Cargo.toml"), "").unwrap();
+        let path = repo_path.to_str().unwrap();
+
+        // Disabled by default
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(path, vec![]).unwrap();
+        storage.record_access(path).unwrap();
+        assert_eq!(storage.repos[path].language, None);
+        storage.save(&config).unwrap();
+
+        config.auto_update_language = true;
+        let mut storage = Storage::new(&config).unwrap();
+        storage.record_access(path).unwrap();
+        assert_eq!(storage.repos[path].language.as_deref(), Some("Rust"));
+        assert!(storage.repos[path].last_language_check.is_some());
+    }
+
+    #[test]
+    fn test_boost_recent_search_results() {
+        let (config, temp_dir) = create_test_config();
+        let recent = create_fake_repo(&temp_dir.path().join("recent"));
+        let busy = create_fake_repo(&temp_dir.path().join("busy"));
+        let recent = recent.to_string_lossy().to_string();
+        let busy = busy.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&recent, vec!["rust".to_string()]).unwrap();
+        storage.add_repo(&busy, vec!["rust".to_string()]).unwrap();
+
+        // One access half an hour ago against five accesses spread over a week
+        storage.repos.get_mut(&recent).unwrap().access_times =
+            vec![Utc::now() - Duration::minutes(30)];
+        storage.repos.get_mut(&busy).unwrap().access_times = (2..7)
+            .map(|days| Utc::now() - Duration::days(days))
+            .collect();
+
+        let expr = SearchExpr {
+            include: vec!["rust".to_string()],
+            ..Default::default()
+        };
+
+        // Recent accesses have to be taken before the search records its own
+        let boosted = storage.accessed_within(Duration::hours(1));
+        assert_eq!(boosted, HashSet::from([recent.clone()]));
+
+        let matches = storage.search_with_scores(&expr);
+        assert_eq!(matches[0].0, busy);
+
+        let matches = boost_scores(matches, &boosted, 5.0);
+        assert_eq!(matches[0].0, recent);
+
+        // Without anything to boost the order is unchanged
+        let matches = boost_scores(matches, &HashSet::new(), 5.0);
+        assert_eq!(matches[0].0, recent);
+    }
+
+    #[test]
+    fn test_score_search_records_only_shown_matches() {
+        let mut storage = Storage::default();
+        for path in ["/repos/a", "/repos/b"] {
+            let mut repo_access = RepoAccess::new(vec!["rust".to_string()]);
+            repo_access.access_times.clear();
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+        let expr = SearchExpr {
+            include: vec!["rust".to_string()],
+            ..Default::default()
+        };
+
+        let mut matches = storage.score_search(&expr);
+        assert_eq!(matches.len(), 2);
+        assert!(storage.repos.values().all(|r| r.access_times.is_empty()));
+        // Scored as if the search's own access were already recorded
+        let shown = matches[0].0.clone();
+        matches.truncate(1);
+        storage.record_search_accesses(&matches);
+        assert_eq!(storage.frecency(&storage.repos[&shown]), matches[0].1);
+
+        assert_eq!(storage.repos[&shown].access_times.len(), 1);
+        let hidden = if shown == "/repos/a" {
+            "/repos/b"
+        } else {
+            "/repos/a"
+        };
+        assert!(storage.repos[hidden].access_times.is_empty());
+    }
+
+    #[test]
+    fn test_search_by_tags_min_score() {
+        let (config, temp_dir) = create_test_config();
+        let active = create_fake_repo(&temp_dir.path().join("active"));
+        let stale = create_fake_repo(&temp_dir.path().join("stale"));
+        let active = active.to_string_lossy().to_string();
+        let stale = stale.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&active, vec!["rust".to_string()]).unwrap();
+        storage.add_repo(&stale, vec!["rust".to_string()]).unwrap();
+        storage.repos.get_mut(&stale).unwrap().access_times = vec![Utc::now() - Duration::days(30)];
+
+        let tags = ["rust".to_string()];
+
+        // The default threshold of 0 keeps every repo
+        assert_eq!(storage.search_by_tags(&tags, Some(0.0)).len(), 2);
+
+        storage.repos.get_mut(&stale).unwrap().access_times = vec![Utc::now() - Duration::days(30)];
+        assert_eq!(
+            storage.search_by_tags(&tags, Some(50.0)),
+            vec![active.clone()]
+        );
+
+        // Range queries also cap the score
+        let expr = SearchExpr {
+            include: tags.to_vec(),
+            max_score: Some(50.0),
+            ..Default::default()
+        };
+        storage.repos.get_mut(&stale).unwrap().access_times = vec![Utc::now() - Duration::days(30)];
+        assert_eq!(storage.search(&expr), vec![stale]);
+    }
+
+    #[test]
+    fn test_defragment() {
+        let mut storage = Storage::default();
+        for i in 0..100 {
+            storage
+                .repos
+                .insert(format!("/repos/{}", i), RepoAccess::new(vec![]));
+        }
+        storage.repos.retain(|path, _| path.ends_with('7'));
+        let capacity = storage.repos.capacity();
+
+        // An unsorted, overlong access history
+        let now = Utc::now();
+        let access = storage.repos.get_mut("/repos/7").unwrap();
+        access.access_times = (0..15)
+            .rev()
+            .map(|days| now - Duration::days(days))
+            .collect();
+
+        let result = storage.defragment();
+
+        assert!(storage.repos.capacity() < capacity);
+        assert!(result.bytes_freed > 0);
+        assert_eq!(result.access_times_trimmed, 5);
+
+        let times = &storage.repos["/repos/7"].access_times;
+        assert_eq!(times.len(), DEFAULT_MAX_ACCESS_HISTORY);
+        assert!(times.is_sorted());
+        assert_eq!(times.last(), Some(&now));
+
+        // Nothing left to clean up
+        assert_eq!(storage.defragment().access_times_trimmed, 0);
+    }
+
+    #[test]
+    fn test_defragment_if_sparse() {
+        let mut storage = Storage::default();
+        for i in 0..200 {
+            storage
+                .repos
+                .insert(format!("/repos/{}", i), RepoAccess::new(vec![]));
+        }
+        let capacity = storage.repos.capacity();
+
+        // Half full is not sparse
+        storage
+            .repos
+            .retain(|path, _| path.ends_with(['0', '2', '4', '6', '8']));
+        assert!(!storage.defragment_if_sparse());
+
+        storage.repos.retain(|path, _| path.ends_with('0'));
+        assert!(storage.defragment_if_sparse());
+        assert!(storage.repos.capacity() < capacity);
+
+        // Small maps are left alone however empty
+        let mut storage = Storage::default();
+        for i in 0..20 {
+            storage
+                .repos
+                .insert(format!("/repos/{}", i), RepoAccess::new(vec![]));
+        }
+        storage.repos.retain(|path, _| path == "/repos/0");
+        assert!(!storage.defragment_if_sparse());
+    }
+
+    #[test]
+    fn test_delete_repo_defragments_sparse_storage() {
+        let (config, temp_dir) = create_test_config();
+        let mut storage = Storage::new(&config).unwrap();
+        let mut paths = Vec::new();
+        for i in 0..100 {
+            let repo = create_fake_repo(&temp_dir.path().join(i.to_string()));
+            let path = repo.to_string_lossy().to_string();
+            storage.add_repo(&path, vec![]).unwrap();
+            paths.push(path);
+        }
+        let capacity = storage.repos.capacity();
+        // Defragmenting sorts access histories, so this one shows if it ran
+        let now = Utc::now();
+        let unsorted = vec![now, now - Duration::days(1)];
+        storage.repos.get_mut(&paths[1]).unwrap().access_times = unsorted.clone();
+
+        // A single delete doesn't defragment
+        storage.delete_repo(&paths[0]).unwrap();
+        assert_eq!(storage.repos[&paths[1]].access_times, unsorted);
+
+        for path in &paths[2..] {
+            storage.delete_repo(path).unwrap();
+        }
+
+        assert!(storage.repos.capacity() < capacity);
+        assert_eq!(storage.repos.len(), 1);
+    }
+
+    #[test]
+    fn test_repo_ids() {
+        let (config, temp_dir) = create_test_config();
+        let repo1 = create_fake_repo(&temp_dir.path().join("repo1"));
+        let repo2 = create_fake_repo(&temp_dir.path().join("repo2"));
+        let repo1 = repo1.to_string_lossy().to_string();
+        let repo2 = repo2.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&repo1, vec![]).unwrap();
+        storage.add_repo(&repo2, vec![]).unwrap();
+
+        let id1 = storage.repos[&repo1].repo_id.clone();
+        let id2 = storage.repos[&repo2].repo_id.clone();
+        assert_eq!(id1.len(), 8);
+        assert_ne!(id1, id2);
+
+        // Updating a repo keeps its ID
+        storage.add_repo(&repo1, vec!["rust".to_string()]).unwrap();
+        assert_eq!(storage.repos[&repo1].repo_id, id1);
+
+        // IDs survive a save and load, and can stand in for paths
+        storage.save(&config).unwrap();
+        let mut storage = Storage::new(&config).unwrap();
+        assert_eq!(
+            storage.find_by_id(&id2).map(|(path, _)| path),
+            Some(repo2.as_str())
+        );
+        assert_eq!(storage.resolve_path(&id1).unwrap(), repo1);
+        assert!(storage.record_access(&id1).unwrap());
+        assert!(storage.find_by_id("00000000").is_none());
+    }
+
+    #[test]
+    fn test_missing_repo_ids_are_assigned_on_load() {
+        let (config, temp_dir) = create_test_config();
+        let repo = create_fake_repo(temp_dir.path());
+        let repo = repo.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.repos.insert(repo.clone(), RepoAccess::new(vec![]));
+        storage.save(&config).unwrap();
+
+        let saved = fs::read_to_string(config.repos_path()).unwrap();
+
+        let storage = Storage::new(&config).unwrap();
+        let repo_id = storage.repos[&repo].repo_id.clone();
+        assert_eq!(repo_id.len(), 8);
+        // Loading doesn't write, the next load derives the same ID
+        assert_eq!(fs::read_to_string(config.repos_path()).unwrap(), saved);
+        let storage = Storage::new(&config).unwrap();
+        assert_eq!(storage.repos[&repo].repo_id, repo_id);
+
+        // And the next save keeps it
+        storage.save(&config).unwrap();
+        let storage = Storage::new(&config).unwrap();
+        assert_eq!(storage.repos[&repo].repo_id, repo_id);
+    }
+
+    #[test]
+    fn test_language_filter() {
+        let temp_dir = tempdir().unwrap();
+        let rust = create_fake_repo(&temp_dir.path().join("rust"));
+        let go = create_fake_repo(&temp_dir.path().join("go"));
+        let unknown = create_fake_repo(&temp_dir.path().join("unknown"));
+        fs::write(rust.join("Cargo.toml"), "").unwrap();
+        // A cached language counts even without build files
+        let mut go_access = RepoAccess::new(vec![]);
+        go_access.language = Some("Go".to_string());
+        let access = RepoAccess::new(vec![]);
+
+        let filter = |filter: LanguageFilter, repos: &[(&PathBuf, &RepoAccess)]| {
+            repos
+                .iter()
+                .filter(|(path, repo_access)| filter.matches(path.to_str().unwrap(), repo_access))
+                .count()
+        };
+
+        let all = [(&rust, &access), (&go, &go_access)];
+        let none = [(&unknown, &access)];
+        let some = [(&rust, &access), (&unknown, &access)];
+
+        for repos in [&all[..], &none[..], &some[..]] {
+            assert_eq!(filter(LanguageFilter::Any, repos), repos.len());
+        }
+        assert_eq!(filter(LanguageFilter::Known, &all), 2);
+        assert_eq!(filter(LanguageFilter::Unknown, &all), 0);
+        assert_eq!(filter(LanguageFilter::Known, &none), 0);
+        assert_eq!(filter(LanguageFilter::Unknown, &none), 1);
+        assert_eq!(filter(LanguageFilter::Known, &some), 1);
+        assert_eq!(filter(LanguageFilter::Unknown, &some), 1);
+    }
+
+    fn storage_with_priorities() -> Storage {
+        let mut storage = Storage::default();
+        for (path, priority) in [
+            ("/repos/none", None),
+            ("/repos/low", Some(Priority::Low)),
+            ("/repos/high", Some(Priority::High)),
+            ("/repos/medium", Some(Priority::Medium)),
+            ("/repos/high-busy", Some(Priority::High)),
+        ] {
+            let mut repo_access = RepoAccess::new(vec![]);
+            repo_access.priority = priority;
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+        storage
+            .repos
+            .get_mut("/repos/high-busy")
+            .unwrap()
+            .record_access(DEFAULT_MAX_ACCESS_HISTORY);
+        storage
+    }
+
+    #[test]
+    fn test_get_repos_by_priority() {
+        let storage = storage_with_priorities();
+        assert_eq!(
+            storage.get_repos_by_priority(),
+            vec![
+                "/repos/high-busy",
+                "/repos/high",
+                "/repos/medium",
+                "/repos/low",
+                "/repos/none"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_by_priority() {
+        let storage = storage_with_priorities();
+        let with_priority = |priority: Option<Priority>| {
+            storage.find_matching(|_, repo_access| repo_access.priority == priority)
+        };
+
+        assert_eq!(
+            with_priority(Some(Priority::High)),
+            vec!["/repos/high", "/repos/high-busy"]
+        );
+        assert_eq!(with_priority(Some(Priority::Medium)), vec!["/repos/medium"]);
+        assert_eq!(with_priority(Some(Priority::Low)), vec!["/repos/low"]);
+        assert_eq!(with_priority(None), vec!["/repos/none"]);
+    }
+
+    #[test]
+    fn test_recent_repos() {
+        let now = Utc::now();
+        let mut storage = Storage::default();
+        // Interleaved: "busy" has the most accesses, but "quiet" was touched last
+        for (path, hours_ago) in [
+            ("/repos/busy", &[1, 3, 5, 7][..]),
+            ("/repos/quiet", &[0, 48][..]),
+            ("/repos/middle", &[2, 4][..]),
+            ("/repos/never", &[][..]),
+        ] {
+            let mut repo_access = RepoAccess::new(vec![]);
+            repo_access.access_times = hours_ago
+                .iter()
+                .map(|hours| now - Duration::hours(*hours))
+                .collect();
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+
+        let recent = storage.recent_repos(10);
+        assert_eq!(
+            recent,
+            vec![
+                ("/repos/quiet".to_string(), now),
+                ("/repos/busy".to_string(), now - Duration::hours(1)),
+                ("/repos/middle".to_string(), now - Duration::hours(2)),
+            ]
+        );
+        assert_eq!(storage.recent_repos(1).len(), 1);
+    }
+
+    #[test]
+    fn test_top_repos() {
+        let mut storage = Storage::default();
+        for (path, accesses) in [("/repos/once", 1), ("/repos/often", 5), ("/repos/never", 0)] {
+            let mut repo_access = RepoAccess::new(vec!["rust".to_string()]);
+            repo_access.access_times.clear();
+            for _ in 0..accesses {
+                repo_access.record_access(DEFAULT_MAX_ACCESS_HISTORY);
+            }
+            storage.repos.insert(path.to_string(), repo_access);
+        }
+
+        let top = storage.top_repos(2);
+        assert_eq!(
+            top,
+            vec![
+                ("/repos/often".to_string(), 500.0),
+                ("/repos/once".to_string(), 100.0)
+            ]
+        );
+        assert_eq!(storage.top_repos(10).len(), 3);
+        assert!(storage.top_repos(0).is_empty());
+    }
+
+    #[test]
+    fn test_frecency_weights() {
+        let mut storage = Storage::default();
+        // Accessed once an hour ago versus three times a few days ago
+        let mut recent = RepoAccess::new(vec![]);
+        recent.access_times = vec![Utc::now() - Duration::hours(2)];
+        let mut frequent = RepoAccess::new(vec![]);
+        frequent.access_times = vec![Utc::now() - Duration::days(3); 3];
+        storage.repos.insert("/repos/recent".to_string(), recent);
+        storage
+            .repos
+            .insert("/repos/frequent".to_string(), frequent);
+
+        // 40 versus 3 * 20
+        assert_eq!(
+            storage.get_repos_by_frecency(),
+            vec!["/repos/frequent", "/repos/recent"]
+        );
+
+        let config = Config {
+            frecency: FrecencyConfig {
+                weight_day: 100.0,
+                ..FrecencyConfig::default()
+            },
+            ..Config::default()
+        };
+        storage.apply_config(&config);
+        assert_eq!(storage.frecency(&storage.repos["/repos/recent"]), 100.0);
+        assert_eq!(
+            storage.get_repos_by_frecency(),
+            vec!["/repos/recent", "/repos/frequent"]
+        );
+    }
+
+    #[test]
+    fn test_frecency_weight_by_priority() {
+        let mut storage = storage_with_priorities();
+        let score = |storage: &Storage, path: &str| storage.frecency(&storage.repos[path]);
+
+        // Off by default
+        assert_eq!(score(&storage, "/repos/high"), 100.0);
+        assert_eq!(score(&storage, "/repos/low"), 100.0);
+
+        storage.weight_by_priority = true;
+        assert_eq!(score(&storage, "/repos/high"), 200.0);
+        assert_eq!(score(&storage, "/repos/medium"), 100.0);
+        assert_eq!(score(&storage, "/repos/none"), 100.0);
+        assert_eq!(score(&storage, "/repos/low"), 50.0);
+    }
+
+    #[test]
+    fn test_find_name_conflicts() {
+        let (config, temp_dir) = create_test_config();
+        let work = create_fake_repo(&temp_dir.path().join("work"));
+        let personal = create_fake_repo(&temp_dir.path().join("personal"));
+        let other = temp_dir.path().join("other");
+        fs::create_dir_all(&other).unwrap();
+        let work = work.to_string_lossy().to_string();
+        let personal = personal.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&work, vec![]).unwrap();
+        storage.add_repo(&personal, vec![]).unwrap();
+        storage.add_repo(other.to_str().unwrap(), vec![]).unwrap();
+
+        // Both are called `fake_repo`
+        let conflicts = storage.find_name_conflicts();
+        assert_eq!(
+            conflicts,
+            vec![NameConflict {
+                name: "fake_repo".to_string(),
+                paths: vec![personal.clone(), work.clone()],
+            }]
+        );
+
+        // A name already in use is refused
+        assert!(storage.set_repo_name(&work, "other").is_err());
+        assert!(storage.set_repo_name(&work, "fake_repo").is_err());
+
+        storage.set_repo_name(&work, "work-project").unwrap();
+        assert!(storage.find_name_conflicts().is_empty());
+        assert_eq!(storage.repos[&work].display_name(&work), "work-project");
+    }
+
+    #[test]
+    fn test_rename_repo() {
+        let (config, temp_dir) = create_test_config();
+        let work = create_fake_repo(&temp_dir.path().join("work"));
+        let personal = create_fake_repo(&temp_dir.path().join("personal"));
+        let work = work.to_string_lossy().to_string();
+        let personal = personal.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage.add_repo(&work, vec![]).unwrap();
+        storage.add_repo(&personal, vec![]).unwrap();
+
+        // Both are called `fake_repo`
+        assert!(storage.rename_repo("fake_repo", "site").is_err());
+        assert!(!storage.rename_repo("missing", "site").unwrap());
+
+        assert!(storage.rename_repo(&work, "site").unwrap());
+        assert!(storage.rename_repo("site", "blog").unwrap());
+        assert_eq!(storage.repos[&work].display_name(&work), "blog");
+        assert!(!storage.rename_repo("site", "other").unwrap());
+        assert_eq!(storage.resolve_repo("blog").unwrap(), work);
+        assert_eq!(storage.resolve_repo("fake_repo").unwrap(), personal);
+
+        // Taken by the other repo
+        assert!(storage.rename_repo("blog", "fake_repo").is_err());
+        assert_eq!(storage.repos[&work].display_name(&work), "blog");
+        let mut paths: Vec<&String> = storage.repos.keys().collect();
+        paths.sort();
+        let mut expected = vec![&personal, &work];
+        expected.sort();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_apply_tag_files() {
+        let (config, temp_dir) = create_test_config();
+        let tagged = create_fake_repo(&temp_dir.path().join("tagged"));
+        let untagged = create_fake_repo(&temp_dir.path().join("untagged"));
+        fs::write(tagged.join(".mangit-tags"), "rust, cli\n").unwrap();
+
+        let tagged = tagged.to_string_lossy().to_string();
+        let untagged = untagged.to_string_lossy().to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(&tagged, vec!["work".to_string(), "rust".to_string()])
+            .unwrap();
+        storage
+            .add_repo(&untagged, vec!["notes".to_string()])
+            .unwrap();
+
+        let report = storage.apply_tag_files(false);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.skipped, 1);
+        assert!(report.errors.is_empty());
+        assert_eq!(storage.repos[&tagged].tags, vec!["work", "rust", "cli"]);
+        assert_eq!(storage.repos[&untagged].tags, vec!["notes"]);
+
+        let report = storage.apply_tag_files(true);
+        assert_eq!(report.updated, 1);
+        assert_eq!(storage.repos[&tagged].tags, vec!["rust", "cli"]);
+    }
+
+    #[test]
+    fn test_canonicalize_paths() {
+        let (config, temp_dir) = create_test_config();
+        let repo = create_fake_repo(temp_dir.path());
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&repo, &link).unwrap();
+        let other = create_fake_repo(&temp_dir.path().join("other"));
+
+        // The temp dir itself may sit behind a symlink (e.g. /var on macOS)
+        let canonical = fs::canonicalize(&repo)
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let other = fs::canonicalize(&other)
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        let mut storage = Storage::new(&config).unwrap();
+        storage
+            .add_repo(link.to_str().unwrap(), vec!["rust".to_string()])
+            .unwrap();
+        storage.add_repo(&other, vec![]).unwrap();
+
+        let result = storage.canonicalize_paths().unwrap();
+
+        assert_eq!(
+            result.changed,
+            vec![(link.to_string_lossy().to_string(), canonical.clone())]
+        );
+        assert_eq!(result.unchanged, 1);
+        assert!(result.failed.is_empty());
+        assert_eq!(storage.repos[&canonical].tags, vec!["rust"]);
+        assert!(!storage.repos.contains_key(link.to_str().unwrap()));
+
+        // Adding the symlink again and normalizing merges it into the same repo
+        storage
+            .add_repo(link.to_str().unwrap(), vec!["cli".to_string()])
+            .unwrap();
+        let result = storage.canonicalize_paths().unwrap();
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(storage.repos.len(), 2);
+        assert_eq!(storage.repos[&canonical].tags, vec!["rust", "cli"]);
+        assert_eq!(storage.repos[&canonical].access_times.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_keeps_metadata() {
+        let mut kept = RepoAccess::new(vec!["rust".to_string()]);
+        kept.repo_id = "aaaaaaaa".to_string();
+        kept.name = Some("kept".to_string());
+        kept.priority = Some(Priority::High);
+
+        let mut other = RepoAccess::new(vec!["cli".to_string()]);
+        other.repo_id = "bbbbbbbb".to_string();
+        other.name = Some("other".to_string());
+        other.priority = Some(Priority::Low);
+        other.description = "From the other entry".to_string();
+        other.project_url = Some("https://tracker.example.com/1".to_string());
+        other.remote_url = Some("git@example.com:me/repo.git".to_string());
+        other.note = Some("ask before force pushing".to_string());
+        other.archived = true;
+
+        kept.merge(other, 10);
+        assert_eq!(kept.tags, vec!["rust", "cli"]);
+        assert_eq!(kept.access_times.len(), 2);
+        // Set on both, this entry wins
+        assert_eq!(kept.repo_id, "aaaaaaaa");
+        assert_eq!(kept.name.as_deref(), Some("kept"));
+        assert_eq!(kept.priority, Some(Priority::High));
+        // Only set on the other entry
+        assert_eq!(kept.description, "From the other entry");
+        assert_eq!(
+            kept.project_url.as_deref(),
+            Some("https://tracker.example.com/1")
+        );
+        assert_eq!(
+            kept.remote_url.as_deref(),
+            Some("git@example.com:me/repo.git")
+        );
+        assert_eq!(kept.note.as_deref(), Some("ask before force pushing"));
+        assert!(kept.archived);
+    }
+
+    #[test]
+    fn test_classify_activity() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let added = now - Duration::days(400);
+        let tier = |last_access: DateTime<Utc>| classify_activity_at(&[added, last_access], now);
+
+        assert_eq!(tier(now), ActivityTier::Daily);
+        assert_eq!(tier(now - Duration::days(1)), ActivityTier::Daily);
+        assert_eq!(
+            tier(now - Duration::days(1) - Duration::seconds(1)),
+            ActivityTier::Weekly
+        );
+        assert_eq!(tier(now - Duration::days(7)), ActivityTier::Weekly);
+        assert_eq!(tier(now - Duration::days(8)), ActivityTier::Monthly);
+        assert_eq!(tier(now - Duration::days(30)), ActivityTier::Monthly);
+        assert_eq!(tier(now - Duration::days(31)), ActivityTier::Rare);
+        assert_eq!(tier(now - Duration::days(365)), ActivityTier::Rare);
+
+        // Only the access from adding the repo, however recent
+        assert_eq!(classify_activity_at(&[now], now), ActivityTier::Never);
+        assert_eq!(classify_activity_at(&[], now), ActivityTier::Never);
+    }
+
+    #[test]
+    fn test_tag_vocabulary_consistency() {
+        let mut storage = Storage::default();
+        storage.repos.insert(
+            "/repos/mangit".to_string(),
+            RepoAccess::new(vec!["rust".to_string(), "cli".to_string()]),
+        );
+
+        // No vocabulary, nothing to compare against
+        let vocab = TagVocabulary::default();
+        assert!(storage.get_orphaned_tag_defs(&vocab).is_empty());
+        assert!(storage.validate_tag_consistency(&vocab).is_empty());
+
+        let vocab =
+            TagVocabulary::parse("rust: Rust projects\nweb: Web apps\ngo: Go projects").unwrap();
+        assert_eq!(storage.get_orphaned_tag_defs(&vocab), vec!["go", "web"]);
+        assert_eq!(storage.validate_tag_consistency(&vocab), vec!["cli"]);
+    }
+
+    #[test]
+    fn test_rebuild_from_paths() {
+        let temp_dir = tempdir().unwrap();
+        let first = create_fake_repo(temp_dir.path());
+        let second = temp_dir.path().join("second");
+        fs::create_dir_all(second.join(".git")).unwrap();
+        let paths = [first.to_str().unwrap(), second.to_str().unwrap()];
+
+        let storage = Storage::rebuild_from_paths(&paths).unwrap();
+        assert_eq!(storage.repos.len(), 2);
+        for path in paths {
+            let repo_access = &storage.repos[path];
+            assert!(repo_access.tags.is_empty());
+            assert_eq!(repo_access.access_times.len(), 1);
+            assert_eq!(repo_access.repo_id.len(), 8);
+        }
+        assert_ne!(
+            storage.repos[paths[0]].repo_id,
+            storage.repos[paths[1]].repo_id
+        );
+
+        let missing = temp_dir.path().join("missing");
+        assert!(Storage::rebuild_from_paths(&[missing.to_str().unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_cached_ci_status() {
+        let mut repo_access = RepoAccess::new(vec![]);
+        assert_eq!(repo_access.cached_ci_status(), None);
+
+        repo_access.ci_status = Some(CiStatus::Passed);
+        repo_access.last_ci_check = Some(Utc::now() - Duration::minutes(1));
+        assert_eq!(repo_access.cached_ci_status(), Some(CiStatus::Passed));
+
+        repo_access.last_ci_check = Some(Utc::now() - Duration::minutes(10));
+        assert_eq!(repo_access.cached_ci_status(), None);
+    }
+
+    #[test]
+    fn test_full_refresh() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args([
+                    "-C",
+                    path,
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                ])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "--quiet", "--initial-branch=main"]);
+        git(&[
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/tizee/mangit.git",
+        ]);
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(temp_dir.path().join(".mangit-tags"), "rust, cli\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+
+        let mut repo_access = RepoAccess::new(vec!["cli".to_string()]);
+        repo_access.full_refresh(path).unwrap();
+        assert_eq!(repo_access.language.as_deref(), Some("Rust"));
+        assert_eq!(
+            repo_access.remote_url.as_deref(),
+            Some("https://github.com/tizee/mangit.git")
+        );
+        assert_eq!(repo_access.git_branch.as_deref(), Some("main"));
+        assert!(repo_access.last_commit_date.is_some());
+        assert_eq!(repo_access.tags, vec!["cli", "rust"]);
+        assert_eq!(repo_access.access_times.len(), 1);
+
+        // Without a manifest the language can't be detected and is kept
+        git(&["rm", "--quiet", "Cargo.toml"]);
+        repo_access.full_refresh(path).unwrap();
+        assert_eq!(repo_access.language.as_deref(), Some("Rust"));
+
+        let not_a_repo = tempdir().unwrap();
+        assert!(
+            repo_access
+                .full_refresh(not_a_repo.path().to_str().unwrap())
+                .is_err()
+        );
+    }
 }
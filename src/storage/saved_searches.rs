@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::Config;
+
+use super::SearchExpr;
+use crate::repository::TagRequirement;
+
+/// Search parameters stored with `search --save-as`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub tags: Vec<String>,
+    // Searches saved before requirements existed have `"or": true` or `false`
+    #[serde(default, alias = "or", deserialize_with = "requirement_or_flag")]
+    pub requirement: TagRequirement,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl SavedSearch {
+    /// Builds the tag part of the search, other filters keep their defaults
+    pub fn to_expr(&self) -> SearchExpr {
+        SearchExpr {
+            include: self.tags.clone(),
+            exclude: self.exclude.clone(),
+            requirement: self.requirement,
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads a requirement, or the `or` flag older saved searches stored instead
+fn requirement_or_flag<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<TagRequirement, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RequirementOrFlag {
+        Requirement(TagRequirement),
+        Or(bool),
+    }
+
+    Ok(match RequirementOrFlag::deserialize(deserializer)? {
+        RequirementOrFlag::Requirement(requirement) => requirement,
+        RequirementOrFlag::Or(true) => TagRequirement::Any,
+        RequirementOrFlag::Or(false) => TagRequirement::All,
+    })
+}
+
+/// Saved searches by name
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SavedSearches {
+    pub searches: BTreeMap<String, SavedSearch>,
+}
+
+impl SavedSearches {
+    /// Loads the saved searches from disk, empty if none were saved yet
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = config.saved_searches_path();
+        if !path.exists() {
+            return Ok(SavedSearches::default());
+        }
+
+        let data = fs::read_to_string(&path).context("Failed to read saved searches")?;
+        serde_json::from_str(&data).context("Failed to parse saved searches")
+    }
+
+    /// Saves the searches to disk
+    pub fn save(&self, config: &Config) -> Result<()> {
+        config.ensure_mangit_dir()?;
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize saved searches")?;
+        fs::write(config.saved_searches_path(), json).context("Failed to write saved searches")?;
+        Ok(())
+    }
+
+    /// Adds a search, replacing any saved under the same name. Returns true if one was replaced
+    pub fn insert(&mut self, search: SavedSearch) -> bool {
+        self.searches.insert(search.name.clone(), search).is_some()
+    }
+
+    /// Removes a search, returns true if it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.searches.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests_saved_searches {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn search(name: &str, tags: &[&str]) -> SavedSearch {
+        SavedSearch {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            requirement: TagRequirement::All,
+            exclude: vec!["archived".to_string()],
+            limit: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config {
+            mangit_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+        assert_eq!(
+            SavedSearches::load(&config).unwrap(),
+            SavedSearches::default()
+        );
+
+        let mut searches = SavedSearches::default();
+        assert!(!searches.insert(search("my-cli-tools", &["rust", "cli"])));
+        assert!(searches.insert(search("my-cli-tools", &["rust", "cli", "tool"])));
+        searches.insert(search("web", &["web"]));
+        searches.save(&config).unwrap();
+
+        let loaded = SavedSearches::load(&config).unwrap();
+        assert_eq!(loaded, searches);
+        assert_eq!(
+            loaded.get("my-cli-tools").unwrap().tags,
+            vec!["rust", "cli", "tool"]
+        );
+
+        // Older files without the optional fields still load
+        let minimal: SavedSearch =
+            serde_json::from_str(r#"{"name": "x", "tags": ["rust"]}"#).unwrap();
+        assert_eq!(minimal.limit, None);
+        assert_eq!(minimal.requirement, TagRequirement::All);
+
+        // So do ones that stored `or` instead of the requirement
+        for (json, requirement) in [
+            (
+                r#"{"name": "x", "tags": [], "or": true}"#,
+                TagRequirement::Any,
+            ),
+            (
+                r#"{"name": "x", "tags": [], "or": false}"#,
+                TagRequirement::All,
+            ),
+            (
+                r#"{"name": "x", "tags": [], "requirement": "exact"}"#,
+                TagRequirement::Exact,
+            ),
+        ] {
+            let saved: SavedSearch = serde_json::from_str(json).unwrap();
+            assert_eq!(saved.requirement, requirement);
+        }
+        let json = serde_json::to_string(&search("x", &[])).unwrap();
+        assert!(json.contains(r#""requirement":"all""#));
+        assert!(!json.contains(r#""or""#));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut searches = SavedSearches::default();
+        searches.insert(search("web", &["web"]));
+        assert!(searches.remove("web"));
+        assert!(!searches.remove("web"));
+        assert!(searches.get("web").is_none());
+    }
+
+    #[test]
+    fn test_replay_expr() {
+        let mut saved = search("cli", &["rust", "cli"]);
+        let expr = saved.to_expr();
+        assert_eq!(expr.requirement, TagRequirement::All);
+        assert!(expr.matches(&["rust".to_string(), "cli".to_string()]));
+        assert!(!expr.matches(&["rust".to_string()]));
+        assert!(!expr.matches(&[
+            "rust".to_string(),
+            "cli".to_string(),
+            "archived".to_string()
+        ]));
+
+        saved.requirement = TagRequirement::Any;
+        assert!(saved.to_expr().matches(&["rust".to_string()]));
+
+        saved.requirement = TagRequirement::Exact;
+        assert!(!saved.to_expr().matches(&["rust".to_string()]));
+        assert!(!saved.to_expr().matches(&[
+            "rust".to_string(),
+            "cli".to_string(),
+            "web".to_string()
+        ]));
+        assert!(
+            saved
+                .to_expr()
+                .matches(&["cli".to_string(), "rust".to_string()])
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+use crate::config::Config;
+
+/// Known tags and their human readable descriptions
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct TagVocabulary {
+    pub tags: HashMap<String, String>,
+}
+
+impl TagVocabulary {
+    /// Loads the vocabulary from disk, empty if it doesn't exist yet
+    pub fn load(config: &Config) -> Result<Self> {
+        let vocab_path = config.tag_vocab_path();
+        if !vocab_path.exists() {
+            return Ok(TagVocabulary::default());
+        }
+
+        let data = fs::read_to_string(&vocab_path).context("Failed to read tag vocabulary")?;
+        serde_json::from_str(&data).context("Failed to parse tag vocabulary")
+    }
+
+    /// Saves the vocabulary to disk
+    pub fn save(&self, config: &Config) -> Result<()> {
+        config.ensure_mangit_dir()?;
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize tag vocabulary")?;
+        fs::write(config.tag_vocab_path(), json).context("Failed to write tag vocabulary")?;
+        Ok(())
+    }
+
+    /// Parses a vocabulary file with one `tag_name: description` per line.
+    /// Blank lines and lines starting with `#` are ignored
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut tags = HashMap::new();
+
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (tag, description) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Line {}: expected `tag: description`", line_number + 1))?;
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return Err(anyhow!("Line {}: tag name is empty", line_number + 1));
+            }
+
+            tags.insert(tag.to_string(), description.trim().to_string());
+        }
+
+        Ok(TagVocabulary { tags })
+    }
+
+    /// Adds or replaces definitions from another vocabulary, returns how many were merged
+    pub fn merge(&mut self, other: TagVocabulary) -> usize {
+        let count = other.tags.len();
+        self.tags.extend(other.tags);
+        count
+    }
+
+    /// Removes tag definitions, returns how many existed
+    pub fn remove(&mut self, tags: &[String]) -> usize {
+        tags.iter()
+            .filter(|tag| self.tags.remove(*tag).is_some())
+            .count()
+    }
+
+    /// Returns the description of a tag
+    pub fn describe(&self, tag: &str) -> Option<&str> {
+        self.tags.get(tag).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests_vocab {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_vocab() {
+        let vocab = TagVocabulary::parse(
+            "# Tag vocabulary\n\nrust: Projects written in Rust\ncli:Command line tools\n  web : Things with a browser: UI  \n",
+        )
+        .unwrap();
+
+        assert_eq!(vocab.tags.len(), 3);
+        assert_eq!(vocab.describe("rust"), Some("Projects written in Rust"));
+        assert_eq!(vocab.describe("cli"), Some("Command line tools"));
+        assert_eq!(vocab.describe("web"), Some("Things with a browser: UI"));
+        assert_eq!(vocab.describe("python"), None);
+    }
+
+    #[test]
+    fn test_parse_vocab_invalid_lines() {
+        assert!(TagVocabulary::parse("rust: ok\nmissing separator").is_err());
+        assert!(TagVocabulary::parse(": no tag").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_vocab() {
+        let temp_dir = tempdir().unwrap();
+        let config = Config {
+            mangit_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            TagVocabulary::load(&config).unwrap(),
+            TagVocabulary::default()
+        );
+
+        let mut vocab = TagVocabulary::parse("rust: Rust projects").unwrap();
+        let merged = vocab.merge(TagVocabulary::parse("rust: Rust code\ncli: Tools").unwrap());
+        assert_eq!(merged, 2);
+        vocab.save(&config).unwrap();
+
+        let loaded = TagVocabulary::load(&config).unwrap();
+        assert_eq!(loaded, vocab);
+        assert_eq!(loaded.describe("rust"), Some("Rust code"));
+    }
+}
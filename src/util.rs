@@ -0,0 +1,605 @@
+pub mod ci;
+pub mod edit_distance;
+pub mod expr;
+pub mod fzf;
+pub mod git;
+pub mod project_url;
+pub mod random;
+pub mod rofi;
+pub mod template;
+pub mod tmux;
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+use crate::config::{PriorityRule, is_git_repo};
+use crate::repository::matches_path_pattern;
+use crate::storage::Priority;
+
+/// Checks whether `path` is `dir` or lies somewhere below it
+pub fn is_under_dir(path: &str, dir: &str) -> bool {
+    Path::new(path).starts_with(dir)
+}
+
+/// Returns the priority of the first rule whose pattern matches the path. `*` in
+/// a pattern stands for any run of characters
+pub fn match_priority_rules(path: &str, rules: &[PriorityRule]) -> Option<Priority> {
+    rules
+        .iter()
+        .find(|rule| matches_path_pattern(path, &rule.path_pattern))
+        .map(|rule| rule.priority)
+}
+
+/// Derives a repo name from a remote URL, e.g.
+/// `git@github.com:user/my-project.git` -> `my-project`. Local paths like
+/// `/srv/git/my-project.git` work too
+pub fn infer_name_from_remote_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let path = match url.split_once("://") {
+        Some((scheme, rest)) if !scheme.is_empty() => rest.split_once('/')?.1,
+        Some(_) => return None,
+        None if url.starts_with(['/', '.', '~']) => url,
+        // scp-like syntax: [user@]host:path
+        None => url.split_once(':')?.1,
+    };
+
+    let last = path.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = last.strip_suffix(".git").unwrap_or(last);
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Resolves `.` and `..` components without touching the file system, so
+/// `/code/./api` and `/code/tools/../api` both become `/code/api`
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            // `..` of the root is the root itself
+            Component::ParentDir if normalized.has_root() => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Checks whether `target` is `base` or lies at most `max_depth` directory levels
+/// below it
+pub fn is_within_depth(base: &Path, target: &Path, max_depth: usize) -> bool {
+    target
+        .strip_prefix(base)
+        .is_ok_and(|relative| relative.components().count() <= max_depth)
+}
+
+/// Finds the git repos in `dir` and its subdirectories, at most `max_depth` levels
+/// down. Repos aren't searched for nested repos, hidden directories are skipped
+pub fn find_git_repos(dir: &Path, max_depth: usize) -> Vec<PathBuf> {
+    scan_git_repos(dir, max_depth).repos
+}
+
+/// Git repos found below a directory, see `scan_git_repos`
+#[derive(Debug, Default, PartialEq)]
+pub struct RepoScan {
+    // Sorted by path
+    pub repos: Vec<PathBuf>,
+    // Directories looked at that aren't git repos, not counting the scanned one
+    pub other_dirs: usize,
+}
+
+/// Like `find_git_repos`, but also counts the directories that were looked at
+/// and turned out not to be git repos
+pub fn scan_git_repos(dir: &Path, max_depth: usize) -> RepoScan {
+    let mut scan = RepoScan::default();
+    if is_git_repo(dir) {
+        scan.repos.push(dir.to_path_buf());
+    } else {
+        scan_subdirs(dir, max_depth, &mut scan);
+    }
+    scan
+}
+
+fn scan_subdirs(dir: &Path, max_depth: usize, scan: &mut RepoScan) {
+    if max_depth == 0 {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut subdirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+
+    for subdir in subdirs {
+        if is_git_repo(&subdir) {
+            scan.repos.push(subdir);
+        } else {
+            scan.other_dirs += 1;
+            scan_subdirs(&subdir, max_depth - 1, scan);
+        }
+    }
+}
+
+/// Describes how long ago `time` was, e.g. `5m ago` or `3d ago`
+pub fn format_time_ago(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now.signed_duration_since(time);
+    if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed.num_days() < 60 {
+        format!("{}d ago", elapsed.num_days())
+    } else if elapsed.num_days() < 730 {
+        format!("{}mo ago", elapsed.num_days() / 30)
+    } else {
+        format!("{}y ago", elapsed.num_days() / 365)
+    }
+}
+
+/// Parses a duration like `90m`, `12h`, `30d`, `2w`, `6mo` or `1y`. Months count
+/// as 30 days and years as 365
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("Missing unit in duration {:?}, e.g. 30d or 1y", s))?;
+    let (amount, unit) = s.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("Invalid duration {:?}", s))?;
+
+    let days = |per_unit: i64| amount.checked_mul(per_unit).and_then(Duration::try_days);
+    let duration = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        "mo" => days(30),
+        "y" => days(365),
+        _ => {
+            return Err(anyhow!(
+                "Unknown unit {:?} in duration {:?}, expected m, h, d, w, mo or y",
+                unit,
+                s
+            ));
+        }
+    };
+    duration.ok_or_else(|| anyhow!("Duration {:?} is too long", s))
+}
+
+/// Opens a URL in the default browser
+pub fn open_url(url: &str) -> Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let status = Command::new(program)
+        .arg(url)
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        return Err(anyhow!("{} failed to open {}", program, url));
+    }
+    Ok(())
+}
+
+/// Starts `$SHELL` (or `/bin/sh`) in `dir` and waits for it to exit
+pub fn open_shell_in(dir: &str) -> Result<()> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    let status = Command::new(&shell)
+        .current_dir(dir)
+        .status()
+        .with_context(|| format!("Failed to run {}", shell))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", shell, status));
+    }
+    Ok(())
+}
+
+/// Finds an executable in the directories of `$PATH`
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Scales scores to 0-100 as a percentage of the highest one. All scores are 0
+/// when none is positive
+pub fn normalize_scores(scores: &[f64]) -> Vec<f64> {
+    let max = scores.iter().copied().fold(0.0, f64::max);
+    scores
+        .iter()
+        .map(|score| if max > 0.0 { score / max * 100.0 } else { 0.0 })
+        .collect()
+}
+
+// Editors offered by `config set-editor` after $EDITOR and $VISUAL, in this order
+const KNOWN_EDITORS: [&str; 5] = ["nvim", "vim", "nano", "code", "emacs"];
+
+/// Returns $EDITOR, $VISUAL and the known editors found in PATH, without duplicates
+pub fn find_available_editors() -> Vec<String> {
+    list_editors(
+        env::var("EDITOR").ok(),
+        env::var("VISUAL").ok(),
+        |program| find_in_path(program).is_some(),
+    )
+}
+
+/// The editor to open repos with: `default_editor` from the config, else $EDITOR,
+/// else $VISUAL
+pub fn resolve_editor(default_editor: Option<&str>) -> Option<String> {
+    pick_editor(
+        default_editor,
+        env::var("EDITOR").ok(),
+        env::var("VISUAL").ok(),
+    )
+}
+
+fn pick_editor(
+    default_editor: Option<&str>,
+    editor: Option<String>,
+    visual: Option<String>,
+) -> Option<String> {
+    default_editor
+        .map(str::to_string)
+        .into_iter()
+        .chain(editor)
+        .chain(visual)
+        .map(|candidate| candidate.trim().to_string())
+        .find(|candidate| !candidate.is_empty())
+}
+
+/// Opens `path` with an editor command such as `nvim` or `code -w`
+pub fn open_in_editor(editor: &str, path: &str) -> Result<()> {
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| anyhow!("The editor command is empty"))?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run {}", editor))?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {}", editor, status));
+    }
+    Ok(())
+}
+
+fn list_editors(
+    editor: Option<String>,
+    visual: Option<String>,
+    is_installed: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let known = KNOWN_EDITORS
+        .iter()
+        .filter(|program| is_installed(program))
+        .map(|program| program.to_string());
+
+    let mut editors: Vec<String> = Vec::new();
+    for candidate in editor.into_iter().chain(visual).chain(known) {
+        let candidate = candidate.trim().to_string();
+        if !candidate.is_empty() && !editors.contains(&candidate) {
+            editors.push(candidate);
+        }
+    }
+    editors
+}
+
+/// Applies `f` to every item using up to `threads` worker threads.
+/// Results are returned in the same order as the input
+pub fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let chunk_size = items.len().div_ceil(threads.max(1));
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+/// Writes an executable shell script that stands in for an external tool in
+/// tests. A separate `sh` writes it, because a file this process holds open for
+/// writing can leak into a command another test thread is spawning, and running
+/// the script then fails with "Text file busy"
+#[cfg(all(test, unix))]
+pub(crate) fn write_fake_tool(path: &Path, script: &str) {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(r#"printf '#!/bin/sh\n%s\n' "$2" > "$1.new" && chmod 755 "$1.new" && mv -f "$1.new" "$1""#)
+        .arg("sh")
+        .arg(path)
+        .arg(script)
+        .status()
+        .expect("Failed to run sh");
+    assert!(status.success(), "Failed to write {}", path.display());
+}
+
+#[cfg(test)]
+mod tests_util {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        let normalize = |path: &str| normalize_path(Path::new(path));
+        assert_eq!(normalize("/code/./api"), PathBuf::from("/code/api"));
+        assert_eq!(normalize("/code/tools/../api/"), PathBuf::from("/code/api"));
+        assert_eq!(normalize("/../code"), PathBuf::from("/code"));
+        assert_eq!(normalize("./a/../../b"), PathBuf::from("../b"));
+    }
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<usize> = (0..10).collect();
+        for threads in [0, 1, 3, 16] {
+            let doubled = parallel_map(&items, threads, |n| n * 2);
+            assert_eq!(doubled, (0..10).map(|n| n * 2).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_list_editors_order() {
+        let installed = |program: &str| ["vim", "code", "emacs"].contains(&program);
+        assert_eq!(
+            list_editors(Some("hx".to_string()), Some("vim".to_string()), installed),
+            vec!["hx", "vim", "code", "emacs"]
+        );
+        assert_eq!(
+            list_editors(None, Some(" ".to_string()), |_| true),
+            vec!["nvim", "vim", "nano", "code", "emacs"]
+        );
+        assert!(list_editors(None, None, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_pick_editor_order() {
+        let env = |value: &str| Some(value.to_string());
+        assert_eq!(
+            pick_editor(Some("code -w"), env("vim"), env("nano")).as_deref(),
+            Some("code -w")
+        );
+        assert_eq!(
+            pick_editor(None, env("vim"), env("nano")).as_deref(),
+            Some("vim")
+        );
+        assert_eq!(
+            pick_editor(Some(" "), env(""), env("nano")).as_deref(),
+            Some("nano")
+        );
+        assert_eq!(pick_editor(None, None, None), None);
+    }
+
+    #[test]
+    fn test_normalize_scores() {
+        assert_eq!(
+            normalize_scores(&[40.0, 0.0, 10.0, 30.0]),
+            vec![100.0, 0.0, 25.0, 75.0]
+        );
+        assert_eq!(normalize_scores(&[0.0, 0.0]), vec![0.0, 0.0]);
+        assert!(normalize_scores(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_match_priority_rules() {
+        let rule = |pattern: &str, priority| PriorityRule {
+            path_pattern: pattern.to_string(),
+            priority,
+        };
+        let rules = vec![
+            rule("*/work/archive/*", Priority::Low),
+            rule("*/work/*", Priority::High),
+            rule("/home/me/*", Priority::Medium),
+        ];
+
+        // Earlier rules win over later ones that also match
+        assert_eq!(
+            match_priority_rules("/home/me/work/archive/old", &rules),
+            Some(Priority::Low)
+        );
+        assert_eq!(
+            match_priority_rules("/home/me/work/api", &rules),
+            Some(Priority::High)
+        );
+        assert_eq!(
+            match_priority_rules("/home/me/blog", &rules),
+            Some(Priority::Medium)
+        );
+        assert_eq!(match_priority_rules("/tmp/scratch", &rules), None);
+        assert_eq!(match_priority_rules("/home/me/work/api", &[]), None);
+    }
+
+    #[test]
+    fn test_is_under_dir() {
+        assert!(is_under_dir(
+            "/home/me/projects/mangit",
+            "/home/me/projects"
+        ));
+        assert!(is_under_dir("/home/me/projects/a/b", "/home/me/projects/"));
+        assert!(is_under_dir("/home/me/projects", "/home/me/projects"));
+        assert!(!is_under_dir(
+            "/home/me/projects2/mangit",
+            "/home/me/projects"
+        ));
+        assert!(!is_under_dir("/tmp/mangit", "/home/me/projects"));
+    }
+
+    #[test]
+    fn test_is_within_depth() {
+        let base = Path::new("/home/user/work");
+        assert!(is_within_depth(base, Path::new("/home/user/work"), 0));
+        assert!(is_within_depth(
+            base,
+            Path::new("/home/user/work/mangit"),
+            1
+        ));
+        assert!(is_within_depth(
+            base,
+            Path::new("/home/user/work/a/mangit"),
+            2
+        ));
+        assert!(is_within_depth(
+            base,
+            Path::new("/home/user/work/a/mangit/"),
+            2
+        ));
+        assert!(!is_within_depth(
+            base,
+            Path::new("/home/user/work/a/b/mangit"),
+            2
+        ));
+        assert!(!is_within_depth(
+            base,
+            Path::new("/home/user/work2/mangit"),
+            2
+        ));
+        assert!(!is_within_depth(base, Path::new("/home/user"), 2));
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        assert!(find_in_path("sh").is_some());
+        assert!(find_in_path("surely-not-an-installed-program").is_none());
+    }
+
+    #[test]
+    fn test_parallel_map_empty() {
+        let items: Vec<usize> = Vec::new();
+        assert!(parallel_map(&items, 4, |n| *n).is_empty());
+    }
+
+    #[test]
+    fn test_infer_name_from_remote_url() {
+        let cases = [
+            ("https://github.com/user/my-project.git", Some("my-project")),
+            ("https://github.com/user/my-project/", Some("my-project")),
+            ("git@github.com:user/my-project.git", Some("my-project")),
+            (
+                "ssh://git@github.com/user/my-project.git",
+                Some("my-project"),
+            ),
+            (
+                "https://gitea.example.com/user/my-project",
+                Some("my-project"),
+            ),
+            ("git@host:my-project", Some("my-project")),
+            ("/srv/git/my-project.git", Some("my-project")),
+            ("../my-project/", Some("my-project")),
+            ("file:///srv/git/my-project.git", Some("my-project")),
+            ("", None),
+            ("my-project", None),
+            ("https://github.com", None),
+            ("https://github.com/", None),
+            ("://github.com/user/repo", None),
+            ("git@github.com:", None),
+            ("https://github.com/user/.git", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(
+                infer_name_from_remote_url(url).as_deref(),
+                expected,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_git_repos() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        for repo in [
+            "a",
+            "group/b",
+            "group/b/nested",
+            ".hidden/c",
+            "deep/1/2/3/d",
+        ] {
+            fs::create_dir_all(root.join(repo).join(".git")).unwrap();
+        }
+        fs::create_dir_all(root.join("not-a-repo")).unwrap();
+
+        let repos = find_git_repos(root, 3);
+        assert_eq!(repos, vec![root.join("a"), root.join("group/b")]);
+        assert_eq!(find_git_repos(&root.join("a"), 0), vec![root.join("a")]);
+
+        // deep, deep/1, deep/1/2, group and not-a-repo; .hidden isn't looked at
+        let scan = scan_git_repos(root, 3);
+        assert_eq!(scan.repos, repos);
+        assert_eq!(scan.other_dirs, 5);
+        assert_eq!(scan_git_repos(root, 1).other_dirs, 3);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_duration("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::days(14));
+        assert_eq!(parse_duration("6mo").unwrap(), Duration::days(180));
+        assert_eq!(parse_duration(" 1y ").unwrap(), Duration::days(365));
+        for invalid in [
+            "",
+            "30",
+            "d",
+            "1.5d",
+            "3x",
+            "-1d",
+            // Too long for a Duration instead of panicking
+            "99999999999999y",
+            "9223372036854775807m",
+            "99999999999999999999d",
+        ] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_format_time_ago() {
+        let now = Utc::now();
+        let cases = [
+            (Duration::seconds(30), "just now"),
+            (Duration::minutes(5), "5m ago"),
+            (Duration::hours(3), "3h ago"),
+            (Duration::days(12), "12d ago"),
+            (Duration::days(90), "3mo ago"),
+            (Duration::days(800), "2y ago"),
+        ];
+        for (elapsed, expected) in cases {
+            assert_eq!(format_time_ago(now - elapsed, now), expected);
+        }
+    }
+}
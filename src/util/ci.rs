@@ -0,0 +1,334 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+
+/// Outcome of the latest CI run of a repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    Passed,
+    Failed,
+    Pending,
+    Unknown,
+}
+
+impl fmt::Display for CiStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiStatus::Passed => write!(f, "✓ passed"),
+            CiStatus::Failed => write!(f, "✗ failed"),
+            CiStatus::Pending => write!(f, "○ pending"),
+            CiStatus::Unknown => write!(f, "— unknown"),
+        }
+    }
+}
+
+/// Minimal HTTP client, so the CI lookups can be tested without a network
+pub trait HttpClient {
+    /// Sends a GET request and returns the response body
+    fn get(&self, url: &str, headers: &[(&str, String)]) -> Result<String>;
+}
+
+/// Sends requests with the curl binary
+pub struct CurlClient;
+
+impl HttpClient for CurlClient {
+    fn get(&self, url: &str, headers: &[(&str, String)]) -> Result<String> {
+        // Headers go through stdin so tokens don't show up in the process list
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .args(["--max-time", "10", "--header", "@-", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run curl")?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let input: String = headers
+                .iter()
+                .map(|(name, value)| format!("{}: {}\n", name, value))
+                .collect();
+            stdin.write_all(input.as_bytes())?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .context("Failed to wait for curl")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("GET {} failed: {}", url, stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Where a repo's CI runs, derived from its remote URL
+#[derive(Debug, PartialEq)]
+enum CiProvider {
+    GitHub { owner: String, repo: String },
+    GitLab { host: String, project: String },
+}
+
+impl CiProvider {
+    /// Recognizes github.com remotes and hosts with "gitlab" in their name
+    fn from_remote_url(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            None => url.split_once(':')?,
+        };
+        // Drop the user and port, e.g. `git@host:22`
+        let host = host.rsplit('@').next()?.split(':').next()?.to_lowercase();
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+
+        if host == "github.com" {
+            let (owner, repo) = path.split_once('/')?;
+            if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+                return None;
+            }
+            return Some(CiProvider::GitHub {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            });
+        }
+        if host.contains("gitlab") && path.contains('/') {
+            return Some(CiProvider::GitLab {
+                host,
+                project: path.to_string(),
+            });
+        }
+        None
+    }
+}
+
+/// Fetches the status of the latest CI run for a repo hosted on GitHub or GitLab.
+/// Other remotes give `CiStatus::Unknown`
+pub fn fetch_ci_status(
+    client: &dyn HttpClient,
+    remote_url: &str,
+    config: &Config,
+) -> Result<CiStatus> {
+    match CiProvider::from_remote_url(remote_url) {
+        Some(CiProvider::GitHub { owner, repo }) => {
+            let url = format!(
+                "https://api.github.com/repos/{}/{}/actions/runs?per_page=1",
+                owner, repo
+            );
+            let mut headers = vec![
+                ("Accept", "application/vnd.github+json".to_string()),
+                ("User-Agent", "mangit".to_string()),
+            ];
+            if let Some(token) = &config.github_token {
+                headers.push(("Authorization", format!("Bearer {}", token)));
+            }
+            parse_github_runs(&client.get(&url, &headers)?)
+        }
+        Some(CiProvider::GitLab { host, project }) => {
+            let url = format!(
+                "https://{}/api/v4/projects/{}/pipelines?per_page=1",
+                host,
+                project.replace('/', "%2F")
+            );
+            let mut headers = Vec::new();
+            if let Some(token) = &config.gitlab_token {
+                headers.push(("PRIVATE-TOKEN", token.clone()));
+            }
+            parse_gitlab_pipelines(&client.get(&url, &headers)?)
+        }
+        None => Ok(CiStatus::Unknown),
+    }
+}
+
+/// Reads the status of the first run in a GitHub Actions `runs` response
+fn parse_github_runs(body: &str) -> Result<CiStatus> {
+    let response: serde_json::Value =
+        serde_json::from_str(body).context("Failed to parse GitHub response")?;
+    let Some(run) = response.get("workflow_runs").and_then(|runs| runs.get(0)) else {
+        return Ok(CiStatus::Unknown);
+    };
+
+    let status = run.get("status").and_then(|v| v.as_str());
+    let conclusion = run.get("conclusion").and_then(|v| v.as_str());
+    Ok(match (status, conclusion) {
+        (Some("completed"), Some("success")) => CiStatus::Passed,
+        (Some("completed"), Some("failure" | "cancelled" | "timed_out" | "startup_failure")) => {
+            CiStatus::Failed
+        }
+        (Some("queued" | "in_progress" | "waiting" | "requested" | "pending"), _) => {
+            CiStatus::Pending
+        }
+        _ => CiStatus::Unknown,
+    })
+}
+
+/// Reads the status of the first pipeline in a GitLab `pipelines` response
+fn parse_gitlab_pipelines(body: &str) -> Result<CiStatus> {
+    let response: serde_json::Value =
+        serde_json::from_str(body).context("Failed to parse GitLab response")?;
+    let status = response
+        .get(0)
+        .and_then(|pipeline| pipeline.get("status"))
+        .and_then(|v| v.as_str());
+
+    Ok(match status {
+        Some("success") => CiStatus::Passed,
+        Some("failed" | "canceled") => CiStatus::Failed,
+        Some(
+            "created"
+            | "waiting_for_resource"
+            | "preparing"
+            | "pending"
+            | "running"
+            | "scheduled"
+            | "manual",
+        ) => CiStatus::Pending,
+        _ => CiStatus::Unknown,
+    })
+}
+
+#[cfg(test)]
+mod tests_ci {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Returns a canned body and remembers the requests
+    struct FakeClient {
+        body: String,
+        requests: RefCell<Vec<(String, Vec<String>)>>,
+    }
+
+    impl FakeClient {
+        fn new(body: &str) -> Self {
+            FakeClient {
+                body: body.to_string(),
+                requests: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl HttpClient for FakeClient {
+        fn get(&self, url: &str, headers: &[(&str, String)]) -> Result<String> {
+            let headers = headers
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect();
+            self.requests.borrow_mut().push((url.to_string(), headers));
+            Ok(self.body.clone())
+        }
+    }
+
+    #[test]
+    fn test_provider_from_remote_url() {
+        let github = CiProvider::GitHub {
+            owner: "tizee".to_string(),
+            repo: "mangit".to_string(),
+        };
+        assert_eq!(
+            CiProvider::from_remote_url("https://github.com/tizee/mangit.git"),
+            Some(github)
+        );
+        assert!(matches!(
+            CiProvider::from_remote_url("git@github.com:tizee/mangit.git"),
+            Some(CiProvider::GitHub { .. })
+        ));
+        assert_eq!(
+            CiProvider::from_remote_url("git@gitlab.com:group/sub/project.git"),
+            Some(CiProvider::GitLab {
+                host: "gitlab.com".to_string(),
+                project: "group/sub/project".to_string(),
+            })
+        );
+        assert_eq!(
+            CiProvider::from_remote_url("https://git.example.com/user/repo"),
+            None
+        );
+        assert_eq!(
+            CiProvider::from_remote_url("https://github.com/tizee"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fetch_github_status() {
+        let client = FakeClient::new(
+            r#"{"total_count": 1, "workflow_runs": [{"status": "completed", "conclusion": "failure"}]}"#,
+        );
+        let config = Config {
+            github_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let status = fetch_ci_status(&client, "git@github.com:tizee/mangit.git", &config);
+        assert_eq!(status.unwrap(), CiStatus::Failed);
+
+        let requests = client.requests.borrow();
+        assert_eq!(
+            requests[0].0,
+            "https://api.github.com/repos/tizee/mangit/actions/runs?per_page=1"
+        );
+        assert!(
+            requests[0]
+                .1
+                .contains(&"Authorization: Bearer secret".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fetch_gitlab_status() {
+        let client = FakeClient::new(r#"[{"id": 1, "status": "running"}]"#);
+        let status = fetch_ci_status(
+            &client,
+            "https://gitlab.com/group/project.git",
+            &Config::default(),
+        );
+        assert_eq!(status.unwrap(), CiStatus::Pending);
+        assert_eq!(
+            client.requests.borrow()[0].0,
+            "https://gitlab.com/api/v4/projects/group%2Fproject/pipelines?per_page=1"
+        );
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let run = |status: &str, conclusion: &str| {
+            format!(
+                r#"{{"workflow_runs": [{{"status": "{}", "conclusion": {}}}]}}"#,
+                status, conclusion
+            )
+        };
+        assert_eq!(
+            parse_github_runs(&run("completed", r#""success""#)).unwrap(),
+            CiStatus::Passed
+        );
+        assert_eq!(
+            parse_github_runs(&run("in_progress", "null")).unwrap(),
+            CiStatus::Pending
+        );
+        assert_eq!(
+            parse_github_runs(r#"{"workflow_runs": []}"#).unwrap(),
+            CiStatus::Unknown
+        );
+        assert!(parse_github_runs("not json").is_err());
+
+        assert_eq!(
+            parse_gitlab_pipelines(r#"[{"status": "success"}]"#).unwrap(),
+            CiStatus::Passed
+        );
+        assert_eq!(
+            parse_gitlab_pipelines(r#"[{"status": "canceled"}]"#).unwrap(),
+            CiStatus::Failed
+        );
+        assert_eq!(parse_gitlab_pipelines("[]").unwrap(), CiStatus::Unknown);
+    }
+
+    #[test]
+    fn test_status_display() {
+        assert_eq!(CiStatus::Passed.to_string(), "✓ passed");
+        assert_eq!(CiStatus::Unknown.to_string(), "— unknown");
+    }
+}
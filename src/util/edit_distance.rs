@@ -0,0 +1,113 @@
+/// Number of single character insertions, deletions, substitutions and swaps of
+/// adjacent characters needed to turn `a` into `b` (optimal string alignment)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i chars of a and first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Corrects every comma separated tag of the query that isn't a known tag but is
+/// one edit away from one. Returns None if nothing needed correcting
+pub fn suggest_correction(query: &str, all_tags: &[String]) -> Option<String> {
+    let mut corrected = false;
+
+    let tags: Vec<String> = query
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| {
+            let lower = tag.to_lowercase();
+            if all_tags.iter().any(|known| known.to_lowercase() == lower) {
+                return tag.to_string();
+            }
+
+            // Closest known tag, alphabetically first on ties
+            let closest = all_tags
+                .iter()
+                .map(|known| (edit_distance(&lower, &known.to_lowercase()), known))
+                .filter(|(distance, _)| *distance == 1)
+                .min_by(|a, b| a.1.cmp(b.1));
+            match closest {
+                Some((_, known)) => {
+                    corrected = true;
+                    known.clone()
+                }
+                None => tag.to_string(),
+            }
+        })
+        .collect();
+
+    corrected.then(|| tags.join(","))
+}
+
+#[cfg(test)]
+mod tests_edit_distance {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("rst", "rust"), 1);
+        assert_eq!(edit_distance("rusty", "rust"), 1);
+        assert_eq!(edit_distance("ruse", "rust"), 1);
+        assert_eq!(edit_distance("ruts", "rust"), 1);
+        assert_eq!(edit_distance("", "go"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_suggest_correction() {
+        let all_tags = tags(&["rust", "python", "cli", "go"]);
+
+        assert_eq!(
+            suggest_correction("ruts", &all_tags),
+            Some("rust".to_string())
+        );
+        assert_eq!(
+            suggest_correction("pyhton", &all_tags),
+            Some("python".to_string())
+        );
+        // Only the misspelled tag of a multi tag query changes
+        assert_eq!(
+            suggest_correction("cli,ruts", &all_tags),
+            Some("cli,rust".to_string())
+        );
+    }
+
+    #[test]
+    fn test_no_correction_needed() {
+        let all_tags = tags(&["rust", "python"]);
+
+        assert_eq!(suggest_correction("rust", &all_tags), None);
+        assert_eq!(suggest_correction("Python", &all_tags), None);
+        // Too far from any known tag
+        assert_eq!(suggest_correction("haskell", &all_tags), None);
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{Result, anyhow};
+
+/// Values a sort expression can refer to
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SortVars {
+    pub frecency: f64,
+    pub tag_count: f64,
+    pub age_days: f64,
+    pub access_count: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Var {
+    Frecency,
+    TagCount,
+    AgeDays,
+    AccessCount,
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Var(Var),
+    Neg(Box<Node>),
+    Binary(Box<Node>, char, Box<Node>),
+}
+
+/// Arithmetic sort key such as `frecency * 0.7 + tag_count * 0.3`. Supports the
+/// variables of `SortVars`, `+ - * /`, unary minus and parentheses
+#[derive(Debug, PartialEq)]
+pub struct SortExpr {
+    root: Node,
+}
+
+impl SortExpr {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.expr()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(anyhow!("Unexpected '{}' in sort expression", token));
+        }
+        Ok(SortExpr { root })
+    }
+
+    /// Evaluates the expression, division by zero gives 0
+    pub fn eval(&self, vars: &SortVars) -> f64 {
+        eval(&self.root, vars)
+    }
+}
+
+fn eval(node: &Node, vars: &SortVars) -> f64 {
+    match node {
+        Node::Number(n) => *n,
+        Node::Var(Var::Frecency) => vars.frecency,
+        Node::Var(Var::TagCount) => vars.tag_count,
+        Node::Var(Var::AgeDays) => vars.age_days,
+        Node::Var(Var::AccessCount) => vars.access_count,
+        Node::Neg(inner) => -eval(inner, vars),
+        Node::Binary(lhs, op, rhs) => {
+            let (lhs, rhs) = (eval(lhs, vars), eval(rhs, vars));
+            match op {
+                '+' => lhs + rhs,
+                '-' => lhs - rhs,
+                '*' => lhs * rhs,
+                _ if rhs == 0.0 => 0.0,
+                _ => lhs / rhs,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(name) => write!(f, "{}", name),
+            Token::Op(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            let value = number
+                .parse()
+                .map_err(|_| anyhow!("Invalid number '{}' in sort expression", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| c.is_ascii_alphanumeric() || **c == '_')
+            {
+                name.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(name));
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(anyhow!("Unexpected '{}' in sort expression", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser, `*` and `/` bind tighter than `+` and `-`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next_op(&mut self, ops: &str) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(c)) if ops.contains(*c) => {
+                self.pos += 1;
+                Some(*c)
+            }
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Node> {
+        let mut node = self.term()?;
+        while let Some(op) = self.next_op("+-") {
+            node = Node::Binary(Box::new(node), op, Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    fn term(&mut self) -> Result<Node> {
+        let mut node = self.factor()?;
+        while let Some(op) = self.next_op("*/") {
+            node = Node::Binary(Box::new(node), op, Box::new(self.factor()?));
+        }
+        Ok(node)
+    }
+
+    fn factor(&mut self) -> Result<Node> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Node::Number(n)),
+            Some(Token::Ident(name)) => {
+                let var = match name.as_str() {
+                    "frecency" => Var::Frecency,
+                    "tag_count" => Var::TagCount,
+                    "age_days" => Var::AgeDays,
+                    "access_count" => Var::AccessCount,
+                    _ => {
+                        return Err(anyhow!(
+                            "Unknown variable '{}', expected frecency, tag_count, age_days or access_count",
+                            name
+                        ));
+                    }
+                };
+                Ok(Node::Var(var))
+            }
+            Some(Token::Op('-')) => Ok(Node::Neg(Box::new(self.factor()?))),
+            Some(Token::Op('(')) => {
+                let node = self.expr()?;
+                if self.next_op(")").is_none() {
+                    return Err(anyhow!("Missing ')' in sort expression"));
+                }
+                Ok(node)
+            }
+            Some(token) => Err(anyhow!("Unexpected '{}' in sort expression", token)),
+            None => Err(anyhow!("Sort expression ends unexpectedly")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_expr {
+    use super::*;
+
+    fn eval(expr: &str) -> f64 {
+        let vars = SortVars {
+            frecency: 100.0,
+            tag_count: 3.0,
+            age_days: 10.0,
+            access_count: 4.0,
+        };
+        SortExpr::parse(expr).unwrap().eval(&vars)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(eval("frecency"), 100.0);
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("-tag_count + access_count"), 1.0);
+        assert_eq!(eval("frecency * 0.7 + tag_count * 0.3"), 70.9);
+        assert_eq!(eval("age_days / 4"), 2.5);
+        assert_eq!(eval("frecency / (tag_count - 3)"), 0.0);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expr in [
+            "",
+            "frecency +",
+            "(frecency",
+            "frecency)",
+            "stars * 2",
+            "1 $ 2",
+            "1..2",
+            "2 3",
+        ] {
+            assert!(SortExpr::parse(expr).is_err(), "{} should not parse", expr);
+        }
+        let err = SortExpr::parse("stars").unwrap_err();
+        assert!(err.to_string().contains("stars"));
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{Context, Result, anyhow};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use super::find_in_path;
+
+/// A repo shown in the fzf picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct FzfItem {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub path: String,
+}
+
+impl FzfItem {
+    /// Formats the item as `name<TAB>[tags]<TAB>path`, the path is always the last field
+    pub fn to_line(&self) -> String {
+        format!("{}\t[{}]\t{}", self.name, self.tags.join(","), self.path)
+    }
+
+    /// Extracts the path from a line produced by `to_line`
+    pub fn path_from_line(line: &str) -> Option<&str> {
+        line.rsplit('\t').next().filter(|path| !path.is_empty())
+    }
+}
+
+/// Checks whether fzf is on the PATH
+pub fn is_fzf_available() -> bool {
+    find_in_path("fzf").is_some()
+}
+
+/// Lets the user pick a repo with fzf. Returns the selected path,
+/// None if the selection was cancelled
+pub fn launch_fzf_picker(items: Vec<FzfItem>) -> Result<Option<String>> {
+    let fzf = find_in_path("fzf").ok_or_else(|| anyhow!("fzf not found in PATH"))?;
+    run_picker(&fzf, &items)
+}
+
+fn run_picker(program: &Path, items: &[FzfItem]) -> Result<Option<String>> {
+    let mut child = Command::new(program)
+        .args([
+            "--delimiter",
+            "\t",
+            "--with-nth",
+            "1,2",
+            "--prompt",
+            "repo> ",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program.display()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let input: String = items.iter().map(|item| item.to_line() + "\n").collect();
+        // fzf may exit before reading everything, e.g. on an early selection
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let output = child.wait_with_output().context("Failed to wait for fzf")?;
+    // fzf exits with 1 on no match and 130 on cancel, neither is an error
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let selection = String::from_utf8_lossy(&output.stdout);
+    Ok(selection
+        .lines()
+        .next()
+        .and_then(FzfItem::path_from_line)
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests_fzf {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(name: &str, tags: &[&str], path: &str) -> FzfItem {
+        FzfItem {
+            name: name.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_item_line_format() {
+        let line = item("mangit", &["rust", "cli"], "/src/my repos/mangit").to_line();
+        assert_eq!(line, "mangit\t[rust,cli]\t/src/my repos/mangit");
+        assert_eq!(FzfItem::path_from_line(&line), Some("/src/my repos/mangit"));
+        assert_eq!(FzfItem::path_from_line(""), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_picker_with_fake_fzf() {
+        // Stand-in for fzf that selects the second line
+        let temp_dir = tempdir().unwrap();
+        let fake_fzf = temp_dir.path().join("fzf");
+        crate::util::write_fake_tool(&fake_fzf, "sed -n 2p");
+
+        let items = vec![
+            item("one", &["a"], "/src/one"),
+            item("two", &["b"], "/src/two"),
+        ];
+        let selected = run_picker(&fake_fzf, &items).unwrap();
+        assert_eq!(selected, Some("/src/two".to_string()));
+
+        // A cancelled picker exits non-zero
+        crate::util::write_fake_tool(&fake_fzf, "exit 130");
+        assert_eq!(run_picker(&fake_fzf, &items).unwrap(), None);
+    }
+}
@@ -0,0 +1,609 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Runs a git command inside `path` and returns its trimmed stdout
+pub fn run_git(path: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Like `run_git`, but never prompts for credentials, and kills git and fails if
+/// it runs longer than `timeout`
+pub fn run_git_with_timeout(path: &str, args: &[&str], timeout: Duration) -> Result<String> {
+    let mut child = non_interactive(Command::new("git").arg("-C").arg(path).args(args))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git")?;
+
+    // Read the output on the side so a full pipe can't block git
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let error_reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let Some(status) = wait_with_timeout(&mut child, timeout).context("Failed to wait for git")?
+    else {
+        return Err(anyhow!(
+            "git {} timed out after {}ms",
+            args.join(" "),
+            timeout.as_millis()
+        ));
+    };
+
+    let output = reader
+        .join()
+        .map_err(|_| anyhow!("Failed to read git output"))?
+        .context("Failed to read git output")?;
+    if !status.success() {
+        let stderr = error_reader.join().unwrap_or_default();
+        return Err(anyhow!("git {} failed: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(output.trim_end().to_string())
+}
+
+/// Keeps git from waiting for a password or passphrase nobody can type: no
+/// terminal prompts, and ssh in batch mode unless `GIT_SSH_COMMAND` is set
+fn non_interactive(command: &mut Command) -> &mut Command {
+    command.env("GIT_TERMINAL_PROMPT", "0").stdin(Stdio::null());
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        command.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    command
+}
+
+/// Waits for `child` to exit, killing it once `timeout` has passed. None if it
+/// timed out. The deadline is checked before the child is, so a zero timeout
+/// always times out
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Result of checking a remote URL with `git ls-remote`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteStatus {
+    Reachable,
+    NotFound,
+    AuthFailed,
+    Timeout,
+    // Any other failure, with git's message
+    Error(String),
+}
+
+impl fmt::Display for RemoteStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteStatus::Reachable => write!(f, "✓ reachable"),
+            RemoteStatus::NotFound => write!(f, "✗ URL not found"),
+            RemoteStatus::AuthFailed => write!(f, "✗ authentication failed"),
+            RemoteStatus::Timeout => write!(f, "✗ timeout"),
+            RemoteStatus::Error(message) => write!(f, "✗ {}", message),
+        }
+    }
+}
+
+/// Checks whether a remote can be reached with `git ls-remote`, giving up after
+/// `timeout_secs`. Never prompts for credentials
+pub fn check_remote_reachable(url: &str, timeout_secs: u64) -> RemoteStatus {
+    ls_remote_status(Path::new("git"), url, Duration::from_secs(timeout_secs))
+}
+
+fn ls_remote_status(git: &Path, url: &str, timeout: Duration) -> RemoteStatus {
+    let mut command = Command::new(git);
+    non_interactive(command.args(["ls-remote", "--exit-code", "--quiet", url]))
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return RemoteStatus::Error(format!("failed to run git: {}", e)),
+    };
+
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let status = match wait_with_timeout(&mut child, timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => return RemoteStatus::Timeout,
+        Err(e) => return RemoteStatus::Error(e.to_string()),
+    };
+
+    let stderr = reader.join().unwrap_or_default();
+    classify_ls_remote(status.code(), &stderr)
+}
+
+/// Maps the exit code and error output of `git ls-remote --exit-code` to a status.
+/// Exit code 2 means the remote exists but has no refs
+fn classify_ls_remote(code: Option<i32>, stderr: &str) -> RemoteStatus {
+    if matches!(code, Some(0 | 2)) {
+        return RemoteStatus::Reachable;
+    }
+
+    let message = stderr.to_lowercase();
+    let has_any = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+    if has_any(&[
+        "not found",
+        "does not exist",
+        "404",
+        "does not appear to be a git repository",
+    ]) {
+        RemoteStatus::NotFound
+    } else if has_any(&[
+        "authentication failed",
+        "could not read username",
+        "permission denied",
+        "403",
+    ]) {
+        RemoteStatus::AuthFailed
+    } else {
+        let first_line = stderr.lines().find(|line| !line.trim().is_empty());
+        RemoteStatus::Error(
+            first_line
+                .unwrap_or("git ls-remote failed")
+                .trim()
+                .to_string(),
+        )
+    }
+}
+
+/// Parses a date printed by git with `--date=iso` (e.g. `2024-01-15 10:30:00 +0100`)
+pub fn parse_git_date(date: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_str(date.trim(), "%Y-%m-%d %H:%M:%S %z")
+        .map(|date| date.with_timezone(&Utc))
+        .with_context(|| format!("Invalid git date: {}", date))
+}
+
+/// Returns the author date of the first commit in the repo, None for an empty repo
+pub fn first_commit_date(path: &str) -> Result<Option<DateTime<Utc>>> {
+    let output = run_git(
+        path,
+        &["log", "--reverse", "--pretty=format:%ad", "--date=iso"],
+    )?;
+
+    match output.lines().next() {
+        Some(line) if !line.trim().is_empty() => Ok(Some(parse_git_date(line)?)),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the URL of the repo's `origin` remote, None if it has none
+pub fn remote_origin_url(path: &str) -> Option<String> {
+    run_git(path, &["remote", "get-url", "origin"])
+        .ok()
+        .filter(|url| !url.is_empty())
+}
+
+/// Returns the name of the checked out branch, None for a detached HEAD
+pub fn current_branch(path: &str) -> Option<String> {
+    run_git(path, &["symbolic-ref", "--short", "--quiet", "HEAD"])
+        .ok()
+        .filter(|branch| !branch.is_empty())
+}
+
+/// Returns the subject line of the latest commit, None for an empty repo
+pub fn last_commit_subject(path: &str) -> Option<String> {
+    run_git(path, &["log", "-1", "--pretty=format:%s"])
+        .ok()
+        .filter(|subject| !subject.is_empty())
+}
+
+/// Returns the committer date of the latest commit, None for an empty repo
+pub fn last_commit_date(path: &str) -> Result<Option<DateTime<Utc>>> {
+    let output = run_git(path, &["log", "-1", "--format=%ci"])?;
+
+    if output.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(parse_git_date(&output)?))
+    }
+}
+
+/// Counts the ignored, untracked files in the repo (e.g. build artifacts)
+pub fn count_ignored_files(path: &str) -> Result<u64> {
+    let output = run_git(
+        path,
+        &["ls-files", "--ignored", "--exclude-standard", "--others"],
+    )?;
+    Ok(parse_ignored_count(&output))
+}
+
+/// Counts the file lines printed by `git ls-files --ignored`
+pub fn parse_ignored_count(output: &str) -> u64 {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count() as u64
+}
+
+/// Counts the stash entries of the repo at `path`
+pub fn count_stash_entries(path: &str) -> Result<usize> {
+    let output = run_git(path, &["stash", "list"])?;
+    Ok(parse_stash_count(&output))
+}
+
+/// Counts the entries printed by `git stash list`, one per line
+pub fn parse_stash_count(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+/// Counts of changed files in a work tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepoStatus {
+    pub modified: u32,
+    pub added: u32,
+    pub deleted: u32,
+    pub untracked: u32,
+}
+
+/// Shows the non-zero counts, e.g. `M:2 A:1 ?:3`, or `clean`
+impl fmt::Display for RepoStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [
+            ("M", self.modified),
+            ("A", self.added),
+            ("D", self.deleted),
+            ("?", self.untracked),
+        ];
+        let parts: Vec<String> = counts
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(code, count)| format!("{}:{}", code, count))
+            .collect();
+
+        if parts.is_empty() {
+            write!(f, "clean")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+/// Returns a summary of the repo's uncommitted changes, giving up after `timeout`
+pub fn get_compact_status(path: &str, timeout: Duration) -> Result<RepoStatus> {
+    let output = run_git_with_timeout(path, &["status", "--porcelain"], timeout)?;
+    Ok(parse_porcelain_status(&output))
+}
+
+/// Counts the files listed by `git status --porcelain`. Renamed, copied and
+/// conflicting files count as modified
+pub fn parse_porcelain_status(output: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+
+    for change in output.lines().filter_map(change_type) {
+        match change {
+            '?' => status.untracked += 1,
+            'A' => status.added += 1,
+            'D' => status.deleted += 1,
+            _ => status.modified += 1,
+        }
+    }
+
+    status
+}
+
+/// A repo's checked out branch and how many files have uncommitted changes
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GitStatus {
+    // None for a detached HEAD
+    pub branch: Option<String>,
+    // Staged or unstaged
+    pub changed_count: usize,
+    pub untracked_count: usize,
+}
+
+impl GitStatus {
+    pub fn is_clean(&self) -> bool {
+        self.changed_count == 0 && self.untracked_count == 0
+    }
+}
+
+/// Returns the branch and change counts of the repo at `path`
+pub fn git_status(path: &str) -> Result<GitStatus> {
+    let output = run_git(path, &["status", "--porcelain", "-z"])?;
+    Ok(GitStatus {
+        branch: current_branch(path),
+        ..parse_status_z(&output)
+    })
+}
+
+/// Counts the files in `git status --porcelain -z` output. A rename or copy is
+/// followed by the original path, which isn't counted again
+pub fn parse_status_z(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+
+    while let Some(entry) = entries.next() {
+        match change_type(entry) {
+            Some('?') => status.untracked_count += 1,
+            Some(_) => status.changed_count += 1,
+            None => {}
+        }
+        if entry.starts_with(['R', 'C']) {
+            entries.next();
+        }
+    }
+
+    status
+}
+
+/// Checks whether the repo has uncommitted changes, untracked files included
+pub fn has_uncommitted_changes(path: &str) -> Result<bool> {
+    let output = run_git(path, &["status", "--porcelain"])?;
+    Ok(!output.is_empty())
+}
+
+/// Returns the kinds of uncommitted changes in the repo, see `parse_change_types`
+pub fn get_change_types(path: &str) -> Result<HashSet<char>> {
+    let output = run_git(path, &["status", "--porcelain"])?;
+    Ok(parse_change_types(&output))
+}
+
+/// Collects the change types in `git status --porcelain` output: `M` (modified),
+/// `A` (added), `D` (deleted) and `?` (untracked)
+pub fn parse_change_types(output: &str) -> HashSet<char> {
+    output.lines().filter_map(change_type).collect()
+}
+
+/// Classifies a `git status --porcelain` line as `M`, `A`, `D` or `?`. Ignored
+/// files and malformed lines give None
+fn change_type(line: &str) -> Option<char> {
+    let mut code = line.chars();
+    let (index, worktree) = (code.next()?, code.next()?);
+
+    match (index, worktree) {
+        ('!', _) => None,
+        ('?', _) => Some('?'),
+        ('A', _) => Some('A'),
+        ('D', _) | (_, 'D') => Some('D'),
+        _ => Some('M'),
+    }
+}
+
+/// Point in history used to check whether a repo has newer commits
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangedAfter {
+    /// A calendar date (`YYYY-MM-DD`), midnight UTC
+    Date(DateTime<Utc>),
+    /// A git ref resolved inside each repo (e.g. `HEAD~10`, `v1.0`)
+    Ref(String),
+}
+
+impl ChangedAfter {
+    /// Parses a `YYYY-MM-DD` date, anything else is treated as a git ref
+    pub fn parse(value: &str) -> Self {
+        match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+            Ok(date) => ChangedAfter::Date(date.and_hms_opt(0, 0, 0).unwrap().and_utc()),
+            Err(_) => ChangedAfter::Ref(value.trim().to_string()),
+        }
+    }
+}
+
+/// Checks whether the repo has at least one commit after the given ref
+pub fn has_commits_after_ref(path: &str, git_ref: &str) -> Result<bool> {
+    let range = format!("{}..HEAD", git_ref);
+    let output = run_git(path, &["log", "--oneline", "-1", &range])?;
+    Ok(!output.is_empty())
+}
+
+#[cfg(test)]
+mod tests_git {
+    use super::*;
+    use chrono::TimeZone;
+    use std::fs;
+
+    #[test]
+    fn test_parse_git_date() {
+        let date = parse_git_date("2024-01-15 10:30:00 +0100").unwrap();
+        assert_eq!(date, Utc.with_ymd_and_hms(2024, 1, 15, 9, 30, 0).unwrap());
+
+        // Trailing newline from git output
+        let date = parse_git_date("2019-12-31 23:00:00 -0500\n").unwrap();
+        assert_eq!(date, Utc.with_ymd_and_hms(2020, 1, 1, 4, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_changed_after_parse() {
+        assert_eq!(
+            ChangedAfter::parse("2024-01-01"),
+            ChangedAfter::Date(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            ChangedAfter::parse("HEAD~10"),
+            ChangedAfter::Ref("HEAD~10".to_string())
+        );
+        assert_eq!(
+            ChangedAfter::parse("2024-13-01"),
+            ChangedAfter::Ref("2024-13-01".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_ignored_count() {
+        let output = "target/debug/mangit\ntarget/debug/mangit.d\n.env\n";
+        assert_eq!(parse_ignored_count(output), 3);
+        assert_eq!(parse_ignored_count(""), 0);
+        assert_eq!(parse_ignored_count("\n\n"), 0);
+    }
+
+    #[test]
+    fn test_parse_stash_count() {
+        let output = "stash@{0}: WIP on main: 1a2b3c4 Add list --output html\n\
+                      stash@{1}: On feature: try another layout\n";
+        assert_eq!(parse_stash_count(output), 2);
+        assert_eq!(parse_stash_count(""), 0);
+    }
+
+    #[test]
+    fn test_parse_porcelain_status() {
+        let output = " M src/main.rs\nM  src/storage.rs\nA  src/util/git.rs\n D old.rs\n\
+                      R  a.rs -> b.rs\n?? notes.txt\n?? tmp/\n?? .env\n";
+        let status = parse_porcelain_status(output);
+
+        assert_eq!(
+            status,
+            RepoStatus {
+                modified: 3,
+                added: 1,
+                deleted: 1,
+                untracked: 3,
+            }
+        );
+        assert_eq!(status.to_string(), "M:3 A:1 D:1 ?:3");
+        assert_eq!(parse_porcelain_status("").to_string(), "clean");
+    }
+
+    #[test]
+    fn test_parse_status_z() {
+        let output = " M src/main.rs\0MM src/storage.rs\0R  b.rs\0a.rs\0A  new.rs\0\
+                      ?? notes.txt\0?? tmp/\0!! target/\0";
+        assert_eq!(
+            parse_status_z(output),
+            GitStatus {
+                branch: None,
+                changed_count: 4,
+                untracked_count: 2,
+            }
+        );
+        // Paths with spaces or arrows are taken as they are
+        assert_eq!(parse_status_z("?? a -> b.txt\0").untracked_count, 1);
+        assert!(parse_status_z("").is_clean());
+    }
+
+    #[test]
+    fn test_parse_change_types() {
+        let output = " M src/main.rs\nMM src/storage.rs\n?? notes.txt\n!! target/\n";
+        assert_eq!(parse_change_types(output), HashSet::from(['M', '?']));
+
+        let output = "A  src/util/git.rs\n D old.rs\nR  a.rs -> b.rs\n";
+        assert_eq!(parse_change_types(output), HashSet::from(['A', 'D', 'M']));
+
+        assert!(parse_change_types("").is_empty());
+        assert!(parse_change_types("!! target/\n").is_empty());
+    }
+
+    #[test]
+    fn test_run_git_with_timeout() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        Command::new("git")
+            .args(["init", "--quiet", path])
+            .status()
+            .unwrap();
+        fs::write(temp_dir.path().join("new.txt"), "").unwrap();
+
+        let status = get_compact_status(path, Duration::from_secs(10)).unwrap();
+        assert_eq!(status.untracked, 1);
+
+        // A zero timeout gives up before looking at git at all
+        let error = run_git_with_timeout(path, &["status"], Duration::ZERO).unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+    }
+
+    #[test]
+    fn test_classify_ls_remote() {
+        assert_eq!(classify_ls_remote(Some(0), ""), RemoteStatus::Reachable);
+        assert_eq!(classify_ls_remote(Some(2), ""), RemoteStatus::Reachable);
+        assert_eq!(
+            classify_ls_remote(
+                Some(128),
+                "remote: Repository not found.\nfatal: repository 'x' not found\n"
+            ),
+            RemoteStatus::NotFound
+        );
+        assert_eq!(
+            classify_ls_remote(
+                Some(128),
+                "git@github.com: Permission denied (publickey).\n"
+            ),
+            RemoteStatus::AuthFailed
+        );
+        assert_eq!(
+            classify_ls_remote(
+                Some(128),
+                "fatal: unable to access 'x': Could not resolve host: x\n"
+            ),
+            RemoteStatus::Error(
+                "fatal: unable to access 'x': Could not resolve host: x".to_string()
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ls_remote_status_with_fake_git() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let fake_git = temp_dir.path().join("git");
+        let check = |script: &str| {
+            crate::util::write_fake_tool(&fake_git, script);
+            ls_remote_status(
+                &fake_git,
+                "https://example.com/repo.git",
+                Duration::from_secs(5),
+            )
+        };
+
+        assert_eq!(check("exit 0"), RemoteStatus::Reachable);
+        assert_eq!(
+            check("echo 'remote: Repository not found.' >&2; exit 128"),
+            RemoteStatus::NotFound
+        );
+        assert_eq!(
+            check("echo 'fatal: Authentication failed for x' >&2; exit 128"),
+            RemoteStatus::AuthFailed
+        );
+
+        crate::util::write_fake_tool(&fake_git, "sleep 5");
+        assert_eq!(
+            ls_remote_status(&fake_git, "x", Duration::from_millis(100)),
+            RemoteStatus::Timeout
+        );
+    }
+
+    #[test]
+    fn test_parse_git_date_invalid() {
+        assert!(parse_git_date("").is_err());
+        assert!(parse_git_date("Mon Jan 15 10:30:00 2024").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Result, anyhow};
+
+/// Services a repo's ticket or issue URL can point to, each with its own URL format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectService {
+    /// Any http(s) URL
+    Any,
+    /// `https://<host>/browse/PROJ-123`
+    Jira,
+    /// `https://github.com/<owner>/<repo>/issues/123`
+    GitHubIssue,
+    /// `https://linear.app/<workspace>/issue/ENG-123[/<title>]`
+    Linear,
+}
+
+/// Checks that `url` is a link to the given service
+pub fn validate_project_url(url: &str, service: ProjectService) -> Result<()> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| anyhow!("Not an http(s) URL: {}", url))?;
+    let segments: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+    let host = segments[0];
+    if host.is_empty() {
+        return Err(anyhow!("URL has no host: {}", url));
+    }
+
+    let valid = match service {
+        ProjectService::Any => true,
+        ProjectService::Jira => {
+            matches!(segments[1..], ["browse", key] if is_issue_key(key))
+        }
+        ProjectService::GitHubIssue => {
+            host == "github.com"
+                && matches!(
+                    segments[1..],
+                    [owner, repo, "issues", number]
+                        if !owner.is_empty() && !repo.is_empty() && is_number(number)
+                )
+        }
+        ProjectService::Linear => {
+            host == "linear.app"
+                && matches!(
+                    segments[1..],
+                    [workspace, "issue", key] | [workspace, "issue", key, _]
+                        if !workspace.is_empty() && is_issue_key(key)
+                )
+        }
+    };
+
+    if !valid {
+        return Err(anyhow!("Not a {:?} URL: {}", service, url));
+    }
+    Ok(())
+}
+
+/// Checks for a ticket key like `PROJ-123`
+fn is_issue_key(key: &str) -> bool {
+    let Some((project, number)) = key.rsplit_once('-') else {
+        return false;
+    };
+    project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && is_number(number)
+}
+
+fn is_number(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests_project_url {
+    use super::*;
+
+    #[test]
+    fn test_validate_jira_url() {
+        let jira = ProjectService::Jira;
+        assert!(validate_project_url("https://jira.myco.com/browse/PROJ-123", jira).is_ok());
+        assert!(validate_project_url("https://myco.atlassian.net/browse/AB2-7/", jira).is_ok());
+        assert!(validate_project_url("https://jira.myco.com/browse/proj-123", jira).is_err());
+        assert!(validate_project_url("https://jira.myco.com/browse/PROJ", jira).is_err());
+        assert!(validate_project_url("https://jira.myco.com/PROJ-123", jira).is_err());
+    }
+
+    #[test]
+    fn test_validate_github_issue_url() {
+        let github = ProjectService::GitHubIssue;
+        assert!(validate_project_url("https://github.com/tizee/mangit/issues/42", github).is_ok());
+        assert!(validate_project_url("https://github.com/tizee/mangit/pull/42", github).is_err());
+        assert!(validate_project_url("https://github.com/tizee/mangit/issues/", github).is_err());
+        assert!(validate_project_url("https://gitlab.com/tizee/mangit/issues/42", github).is_err());
+    }
+
+    #[test]
+    fn test_validate_linear_url() {
+        let linear = ProjectService::Linear;
+        assert!(validate_project_url("https://linear.app/myco/issue/ENG-12", linear).is_ok());
+        assert!(
+            validate_project_url("https://linear.app/myco/issue/ENG-12/fix-the-thing", linear)
+                .is_ok()
+        );
+        assert!(validate_project_url("https://linear.app/myco/project/ENG-12", linear).is_err());
+        assert!(validate_project_url("https://example.com/myco/issue/ENG-12", linear).is_err());
+    }
+
+    #[test]
+    fn test_validate_any_url() {
+        let any = ProjectService::Any;
+        assert!(validate_project_url("https://tracker.example.com/t/1", any).is_ok());
+        assert!(validate_project_url("http://localhost:8080", any).is_ok());
+        assert!(validate_project_url("ftp://tracker.example.com", any).is_err());
+        assert!(validate_project_url("https://", any).is_err());
+    }
+}
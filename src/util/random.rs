@@ -0,0 +1,79 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Small pseudo random number generator (SplitMix64). Good enough for picking
+/// repos, not for anything security related
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn with_seed(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Seeds the generator from the randomly keyed std hasher
+    pub fn from_entropy() -> Self {
+        Rng::with_seed(RandomState::new().build_hasher().finish())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Picks `amount` distinct items in random order, or all of them if there
+    /// are fewer
+    pub fn choose_multiple<T: Clone>(&mut self, items: &[T], amount: usize) -> Vec<T> {
+        let mut items = items.to_vec();
+        let amount = amount.min(items.len());
+        // Partial Fisher-Yates shuffle, the first `amount` slots are the sample
+        for i in 0..amount {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+        items.truncate(amount);
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests_random {
+    use super::*;
+
+    #[test]
+    fn test_choose_multiple() {
+        let items: Vec<u32> = (0..10).collect();
+        let mut rng = Rng::with_seed(7);
+
+        assert!(rng.choose_multiple(&items, 0).is_empty());
+
+        let mut all = rng.choose_multiple(&items, 25);
+        all.sort();
+        assert_eq!(all, items);
+
+        let mut sample = rng.choose_multiple(&items, 3);
+        assert_eq!(sample.len(), 3);
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 3);
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let items: Vec<u32> = (0..100).collect();
+        let first = Rng::with_seed(42).choose_multiple(&items, 5);
+        let second = Rng::with_seed(42).choose_multiple(&items, 5);
+        assert_eq!(first, second);
+        assert_ne!(first, Rng::with_seed(43).choose_multiple(&items, 5));
+    }
+}
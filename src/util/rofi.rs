@@ -0,0 +1,111 @@
+use anyhow::{Context, Result, anyhow};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use super::find_in_path;
+
+// Separates the repo name from its path in a rofi line
+const SEPARATOR: &str = " :: ";
+
+/// A repo shown in the rofi picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct RofiItem {
+    pub name: String,
+    pub path: String,
+}
+
+impl RofiItem {
+    /// Formats the item as `name :: path`
+    pub fn to_line(&self) -> String {
+        format!("{}{}{}", self.name, SEPARATOR, self.path)
+    }
+
+    /// Extracts the path from a line produced by `to_line`
+    pub fn path_from_line(line: &str) -> Option<&str> {
+        line.split_once(SEPARATOR)
+            .map(|(_, path)| path)
+            .filter(|path| !path.is_empty())
+    }
+}
+
+/// Checks whether rofi is on the PATH
+pub fn is_rofi_available() -> bool {
+    find_in_path("rofi").is_some()
+}
+
+/// Lets the user pick a repo with `rofi -dmenu`. Returns the selected path,
+/// None if the selection was cancelled
+pub fn launch_rofi_picker(items: Vec<RofiItem>) -> Result<Option<String>> {
+    let rofi = find_in_path("rofi").ok_or_else(|| anyhow!("rofi not found in PATH"))?;
+    run_picker(&rofi, &items)
+}
+
+fn run_picker(program: &Path, items: &[RofiItem]) -> Result<Option<String>> {
+    let mut child = Command::new(program)
+        .args(["-dmenu", "-i", "-p", "Select repo:"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program.display()))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let input: String = items.iter().map(|item| item.to_line() + "\n").collect();
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let output = child
+        .wait_with_output()
+        .context("Failed to wait for rofi")?;
+    // rofi exits with 1 when the menu is closed without a selection
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let selection = String::from_utf8_lossy(&output.stdout);
+    Ok(selection
+        .lines()
+        .next()
+        .and_then(RofiItem::path_from_line)
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests_rofi {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(name: &str, path: &str) -> RofiItem {
+        RofiItem {
+            name: name.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_item_line_format() {
+        let line = item("mangit", "/src/my repos/mangit").to_line();
+        assert_eq!(line, "mangit :: /src/my repos/mangit");
+        assert_eq!(
+            RofiItem::path_from_line(&line),
+            Some("/src/my repos/mangit")
+        );
+        assert_eq!(RofiItem::path_from_line("typed by the user"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_picker_with_fake_rofi() {
+        // Stand-in for rofi that selects the second line
+        let temp_dir = tempdir().unwrap();
+        let fake_rofi = temp_dir.path().join("rofi");
+        crate::util::write_fake_tool(&fake_rofi, "sed -n 2p");
+
+        let items = vec![item("one", "/src/one"), item("two", "/src/two")];
+        let selected = run_picker(&fake_rofi, &items).unwrap();
+        assert_eq!(selected, Some("/src/two".to_string()));
+
+        crate::util::write_fake_tool(&fake_rofi, "exit 1");
+        assert_eq!(run_picker(&fake_rofi, &items).unwrap(), None);
+    }
+}
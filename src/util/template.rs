@@ -0,0 +1,164 @@
+use anyhow::{Result, anyhow};
+
+use crate::repository::Repository;
+
+const DEFAULT_TAG_SEPARATOR: &str = ",";
+
+#[derive(Debug, PartialEq)]
+enum Placeholder {
+    Name,
+    Path,
+    Tags { separator: String },
+    Language,
+    LastModified,
+    Score,
+}
+
+#[derive(Debug, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// Output template such as `{name}: {path} [{tags:sep=|}]`. `{{` and `}}` stand
+/// for literal braces
+#[derive(Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(anyhow!("Unclosed placeholder: {{{}", name)),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Placeholder(parse_placeholder(&name)?));
+                }
+                '}' => return Err(anyhow!("Unmatched '}}' in template, use '}}}}'")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn render(&self, repo: &Repository, score: f64) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out += text,
+                Part::Placeholder(Placeholder::Name) => out += &repo.name,
+                Part::Placeholder(Placeholder::Path) => out += &repo.path,
+                Part::Placeholder(Placeholder::Tags { separator }) => {
+                    out += &repo.tags.join(separator)
+                }
+                Part::Placeholder(Placeholder::Language) => {
+                    out += repo.language.as_deref().unwrap_or("")
+                }
+                Part::Placeholder(Placeholder::LastModified) => {
+                    out += &repo.last_modified.format("%Y-%m-%d %H:%M").to_string()
+                }
+                Part::Placeholder(Placeholder::Score) => out += &format!("{:.1}", score),
+            }
+        }
+        out
+    }
+}
+
+fn parse_placeholder(name: &str) -> Result<Placeholder> {
+    let placeholder = match name {
+        "name" => Placeholder::Name,
+        "path" => Placeholder::Path,
+        "tags" => Placeholder::Tags {
+            separator: DEFAULT_TAG_SEPARATOR.to_string(),
+        },
+        "language" => Placeholder::Language,
+        "last_modified" => Placeholder::LastModified,
+        "score" => Placeholder::Score,
+        _ => match name.strip_prefix("tags:sep=") {
+            Some(separator) => Placeholder::Tags {
+                separator: separator.to_string(),
+            },
+            None => return Err(anyhow!("Unknown placeholder: {{{}}}", name)),
+        },
+    };
+    Ok(placeholder)
+}
+
+#[cfg(test)]
+mod tests_template {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn repo() -> Repository {
+        let mut repo = Repository::new(
+            "mangit".to_string(),
+            "/repos/mangit".to_string(),
+            vec!["rust".to_string(), "cli".to_string()],
+            String::new(),
+        );
+        repo.language = Some("Rust".to_string());
+        repo.last_modified = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+        repo
+    }
+
+    fn render(template: &str) -> String {
+        Template::parse(template).unwrap().render(&repo(), 42.26)
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(render("{name}"), "mangit");
+        assert_eq!(render("{path}"), "/repos/mangit");
+        assert_eq!(render("{tags}"), "rust,cli");
+        assert_eq!(render("{tags:sep=|}"), "rust|cli");
+        assert_eq!(render("{tags:sep=, }"), "rust, cli");
+        assert_eq!(render("{language}"), "Rust");
+        assert_eq!(render("{last_modified}"), "2024-05-01 12:30");
+        assert_eq!(render("{score}"), "42.3");
+        assert_eq!(
+            render("{name}: {path} [{tags}]"),
+            "mangit: /repos/mangit [rust,cli]"
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        assert_eq!(render("{{{name}}}"), "{mangit}");
+        assert_eq!(render("{{name}}"), "{name}");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let err = Template::parse("{name} {stars}").unwrap_err();
+        assert!(err.to_string().contains("{stars}"));
+        assert!(Template::parse("{name").is_err());
+        assert!(Template::parse("name}").is_err());
+    }
+}
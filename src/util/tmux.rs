@@ -0,0 +1,78 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::process::Command;
+
+/// Where `open --tmux` opens a repo
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TmuxOpenMode {
+    #[default]
+    NewWindow,
+    /// Pane to the right of the current one
+    HSplit,
+    /// Pane below the current one
+    VSplit,
+}
+
+/// Whether mangit runs inside a tmux session
+pub fn inside_tmux() -> bool {
+    env::var_os("TMUX").is_some_and(|tmux| !tmux.is_empty())
+}
+
+/// Opens a new tmux window or pane with `path` as its working directory
+pub fn open_in_tmux(path: &str, mode: TmuxOpenMode) -> Result<()> {
+    let status = Command::new("tmux")
+        .args(tmux_args(path, mode))
+        .status()
+        .context("Failed to run tmux")?;
+    if !status.success() {
+        return Err(anyhow!("tmux failed to open {}", path));
+    }
+    Ok(())
+}
+
+/// Arguments for the tmux command that opens `path`
+fn tmux_args(path: &str, mode: TmuxOpenMode) -> Vec<&str> {
+    match mode {
+        TmuxOpenMode::NewWindow => vec!["new-window", "-c", path],
+        TmuxOpenMode::HSplit => vec!["split-window", "-h", "-c", path],
+        TmuxOpenMode::VSplit => vec!["split-window", "-v", "-c", path],
+    }
+}
+
+#[cfg(test)]
+mod tests_tmux {
+    use super::*;
+
+    #[test]
+    fn test_tmux_args() {
+        let path = "/home/me/src/my project";
+        assert_eq!(
+            tmux_args(path, TmuxOpenMode::NewWindow),
+            vec!["new-window", "-c", path]
+        );
+        assert_eq!(
+            tmux_args(path, TmuxOpenMode::HSplit),
+            vec!["split-window", "-h", "-c", path]
+        );
+        assert_eq!(
+            tmux_args(path, TmuxOpenMode::VSplit),
+            vec!["split-window", "-v", "-c", path]
+        );
+    }
+
+    #[test]
+    fn test_open_mode_config_names() {
+        let modes: Vec<TmuxOpenMode> =
+            serde_json::from_str(r#"["new-window", "h-split", "v-split"]"#).unwrap();
+        assert_eq!(
+            modes,
+            vec![
+                TmuxOpenMode::NewWindow,
+                TmuxOpenMode::HSplit,
+                TmuxOpenMode::VSplit
+            ]
+        );
+    }
+}